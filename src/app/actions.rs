@@ -1,6 +1,15 @@
-use std::path::PathBuf;
+use std::{path::PathBuf, time::SystemTime};
 
-use super::state::{ModalType, TabIdx};
+use super::{
+    config::tab_config::{SortEnum, SortKey},
+    dir_size::DirSize,
+    file_system::file_system_item::FileSystemItem,
+    find::{FindId, FindQuery, FindSearch},
+    grep::{GrepMatch, GrepQuery},
+    jobs::{ConflictResolution, Job, JobId, JobOutcome, JobProgress},
+    state::{AppError, ModalType, TabIdx},
+    sync::{SyncEntry, SyncOperation},
+};
 
 #[derive(Clone, Debug)]
 pub enum FileManagerActions {
@@ -11,6 +20,15 @@ pub enum FileManagerActions {
     Panel(PanelAction),
     Tab(TabAction),
     Search(SearchAction),
+    Trash(TrashAction),
+    Journal(JournalAction),
+    Job(JobAction),
+    DirSize(DirSizeAction),
+    Compare(CompareAction),
+    Sync(SyncAction),
+    Find(FindAction),
+    Grep(GrepAction),
+    Listing(ListingAction),
 }
 
 #[derive(Clone, Debug)]
@@ -37,10 +55,14 @@ pub enum SearchAction {
 #[derive(Clone, Debug)]
 pub enum AppAction {
     Exit,
-    ChildProgramClosed,
+    /// The program in `AppState::child_program` ended, `error` tells why it
+    /// failed to start or ended unsuccessfully.
+    ChildProgramClosed {
+        error: Option<AppError>,
+    },
     FocusLeft,
     FocusRight,
-    ShowModal(ModalType),
+    ShowModal(Box<ModalType>),
     CloseModal,
 }
 
@@ -62,6 +84,21 @@ pub enum TabAction {
         panel_side: PanelSide,
         path: PathBuf,
     },
+    OpenDiskUsage {
+        panel_side: PanelSide,
+        path: PathBuf,
+    },
+    /// Sorts the current tab of the panel by `key` alone.
+    SetSort {
+        panel_side: PanelSide,
+        key: SortKey,
+        order: SortEnum,
+    },
+    /// Switches the current tab of the panel between the names only and the
+    /// detail view.
+    ToggleDetailView {
+        panel_side: PanelSide,
+    },
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
@@ -85,18 +122,47 @@ impl PartialEq for PanelInfo {
 
 #[derive(Clone, Debug)]
 pub enum FileAction {
-    Delete { panel: PanelInfo },
-    Rename { from: PanelInfo, to: PanelInfo },
-    Copy { from: PanelInfo, to: PanelInfo },
-    Move { from: PanelInfo, to: PanelInfo },
-    Open { panel: PanelInfo },
-    Create { file_name: String, panel: PanelInfo },
+    Delete {
+        panel: PanelInfo,
+    },
+    DeletePermanently {
+        panel: PanelInfo,
+    },
+    Rename {
+        from: PanelInfo,
+        to: PanelInfo,
+    },
+    Copy {
+        from: PanelInfo,
+        to: PanelInfo,
+    },
+    Move {
+        from: PanelInfo,
+        to: PanelInfo,
+    },
+    Open {
+        panel: PanelInfo,
+    },
+    Create {
+        file_name: String,
+        panel: PanelInfo,
+    },
+    BulkRename {
+        panel: PanelInfo,
+        items: Vec<PathBuf>,
+    },
+    MultiRename {
+        panel: PanelInfo,
+        renames: Vec<(PathBuf, PathBuf)>,
+    },
 }
 
 #[derive(Clone, Debug)]
 pub enum DirectoryAction {
     DeleteWithContent { panel: PanelInfo },
+    DeleteWithContentPermanently { panel: PanelInfo },
     Delete { panel: PanelInfo, is_empty: bool },
+    DeletePermanently { panel: PanelInfo, is_empty: bool },
     Rename { from: PanelInfo, to: PanelInfo },
     Copy { from: PanelInfo, to: PanelInfo },
     Move { from: PanelInfo, to: PanelInfo },
@@ -104,6 +170,53 @@ pub enum DirectoryAction {
     Create { dir_name: String, panel: PanelInfo },
 }
 
+#[derive(Clone, Debug)]
+pub enum TrashAction {
+    Open { panel_side: PanelSide },
+    Restore { panel: PanelInfo },
+    Purge { panel: PanelInfo },
+}
+
+#[derive(Clone, Debug)]
+pub enum JournalAction {
+    Undo,
+    Redo,
+}
+
+#[derive(Clone, Debug)]
+pub enum JobAction {
+    Start {
+        job: Job,
+    },
+    Progress {
+        id: JobId,
+        progress: JobProgress,
+    },
+    Finished {
+        id: JobId,
+        outcome: JobOutcome,
+    },
+    Pause {
+        id: JobId,
+    },
+    Resume {
+        id: JobId,
+    },
+    Cancel {
+        id: JobId,
+    },
+    Conflict {
+        job: Job,
+    },
+    ResolveConflict {
+        resolution: ConflictResolution,
+        apply_to_all: bool,
+    },
+    ConflictsResolved {
+        ids: Vec<JobId>,
+    },
+}
+
 #[derive(Clone, Debug)]
 pub enum SymlinkAction {
     Delete {
@@ -118,3 +231,126 @@ pub enum SymlinkAction {
         panel: PanelInfo,
     },
 }
+
+#[derive(Clone, Debug)]
+pub enum DirSizeAction {
+    Scan {
+        paths: Vec<PathBuf>,
+        force: bool,
+    },
+    Started {
+        paths: Vec<PathBuf>,
+    },
+    Calculated {
+        path: PathBuf,
+        modified: SystemTime,
+        size: DirSize,
+    },
+    Failed {
+        path: PathBuf,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum CompareAction {
+    Start {
+        by_content: bool,
+    },
+    Stop,
+    ContentCompared {
+        left: PathBuf,
+        right: PathBuf,
+        left_modified: SystemTime,
+        right_modified: SystemTime,
+        equal: bool,
+    },
+    SelectDifferent {
+        panel_side: PanelSide,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum SyncAction {
+    Compare {
+        left_root: PathBuf,
+        right_root: PathBuf,
+    },
+    Compared {
+        left_root: PathBuf,
+        right_root: PathBuf,
+        entries: Result<Vec<SyncEntry>, AppError>,
+    },
+    SetOperation {
+        index: usize,
+        operation: SyncOperation,
+    },
+    Run,
+    Cancel,
+}
+
+#[derive(Clone, Debug)]
+pub enum FindAction {
+    Start {
+        panel_side: PanelSide,
+        root: PathBuf,
+        query: FindQuery,
+    },
+    Started {
+        panel_side: PanelSide,
+        root: PathBuf,
+        query: FindQuery,
+        search: FindSearch,
+    },
+    Found {
+        search: FindSearch,
+        paths: Vec<PathBuf>,
+    },
+    Finished {
+        id: FindId,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum ListingAction {
+    Start {
+        panel_side: PanelSide,
+        path: PathBuf,
+    },
+    Started {
+        panel_side: PanelSide,
+        path: PathBuf,
+        search: FindSearch,
+    },
+    Listed {
+        search: FindSearch,
+        items: Vec<FileSystemItem>,
+    },
+    Finished {
+        id: FindId,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub enum GrepAction {
+    Start {
+        root: PathBuf,
+        query: GrepQuery,
+    },
+    Started {
+        root: PathBuf,
+        query: GrepQuery,
+        search: FindSearch,
+    },
+    Found {
+        search: FindSearch,
+        matches: Vec<GrepMatch>,
+    },
+    Finished {
+        id: FindId,
+    },
+    /// Opens the file of the match at `index` at its line.
+    Open {
+        index: usize,
+    },
+    Close,
+}
@@ -0,0 +1,200 @@
+use std::{
+    collections::HashSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    file_system::{functions::first_free_path, FileSystem},
+    journal::JournalOperation,
+};
+
+/// Renaming of several items of one directory by editing their names in a
+/// text file, one name per line, the way `vidir` does it.
+#[derive(Clone, Debug)]
+pub struct BulkRename {
+    pub dir: PathBuf,
+    pub paths: Vec<PathBuf>,
+    /// Temporary file holding the names while the editor is open.
+    pub list_path: PathBuf,
+}
+
+impl BulkRename {
+    /// Writes the names of `paths` to a new file in the temporary directory.
+    pub fn start<TFileSystem: FileSystem>(
+        file_system: &mut TFileSystem,
+        dir: PathBuf,
+        paths: Vec<PathBuf>,
+    ) -> io::Result<Self> {
+        let mut names = String::new();
+        for path in paths.iter() {
+            let name = file_name(path)?;
+            if name.contains('\n') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} has a line break in its name", path.display()),
+                ));
+            }
+            names.push_str(&name);
+            names.push('\n');
+        }
+
+        let temp_dir = file_system.temp_dir();
+        let mut counter = 0;
+        let list_path = loop {
+            let list_path = temp_dir.join(format!(
+                "rufus-rename-{}-{}.txt",
+                std::process::id(),
+                counter
+            ));
+            match file_system.write_new_file(&list_path, names.as_bytes()) {
+                Ok(_) => break list_path,
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => counter += 1,
+                Err(err) => return Err(err),
+            }
+        };
+
+        Ok(BulkRename {
+            dir,
+            paths,
+            list_path,
+        })
+    }
+
+    /// Reads the edited names and returns the renames they ask for, leaving
+    /// out the unchanged items. Nothing is returned unless every line is a
+    /// valid name which no other item keeps or gets.
+    pub fn read_renames<TFileSystem: FileSystem>(
+        &self,
+        file_system: &TFileSystem,
+    ) -> io::Result<Vec<(PathBuf, PathBuf)>> {
+        let content = file_system.read_to_string(&self.list_path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("can't read the names from {}", self.list_path.display()),
+            )
+        })?;
+        let names: Vec<&str> = content
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .collect();
+        if names.len() != self.paths.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "expected {} names but found {}, lines can't be added or removed",
+                    self.paths.len(),
+                    names.len()
+                ),
+            ));
+        }
+
+        let mut used_names = HashSet::new();
+        for name in names.iter() {
+            if !is_valid_name(name) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("\"{}\" is not a valid name", name),
+                ));
+            }
+            if !used_names.insert(*name) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is given to more than one item", name),
+                ));
+            }
+        }
+
+        let renames: Vec<(PathBuf, PathBuf)> = self
+            .paths
+            .iter()
+            .zip(names.iter())
+            .map(|(from, name)| (from.clone(), self.dir.join(name)))
+            .filter(|(from, to)| from != to)
+            .collect();
+        for (_, to) in renames.iter() {
+            let freed = renames.iter().any(|(from, _)| from == to);
+            if !freed && file_system.exist(to) {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", to.display()),
+                ));
+            }
+        }
+
+        Ok(renames)
+    }
+}
+
+/// Applies renames checked by `BulkRename::read_renames`. An item is only
+/// renamed once the item holding its new name has moved away, and swaps and
+/// longer cycles are broken by parking one item under a temporary name.
+/// Returns the renames done, also when a later one fails.
+pub fn apply_renames<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    mut renames: Vec<(PathBuf, PathBuf)>,
+) -> (Vec<JournalOperation>, io::Result<()>) {
+    let mut done = Vec::new();
+    while !renames.is_empty() {
+        let free = renames
+            .iter()
+            .position(|(_, to)| !renames.iter().any(|(from, _)| from == to));
+        let (from, to) = match free {
+            Some(idx) => renames.remove(idx),
+            None => {
+                let parked = parking_path(file_system, &renames[0].0);
+                let from = std::mem::replace(&mut renames[0].0, parked.clone());
+                (from, parked)
+            }
+        };
+
+        // A rename silently replaces files, so a target which appeared
+        // after the names were checked stops the whole thing.
+        let result = if file_system.exist(&to) {
+            Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "the target already exists",
+            ))
+        } else {
+            file_system.rename_item(&from, &to)
+        };
+        if let Err(err) = result {
+            let err = io::Error::new(
+                err.kind(),
+                format!("{} -> {}: {}", from.display(), to.display(), err),
+            );
+            return (done, Err(err));
+        }
+        done.push(JournalOperation::Rename { from, to });
+    }
+
+    (done, Ok(()))
+}
+
+/// Whether `name` can be used as the name of an item in a directory.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(&['/', '\0'][..])
+}
+
+fn parking_path<TFileSystem: FileSystem>(file_system: &TFileSystem, path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    first_free_path(
+        file_system,
+        &path.with_file_name(format!(".{}.rufus-rename", name)),
+    )
+}
+
+fn file_name(path: &Path) -> io::Result<String> {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.to_string())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} has no name which can be edited", path.display()),
+            )
+        })
+}
@@ -0,0 +1,200 @@
+use tui::{
+    style::{Modifier, Style},
+    text::{Span, Spans},
+};
+
+use crate::core::config::Column;
+
+use super::file_system::{
+    file_system_item::FileSystemItem,
+    functions::{format_permissions, format_size},
+};
+
+/// Cells taken by the type marker in front of every row, `/` for
+/// directories and `@` for symlinks.
+const MARKER_WIDTH: usize = 3;
+const COLUMN_GAP: usize = 2;
+/// Columns are dropped from the end of the list before the name gets
+/// narrower than this.
+const MIN_NAME_WIDTH: usize = 12;
+const MIN_TARGET_WIDTH: usize = 8;
+const ELLIPSIS: char = '…';
+
+impl Column {
+    /// Width of the columns which do not grow with the panel.
+    fn fixed_width(self) -> Option<usize> {
+        match self {
+            Column::Size => Some(10),
+            Column::Modified => Some(16),
+            Column::Permissions => Some(9),
+            Column::Owner => Some(17),
+            Column::Inode => Some(10),
+            Column::Links => Some(5),
+            Column::Name | Column::Target => None,
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Column::Name => "Name",
+            Column::Size => "Size",
+            Column::Modified => "Modified",
+            Column::Permissions => "Perms",
+            Column::Owner => "Owner",
+            Column::Inode => "Inode",
+            Column::Links => "Links",
+            Column::Target => "Target",
+        }
+    }
+
+    fn is_right_aligned(self) -> bool {
+        matches!(self, Column::Size | Column::Inode | Column::Links)
+    }
+
+    fn cell(self, item: &FileSystemItem) -> String {
+        match self {
+            Column::Name => item.get_name(),
+            Column::Size => match item {
+                FileSystemItem::Directory(dir) if dir.content.is_none() => String::from("<DIR>"),
+                _ => format_size(item.get_size()),
+            },
+            Column::Modified => item.get_modified().format("%Y-%m-%d %H:%M").to_string(),
+            Column::Permissions => format_permissions(item.get_mode()),
+            Column::Owner => format!("{}:{}", item.get_username(), item.get_groupname()),
+            Column::Inode => item.get_inode().to_string(),
+            Column::Links => item.get_nlink().to_string(),
+            Column::Target => match item {
+                FileSystemItem::Symlink(symlink) => {
+                    symlink.get_target().to_string_lossy().to_string()
+                }
+                _ => String::new(),
+            },
+        }
+    }
+}
+
+/// The columns of the detail view which fit in a row and their widths.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnLayout {
+    columns: Vec<(Column, usize)>,
+}
+
+impl ColumnLayout {
+    /// Fits `columns` into rows `width` cells wide. While the name would get
+    /// narrower than its minimum the last other column is dropped, the name
+    /// and the symlink target share what the fixed columns leave.
+    pub fn fit(columns: &[Column], width: usize) -> Self {
+        let mut shown: Vec<Column> = Vec::new();
+        for &column in columns {
+            if !shown.contains(&column) {
+                shown.push(column);
+            }
+        }
+        if !shown.contains(&Column::Name) {
+            shown.insert(0, Column::Name);
+        }
+
+        let others_width = |shown: &[Column]| -> usize {
+            shown
+                .iter()
+                .filter(|&&column| column != Column::Name)
+                .map(|column| COLUMN_GAP + column.fixed_width().unwrap_or(MIN_TARGET_WIDTH))
+                .sum()
+        };
+        while MARKER_WIDTH + MIN_NAME_WIDTH + others_width(&shown) > width {
+            match shown.iter().rposition(|&column| column != Column::Name) {
+                Some(idx) => shown.remove(idx),
+                None => break,
+            };
+        }
+
+        let fixed: usize = shown
+            .iter()
+            .filter_map(|column| column.fixed_width())
+            .map(|column_width| COLUMN_GAP + column_width)
+            .sum();
+        let has_target = shown.contains(&Column::Target);
+        let free = width
+            .saturating_sub(MARKER_WIDTH + fixed)
+            .saturating_sub(if has_target { COLUMN_GAP } else { 0 });
+        let target_width = if has_target {
+            (free / 3).max(MIN_TARGET_WIDTH)
+        } else {
+            0
+        };
+        let name_width = free.saturating_sub(target_width).max(1);
+
+        ColumnLayout {
+            columns: shown
+                .into_iter()
+                .map(|column| match column {
+                    Column::Name => (column, name_width),
+                    Column::Target => (column, target_width),
+                    _ => (column, column.fixed_width().unwrap_or(0)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Titles of the shown columns, `indent` is the width of the list
+    /// highlight symbol.
+    pub fn header(&self, indent: usize) -> Spans<'static> {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        let mut spans = vec![Span::from(" ".repeat(indent + MARKER_WIDTH))];
+        for (idx, &(column, width)) in self.columns.iter().enumerate() {
+            if idx > 0 {
+                spans.push(Span::from(" ".repeat(COLUMN_GAP)));
+            }
+            spans.push(Span::styled(align(column, column.title(), width), style));
+        }
+        Spans::from(spans)
+    }
+
+    /// A row of the detail view. The name gets a span of its own, without
+    /// the padding, so the filter can highlight it.
+    pub fn row(&self, item: &FileSystemItem) -> Spans<'static> {
+        let marker = match item {
+            FileSystemItem::Directory(_) => "  /",
+            FileSystemItem::Symlink(_) => "  @",
+            _ => "   ",
+        };
+        let mut spans = vec![Span::from(marker)];
+        for (idx, &(column, width)) in self.columns.iter().enumerate() {
+            if idx > 0 {
+                spans.push(Span::from(" ".repeat(COLUMN_GAP)));
+            }
+            let cell = column.cell(item);
+            if column == Column::Name {
+                let name = truncate(&cell, width);
+                let padding = width.saturating_sub(name.chars().count());
+                spans.push(Span::from(name));
+                spans.push(Span::from(" ".repeat(padding)));
+            } else {
+                spans.push(Span::from(align(column, &cell, width)));
+            }
+        }
+        Spans::from(spans)
+    }
+}
+
+/// Pads `text` to `width`, cutting it with an ellipsis when it is longer.
+fn align(column: Column, text: &str, width: usize) -> String {
+    let text = truncate(text, width);
+    if column.is_right_aligned() {
+        format!("{:>width$}", text, width = width)
+    } else {
+        format!("{:<width$}", text, width = width)
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut truncated: String = text.chars().take(width - 1).collect();
+    truncated.push(ELLIPSIS);
+    truncated
+}
@@ -0,0 +1,214 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::Hasher,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    thread,
+    time::SystemTime,
+};
+
+use super::{
+    actions::{CompareAction, FileManagerActions},
+    file_system::{file_system_item::FileSystemItem, FileSystem},
+};
+
+/// How an item differs from the item of the same name in the other panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difference {
+    /// There is no item of that name on the other side.
+    Unique,
+    Newer,
+    Older,
+    /// Not the same, but not newer or older either.
+    Differs,
+}
+
+/// Result of comparing the contents of two files, valid while neither of
+/// them is modified.
+#[derive(Clone, Debug)]
+pub struct ContentCheck {
+    pub right: PathBuf,
+    pub left_modified: SystemTime,
+    pub right_modified: SystemTime,
+    pub equal: bool,
+}
+
+/// Comparison of the directories shown by the current tabs of both panels.
+/// It ends when either of the tabs shows another directory.
+#[derive(Clone, Debug)]
+pub struct Comparison {
+    pub by_content: bool,
+    pub left_path: PathBuf,
+    pub right_path: PathBuf,
+    /// Keyed by the path of the left file.
+    pub content_checks: HashMap<PathBuf, ContentCheck>,
+}
+
+impl Comparison {
+    pub fn new(by_content: bool, left_path: PathBuf, right_path: PathBuf) -> Self {
+        Comparison {
+            by_content,
+            left_path,
+            right_path,
+            content_checks: HashMap::new(),
+        }
+    }
+
+    /// Matches the items by name and returns the differences of the items on
+    /// the left and on the right. Items which are the same are left out.
+    ///
+    /// Files of the same size are the same when their contents were found
+    /// equal, otherwise items are compared by size and by their modification
+    /// time, to the second since some file systems don't keep more.
+    pub fn differences(
+        &self,
+        left: &[FileSystemItem],
+        right: &[FileSystemItem],
+    ) -> (HashMap<PathBuf, Difference>, HashMap<PathBuf, Difference>) {
+        let right_by_name: HashMap<String, &FileSystemItem> =
+            right.iter().map(|item| (item.get_name(), item)).collect();
+        let left_names: HashSet<String> = left.iter().map(|item| item.get_name()).collect();
+
+        let mut left_differences = HashMap::new();
+        let mut right_differences = HashMap::new();
+        for item in left.iter() {
+            let other = match right_by_name.get(&item.get_name()) {
+                Some(other) => other,
+                None => {
+                    left_differences.insert(item.get_path(), Difference::Unique);
+                    continue;
+                }
+            };
+            if let Some((left_difference, right_difference)) = self.compare(item, other) {
+                left_differences.insert(item.get_path(), left_difference);
+                right_differences.insert(other.get_path(), right_difference);
+            }
+        }
+        for item in right.iter() {
+            if !left_names.contains(&item.get_name()) {
+                right_differences.insert(item.get_path(), Difference::Unique);
+            }
+        }
+
+        (left_differences, right_differences)
+    }
+
+    fn compare(
+        &self,
+        left: &FileSystemItem,
+        right: &FileSystemItem,
+    ) -> Option<(Difference, Difference)> {
+        match (left, right) {
+            (FileSystemItem::Directory(_), FileSystemItem::Directory(_)) => return None,
+            (FileSystemItem::File(_), FileSystemItem::File(_)) => {}
+            (FileSystemItem::Symlink(_), FileSystemItem::Symlink(_)) => {}
+            _ => return Some((Difference::Differs, Difference::Differs)),
+        }
+
+        let left_modified = left.get_modified().timestamp();
+        let right_modified = right.get_modified().timestamp();
+        let same = match self.content_check(left, right) {
+            Some(check) => check.equal,
+            None => left.get_size() == right.get_size() && left_modified == right_modified,
+        };
+        if same {
+            None
+        } else if left_modified > right_modified {
+            Some((Difference::Newer, Difference::Older))
+        } else if left_modified < right_modified {
+            Some((Difference::Older, Difference::Newer))
+        } else {
+            Some((Difference::Differs, Difference::Differs))
+        }
+    }
+
+    fn content_check(
+        &self,
+        left: &FileSystemItem,
+        right: &FileSystemItem,
+    ) -> Option<&ContentCheck> {
+        self.content_checks.get(&left.get_path()).filter(|check| {
+            check.right == right.get_path()
+                && check.left_modified == SystemTime::from(left.get_modified())
+                && check.right_modified == SystemTime::from(right.get_modified())
+        })
+    }
+}
+
+/// Pairs of files with the same name and size, which only their contents
+/// can tell apart.
+pub fn content_pairs(left: &[FileSystemItem], right: &[FileSystemItem]) -> Vec<(PathBuf, PathBuf)> {
+    let right_files: HashMap<String, &FileSystemItem> = right
+        .iter()
+        .filter(|item| item.is_file())
+        .map(|item| (item.get_name(), item))
+        .collect();
+    left.iter()
+        .filter(|item| item.is_file())
+        .filter_map(|item| {
+            right_files
+                .get(&item.get_name())
+                .filter(|other| other.get_size() == item.get_size())
+                .map(|other| (item.get_path(), other.get_path()))
+        })
+        .collect()
+}
+
+/// Hashes the contents of `pairs` on a worker thread and sends whether they
+/// are equal pair by pair. Files which can't be read count as different.
+pub fn compare_contents<TFileSystem: Clone + FileSystem + Send + 'static>(
+    file_system: TFileSystem,
+    pairs: Vec<(PathBuf, PathBuf)>,
+    sender: Sender<FileManagerActions>,
+) {
+    thread::spawn(move || {
+        for (left, right) in pairs {
+            let modified = |path: &Path| {
+                file_system
+                    .metadata(path)
+                    .map(|metadata| metadata.modified)
+                    .ok()
+            };
+            let (left_modified, right_modified) = match (modified(&left), modified(&right)) {
+                (Some(left_modified), Some(right_modified)) => (left_modified, right_modified),
+                _ => continue,
+            };
+            let equal = match (
+                content_hash(&file_system, &left),
+                content_hash(&file_system, &right),
+            ) {
+                (Ok(left_hash), Ok(right_hash)) => left_hash == right_hash,
+                _ => false,
+            };
+            let action = CompareAction::ContentCompared {
+                left,
+                right,
+                left_modified,
+                right_modified,
+                equal,
+            };
+            if sender.send(FileManagerActions::Compare(action)).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+fn content_hash<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    path: &Path,
+) -> io::Result<u64> {
+    let mut file = file_system.open_file(path)?;
+    let mut hasher = DefaultHasher::new();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = match file.read(&mut buffer) {
+            Ok(0) => return Ok(hasher.finish()),
+            Ok(read) => read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        hasher.write(&buffer[..read]);
+    }
+}
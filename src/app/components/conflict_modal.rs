@@ -0,0 +1,235 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::{FileManagerActions, JobAction},
+        file_system::FileSystem,
+        jobs::{ConflictResolution, Job},
+        state::AppState,
+    },
+    core::{
+        events::Event,
+        store::Store,
+        ui::{component::Component, component_base::ComponentBase},
+    },
+};
+use std::{fmt::Debug, marker::PhantomData};
+
+use super::{create_modal_layout, ModalStyle};
+
+const RESOLUTIONS: [ConflictResolution; 4] = [
+    ConflictResolution::Overwrite,
+    ConflictResolution::OverwriteIfNewer,
+    ConflictResolution::Rename,
+    ConflictResolution::Skip,
+];
+
+#[derive(Clone, Default)]
+pub struct ConflictModalComponentProps {
+    job: Option<Job>,
+    conflicts_count: usize,
+    list_selector: String,
+    modal_style: ModalStyle,
+}
+
+impl ConflictModalComponentProps {
+    pub fn new(
+        job: Option<Job>,
+        conflicts_count: usize,
+        list_selector: String,
+        modal_style: ModalStyle,
+    ) -> Self {
+        ConflictModalComponentProps {
+            job,
+            conflicts_count,
+            list_selector,
+            modal_style,
+        }
+    }
+
+    /// The "apply to all" toggle is only offered when other conflicts wait
+    /// behind the displayed one.
+    fn items_count(&self) -> usize {
+        if self.conflicts_count > 1 {
+            RESOLUTIONS.len() + 1
+        } else {
+            RESOLUTIONS.len()
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct ConflictModalComponentState {
+    list_state: ListState,
+    apply_to_all: bool,
+}
+
+pub struct ConflictModalComponent<TFileSystem: Clone + Debug + Default + FileSystem> {
+    base: ComponentBase<ConflictModalComponentProps, ConflictModalComponentState>,
+    _marker: PhantomData<TFileSystem>,
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem> ConflictModalComponent<TFileSystem> {
+    pub fn new(props: ConflictModalComponentProps) -> Self {
+        let mut state = ConflictModalComponentState::default();
+        state.list_state.select(Some(0));
+        ConflictModalComponent {
+            base: ComponentBase::new(Some(props), Some(state)),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Shows the next queued conflict while keeping the current selection.
+    pub fn update(&mut self, props: ConflictModalComponentProps) {
+        let mut state = self.base.get_state().unwrap_or_default();
+        let selected = state.list_state.selected().unwrap_or(0);
+        state
+            .list_state
+            .select(Some(selected.min(props.items_count() - 1)));
+        if props.conflicts_count <= 1 {
+            state.apply_to_all = false;
+        }
+        self.base = ComponentBase::new(Some(props), Some(state));
+    }
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem>
+    Component<Event, AppState<TFileSystem>, FileManagerActions>
+    for ConflictModalComponent<TFileSystem>
+{
+    fn handle_event(
+        &mut self,
+        event: Event,
+        store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    ) -> bool {
+        let state = store.get_state();
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+        if let Event::Keyboard(key_evt) = event {
+            if state.config.keyboard_cfg.move_up.is_pressed(key_evt) {
+                let next_item = match local_state.list_state.selected() {
+                    Some(current) if current > 0 => current - 1,
+                    _ => props.items_count() - 1,
+                };
+                self.base.set_state(|mut current_state| {
+                    current_state.list_state.select(Some(next_item));
+                    current_state
+                });
+                return true;
+            }
+
+            if state.config.keyboard_cfg.move_down.is_pressed(key_evt) {
+                let next_item = match local_state.list_state.selected() {
+                    Some(current) if current + 1 < props.items_count() => current + 1,
+                    _ => 0,
+                };
+                self.base.set_state(|mut current_state| {
+                    current_state.list_state.select(Some(next_item));
+                    current_state
+                });
+                return true;
+            }
+
+            if state.config.keyboard_cfg.accept.is_pressed(key_evt) {
+                if let Some(selected) = local_state.list_state.selected() {
+                    match RESOLUTIONS.get(selected) {
+                        Some(resolution) => {
+                            store.dispatch(FileManagerActions::Job(JobAction::ResolveConflict {
+                                resolution: *resolution,
+                                apply_to_all: local_state.apply_to_all,
+                            }))
+                        }
+                        None => self
+                            .base
+                            .set_state(|current_state| ConflictModalComponentState {
+                                apply_to_all: !current_state.apply_to_all,
+                                ..current_state
+                            }),
+                    }
+                }
+                return true;
+            }
+
+            if state.config.keyboard_cfg.close.is_pressed(key_evt) {
+                store.dispatch(FileManagerActions::Job(JobAction::ResolveConflict {
+                    resolution: ConflictResolution::Skip,
+                    apply_to_all: true,
+                }));
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn render<TBackend: Backend>(&self, frame: &mut Frame<TBackend>, area: Option<Rect>) {
+        let layout = if let Some(area) = area {
+            create_modal_layout(50, 30, area)
+        } else {
+            create_modal_layout(50, 30, frame.size())
+        };
+
+        let props = self.base.get_props().unwrap();
+        let mut local_state = self.base.get_state().unwrap();
+
+        let mut items: Vec<ListItem> = RESOLUTIONS
+            .iter()
+            .map(|resolution| ListItem::new(Spans::from(vec![Span::from(resolution.to_string())])))
+            .collect();
+        if props.conflicts_count > 1 {
+            items.push(ListItem::new(Spans::from(vec![Span::from(format!(
+                "[{}] Apply to all {} conflicts",
+                if local_state.apply_to_all { "x" } else { " " },
+                props.conflicts_count
+            ))])));
+        }
+
+        let block = Block::default()
+            .title(Spans::from(vec![
+                Span::from("| "),
+                Span::from("Target already exists"),
+                Span::from(" |"),
+            ]))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(props.modal_style.border_color))
+            .border_type(tui::widgets::BorderType::Thick)
+            .style(Style::default().bg(tui::style::Color::Reset));
+
+        let inner_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(block.inner(layout));
+
+        let paths = match props.job {
+            Some(ref job) => vec![
+                Spans::from(vec![Span::from(format!(
+                    "{} {}",
+                    job.kind,
+                    job.from.to_string_lossy()
+                ))]),
+                Spans::from(vec![Span::from(format!("to {}", job.to.to_string_lossy()))]),
+            ],
+            None => Vec::new(),
+        };
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(props.modal_style.selected_element_background)
+                    .fg(props.modal_style.selected_element_foreground),
+            )
+            .highlight_symbol(props.list_selector.as_str());
+
+        frame.render_widget(Clear, layout);
+        frame.render_widget(block, layout);
+        frame.render_widget(Paragraph::new(paths), inner_layout[0]);
+        frame.render_stateful_widget(list, inner_layout[1], &mut local_state.list_state);
+    }
+}
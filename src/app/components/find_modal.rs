@@ -0,0 +1,279 @@
+use std::{fmt::Debug, marker::PhantomData, path::PathBuf, time::SystemTime};
+
+use crossterm::event::KeyCode;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::{AppAction, FileManagerActions, FindAction, PanelSide},
+        file_system::FileSystem,
+        find::FindQuery,
+        state::AppState,
+    },
+    core::{
+        events::Event,
+        store::Store,
+        ui::{component::Component, component_base::ComponentBase},
+    },
+};
+
+use super::{create_modal_layout, ModalStyle};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Name,
+    NameSyntax,
+    Size,
+    Modified,
+    Owner,
+    Type,
+}
+
+const FIELDS: [Field; 6] = [
+    Field::Name,
+    Field::NameSyntax,
+    Field::Size,
+    Field::Modified,
+    Field::Owner,
+    Field::Type,
+];
+
+impl Field {
+    fn label(self) -> &'static str {
+        match self {
+            Field::Name => "Name",
+            Field::NameSyntax => "Name syntax",
+            Field::Size => "Size (k/M/G)",
+            Field::Modified => "Modified",
+            Field::Owner => "Owner",
+            Field::Type => "Type",
+        }
+    }
+
+    fn is_choice(self) -> bool {
+        matches!(self, Field::NameSyntax | Field::Type)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct FindModalComponentProps {
+    panel_side: Option<PanelSide>,
+    path: PathBuf,
+    modal_style: ModalStyle,
+}
+
+impl FindModalComponentProps {
+    pub fn new(panel_side: Option<PanelSide>, path: PathBuf, modal_style: ModalStyle) -> Self {
+        FindModalComponentProps {
+            panel_side,
+            path,
+            modal_style,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct FindModalComponentState {
+    query: FindQuery,
+    focused_field: usize,
+}
+
+pub struct FindModalComponent<TFileSystem: Clone + Debug + Default + FileSystem> {
+    base: ComponentBase<FindModalComponentProps, FindModalComponentState>,
+    _marker: PhantomData<TFileSystem>,
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem> FindModalComponent<TFileSystem> {
+    pub fn with_props(props: FindModalComponentProps) -> Self {
+        FindModalComponent {
+            base: ComponentBase::new(Some(props), Some(FindModalComponentState::default())),
+            _marker: PhantomData,
+        }
+    }
+
+    fn edit_query(&mut self, edit: impl Fn(&mut FindQuery, Field)) {
+        self.base.set_state(|mut current_state| {
+            edit(
+                &mut current_state.query,
+                FIELDS[current_state.focused_field],
+            );
+            current_state
+        });
+    }
+
+    fn focus_field(&mut self, forward: bool) {
+        self.base.set_state(|mut current_state| {
+            current_state.focused_field = if forward {
+                (current_state.focused_field + 1) % FIELDS.len()
+            } else {
+                (current_state.focused_field + FIELDS.len() - 1) % FIELDS.len()
+            };
+            current_state
+        });
+    }
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem>
+    Component<Event, AppState<TFileSystem>, FileManagerActions>
+    for FindModalComponent<TFileSystem>
+{
+    fn handle_event(
+        &mut self,
+        event: Event,
+        store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    ) -> bool {
+        let state = store.get_state();
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+        if let Event::Keyboard(key_evt) = event {
+            if state.config.keyboard_cfg.accept.is_pressed(key_evt) {
+                if local_state.query.criteria(SystemTime::now()).is_ok() {
+                    store.dispatch(FileManagerActions::App(AppAction::CloseModal));
+                    store.dispatch(FileManagerActions::Find(FindAction::Start {
+                        panel_side: props.panel_side.unwrap(),
+                        root: props.path,
+                        query: local_state.query,
+                    }));
+                }
+                return true;
+            }
+
+            if state.config.keyboard_cfg.close.is_pressed(key_evt) {
+                store.dispatch(FileManagerActions::App(AppAction::CloseModal));
+                return true;
+            }
+
+            let on_choice = FIELDS[local_state.focused_field].is_choice();
+            match key_evt.code {
+                KeyCode::Tab | KeyCode::Down => self.focus_field(true),
+                KeyCode::BackTab | KeyCode::Up => self.focus_field(false),
+                KeyCode::Right | KeyCode::Char(' ') if on_choice => {
+                    self.edit_query(|query, field| match field {
+                        Field::NameSyntax => query.name_syntax = query.name_syntax.next(),
+                        Field::Type => query.item_type = query.item_type.next(),
+                        _ => {}
+                    })
+                }
+                KeyCode::Left if on_choice => self.edit_query(|query, field| match field {
+                    Field::NameSyntax => query.name_syntax = query.name_syntax.prev(),
+                    Field::Type => query.item_type = query.item_type.prev(),
+                    _ => {}
+                }),
+                KeyCode::Char(c) => self.edit_query(|query, field| {
+                    if let Some(text) = field_text(query, field) {
+                        text.push(c);
+                    }
+                }),
+                KeyCode::Backspace => self.edit_query(|query, field| {
+                    if let Some(text) = field_text(query, field) {
+                        text.pop();
+                    }
+                }),
+                _ => return false,
+            };
+            return true;
+        }
+
+        false
+    }
+
+    fn render<TBackend: Backend>(&self, frame: &mut Frame<TBackend>, area: Option<Rect>) {
+        let layout = if let Some(area) = area {
+            create_modal_layout(70, 40, area)
+        } else {
+            create_modal_layout(70, 40, frame.size())
+        };
+
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+
+        let block = Block::default()
+            .title(Spans::from(vec![
+                Span::from("| "),
+                Span::from(format!("Find in {}", props.path.to_string_lossy())),
+                Span::from(" |"),
+            ]))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(props.modal_style.border_color))
+            .border_type(tui::widgets::BorderType::Thick)
+            .style(Style::default().bg(Color::Reset));
+
+        let inner_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(FIELDS.len() as u16 + 1),
+                    Constraint::Min(0),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(block.inner(layout));
+
+        let query = &local_state.query;
+        let fields: Vec<Spans> = FIELDS
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let value = match field {
+                    Field::Name => query.name.clone(),
+                    Field::NameSyntax => format!("< {} >", query.name_syntax),
+                    Field::Size => query.size.clone(),
+                    Field::Modified => query.modified.clone(),
+                    Field::Owner => query.owner.clone(),
+                    Field::Type => format!("< {} >", query.item_type),
+                };
+                let style = if idx == local_state.focused_field {
+                    Style::default()
+                        .bg(props.modal_style.selected_element_background)
+                        .fg(props.modal_style.selected_element_foreground)
+                } else {
+                    Style::default()
+                };
+                Spans::from(vec![
+                    Span::from(format!("{:>13}: ", field.label())),
+                    Span::styled(value, style),
+                ])
+            })
+            .collect();
+
+        let help = vec![
+            Spans::from(vec![Span::styled(
+                "Globs: *.rs;*.toml  Sizes: 10k..1M  Modified: 7d.. or 2024-01-01..2024-02-01",
+                Style::default().fg(Color::DarkGray),
+            )]),
+            Spans::from(vec![Span::styled(
+                "Ranges are FROM..TO with either end left out, ages are s/m/h/d/w ago",
+                Style::default().fg(Color::DarkGray),
+            )]),
+        ];
+
+        let status = match query.criteria(SystemTime::now()) {
+            Ok(_) => Span::from("enter searches, esc cancels"),
+            Err(err) => Span::styled(err, Style::default().fg(Color::Red)),
+        };
+
+        frame.render_widget(Clear, layout);
+        frame.render_widget(block, layout);
+        frame.render_widget(Paragraph::new(fields), inner_layout[0]);
+        frame.render_widget(Paragraph::new(help), inner_layout[1]);
+        frame.render_widget(Paragraph::new(Spans::from(vec![status])), inner_layout[2]);
+    }
+}
+
+fn field_text(query: &mut FindQuery, field: Field) -> Option<&mut String> {
+    match field {
+        Field::Name => Some(&mut query.name),
+        Field::Size => Some(&mut query.size),
+        Field::Modified => Some(&mut query.modified),
+        Field::Owner => Some(&mut query.owner),
+        Field::NameSyntax | Field::Type => None,
+    }
+}
@@ -0,0 +1,366 @@
+use std::{fmt::Debug, marker::PhantomData, path::PathBuf};
+
+use crossterm::event::KeyCode;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::{FileManagerActions, GrepAction},
+        file_system::FileSystem,
+        grep::{GrepMatch, GrepQuery, GrepResults},
+        state::AppState,
+    },
+    core::{
+        events::Event,
+        store::Store,
+        ui::{component::Component, component_base::ComponentBase},
+    },
+};
+
+use super::{create_modal_layout, ModalStyle};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Pattern,
+    Syntax,
+    IgnoreCase,
+}
+
+const FIELDS: [Field; 3] = [Field::Pattern, Field::Syntax, Field::IgnoreCase];
+
+impl Field {
+    fn label(self) -> &'static str {
+        match self {
+            Field::Pattern => "Pattern",
+            Field::Syntax => "Syntax",
+            Field::IgnoreCase => "Ignore case",
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct GrepModalComponentProps {
+    path: PathBuf,
+    grep: Option<GrepResults>,
+    list_selector: String,
+    modal_style: ModalStyle,
+}
+
+impl GrepModalComponentProps {
+    pub fn new(
+        path: PathBuf,
+        grep: Option<GrepResults>,
+        list_selector: String,
+        modal_style: ModalStyle,
+    ) -> Self {
+        GrepModalComponentProps {
+            path,
+            grep,
+            list_selector,
+            modal_style,
+        }
+    }
+
+    fn matches(&self) -> &[GrepMatch] {
+        self.grep
+            .as_ref()
+            .map(|grep| grep.matches.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct GrepModalComponentState {
+    query: GrepQuery,
+    focused_field: usize,
+    list_state: ListState,
+}
+
+pub struct GrepModalComponent<TFileSystem: Clone + Debug + Default + FileSystem> {
+    base: ComponentBase<GrepModalComponentProps, GrepModalComponentState>,
+    _marker: PhantomData<TFileSystem>,
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem> GrepModalComponent<TFileSystem> {
+    pub fn new(props: GrepModalComponentProps) -> Self {
+        let mut state = GrepModalComponentState::default();
+        state.list_state.select(Some(0));
+        GrepModalComponent {
+            base: ComponentBase::new(Some(props), Some(state)),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Shows the matches found so far while keeping the typed query and the
+    /// current selection.
+    pub fn update(&mut self, props: GrepModalComponentProps) {
+        let state = self.base.get_state().unwrap_or_default();
+        self.base = ComponentBase::new(Some(props), Some(state));
+    }
+
+    fn edit_query(&mut self, edit: impl Fn(&mut GrepQuery, Field)) {
+        self.base.set_state(|mut current_state| {
+            edit(
+                &mut current_state.query,
+                FIELDS[current_state.focused_field],
+            );
+            current_state
+        });
+    }
+
+    fn focus_field(&mut self, forward: bool) {
+        self.base.set_state(|mut current_state| {
+            current_state.focused_field = if forward {
+                (current_state.focused_field + 1) % FIELDS.len()
+            } else {
+                (current_state.focused_field + FIELDS.len() - 1) % FIELDS.len()
+            };
+            current_state
+        });
+    }
+
+    fn handle_query_event(&mut self, key_code: KeyCode, focused_field: Field) -> bool {
+        let on_choice = focused_field != Field::Pattern;
+        match key_code {
+            KeyCode::Tab | KeyCode::Down => self.focus_field(true),
+            KeyCode::BackTab | KeyCode::Up => self.focus_field(false),
+            KeyCode::Left | KeyCode::Right | KeyCode::Char(' ') if on_choice => {
+                self.edit_query(|query, field| match field {
+                    Field::Syntax => query.syntax = query.syntax.next(),
+                    Field::IgnoreCase => query.ignore_case = !query.ignore_case,
+                    Field::Pattern => {}
+                })
+            }
+            KeyCode::Char(c) => self.edit_query(|query, field| {
+                if field == Field::Pattern {
+                    query.pattern.push(c);
+                }
+            }),
+            KeyCode::Backspace => self.edit_query(|query, field| {
+                if field == Field::Pattern {
+                    query.pattern.pop();
+                }
+            }),
+            _ => return false,
+        };
+        true
+    }
+
+    fn render_query<TBackend: Backend>(&self, frame: &mut Frame<TBackend>, area: Rect) {
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+        let query = &local_state.query;
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(FIELDS.len() as u16 + 1),
+                    Constraint::Min(0),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        let fields: Vec<Spans> = FIELDS
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let value = match field {
+                    Field::Pattern => query.pattern.clone(),
+                    Field::Syntax => format!("< {} >", query.syntax),
+                    Field::IgnoreCase => {
+                        format!("< {} >", if query.ignore_case { "yes" } else { "no" })
+                    }
+                };
+                let style = if idx == local_state.focused_field {
+                    Style::default()
+                        .bg(props.modal_style.selected_element_background)
+                        .fg(props.modal_style.selected_element_foreground)
+                } else {
+                    Style::default()
+                };
+                Spans::from(vec![
+                    Span::from(format!("{:>11}: ", field.label())),
+                    Span::styled(value, style),
+                ])
+            })
+            .collect();
+
+        let help = vec![Spans::from(vec![Span::styled(
+            "Binary files and items ignored by .gitignore are skipped",
+            Style::default().fg(Color::DarkGray),
+        )])];
+
+        let status = match query.regex() {
+            Ok(_) => Span::from("enter searches, esc cancels"),
+            Err(err) => Span::styled(err, Style::default().fg(Color::Red)),
+        };
+
+        frame.render_widget(Paragraph::new(fields), layout[0]);
+        frame.render_widget(Paragraph::new(help), layout[1]);
+        frame.render_widget(Paragraph::new(Spans::from(vec![status])), layout[2]);
+    }
+
+    fn render_matches<TBackend: Backend>(
+        &self,
+        frame: &mut Frame<TBackend>,
+        area: Rect,
+        grep: &GrepResults,
+    ) {
+        let props = self.base.get_props().unwrap();
+        let mut local_state = self.base.get_state().unwrap();
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(0)].as_ref())
+            .split(area);
+
+        let header = vec![Spans::from(vec![Span::from(format!(
+            "{} matches of '{}'{}",
+            grep.matches.len(),
+            grep.query.pattern,
+            if grep.searching { ", searching..." } else { "" }
+        ))])];
+
+        let items: Vec<ListItem> = grep
+            .matches
+            .iter()
+            .map(|found| {
+                let path = found.path.strip_prefix(&grep.root).unwrap_or(&found.path);
+                ListItem::new(Spans::from(vec![
+                    Span::styled(
+                        format!("{}:{}: ", path.to_string_lossy(), found.line),
+                        Style::default().fg(props.modal_style.border_color),
+                    ),
+                    Span::from(found.text.clone()),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(props.modal_style.selected_element_background)
+                    .fg(props.modal_style.selected_element_foreground),
+            )
+            .highlight_symbol(props.list_selector.as_str());
+
+        frame.render_widget(Paragraph::new(header), layout[0]);
+        frame.render_stateful_widget(list, layout[1], &mut local_state.list_state);
+    }
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem>
+    Component<Event, AppState<TFileSystem>, FileManagerActions>
+    for GrepModalComponent<TFileSystem>
+{
+    fn handle_event(
+        &mut self,
+        event: Event,
+        store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    ) -> bool {
+        let state = store.get_state();
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+        let matches_count = props.matches().len();
+        if let Event::Keyboard(key_evt) = event {
+            if state.config.keyboard_cfg.close.is_pressed(key_evt) {
+                store.dispatch(FileManagerActions::Grep(GrepAction::Close));
+                return true;
+            }
+
+            if props.grep.is_none() {
+                if state.config.keyboard_cfg.accept.is_pressed(key_evt) {
+                    if local_state.query.regex().is_ok() {
+                        store.dispatch(FileManagerActions::Grep(GrepAction::Start {
+                            root: props.path,
+                            query: local_state.query,
+                        }));
+                    }
+                    return true;
+                }
+
+                return self.handle_query_event(key_evt.code, FIELDS[local_state.focused_field]);
+            }
+
+            if state.config.keyboard_cfg.move_up.is_pressed(key_evt) && matches_count > 0 {
+                let next_item = match local_state.list_state.selected() {
+                    Some(current) if current > 0 => current - 1,
+                    _ => matches_count - 1,
+                };
+                self.base.set_state(|mut current_state| {
+                    current_state.list_state.select(Some(next_item));
+                    current_state
+                });
+                return true;
+            }
+
+            if state.config.keyboard_cfg.move_down.is_pressed(key_evt) && matches_count > 0 {
+                let next_item = match local_state.list_state.selected() {
+                    Some(current) if current + 1 < matches_count => current + 1,
+                    _ => 0,
+                };
+                self.base.set_state(|mut current_state| {
+                    current_state.list_state.select(Some(next_item));
+                    current_state
+                });
+                return true;
+            }
+
+            if state.config.keyboard_cfg.accept.is_pressed(key_evt) && matches_count > 0 {
+                store.dispatch(FileManagerActions::Grep(GrepAction::Open {
+                    index: local_state.list_state.selected().unwrap_or(0),
+                }));
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn render<TBackend: Backend>(&self, frame: &mut Frame<TBackend>, area: Option<Rect>) {
+        let props = self.base.get_props().unwrap();
+        // The results get more room than the query.
+        let (width, height) = if props.grep.is_some() {
+            (80, 70)
+        } else {
+            (70, 30)
+        };
+        let layout = if let Some(area) = area {
+            create_modal_layout(width, height, area)
+        } else {
+            create_modal_layout(width, height, frame.size())
+        };
+
+        let block = Block::default()
+            .title(Spans::from(vec![
+                Span::from("| "),
+                Span::from(format!(
+                    "Search content in {}",
+                    props.path.to_string_lossy()
+                )),
+                Span::from(" |"),
+            ]))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(props.modal_style.border_color))
+            .border_type(tui::widgets::BorderType::Thick)
+            .style(Style::default().bg(Color::Reset));
+        let inner = block.inner(layout);
+
+        frame.render_widget(Clear, layout);
+        frame.render_widget(block, layout);
+        match props.grep {
+            Some(ref grep) => self.render_matches(frame, inner, grep),
+            None => self.render_query(frame, inner),
+        }
+    }
+}
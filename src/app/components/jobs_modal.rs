@@ -0,0 +1,233 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::{AppAction, FileManagerActions, JobAction},
+        file_system::{functions::format_size, FileSystem},
+        jobs::{Job, JobId, JobStatus},
+        state::AppState,
+    },
+    core::{
+        events::Event,
+        store::Store,
+        ui::{component::Component, component_base::ComponentBase},
+    },
+};
+use std::{fmt::Debug, marker::PhantomData};
+
+use super::{create_modal_layout, ModalStyle};
+
+const PROGRESS_BAR_WIDTH: u64 = 20;
+
+#[derive(Clone, Default)]
+pub struct JobsModalComponentProps {
+    jobs: Vec<Job>,
+    job_id: Option<JobId>,
+    list_selector: String,
+    modal_style: ModalStyle,
+}
+
+impl JobsModalComponentProps {
+    pub fn new(
+        jobs: Vec<Job>,
+        job_id: Option<JobId>,
+        list_selector: String,
+        modal_style: ModalStyle,
+    ) -> Self {
+        JobsModalComponentProps {
+            jobs: match job_id {
+                Some(job_id) => jobs.into_iter().filter(|job| job.id == job_id).collect(),
+                None => jobs,
+            },
+            job_id,
+            list_selector,
+            modal_style,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct JobsModalComponentState {
+    list_state: ListState,
+}
+
+pub struct JobsModalComponent<TFileSystem: Clone + Debug + Default + FileSystem> {
+    base: ComponentBase<JobsModalComponentProps, JobsModalComponentState>,
+    _marker: PhantomData<TFileSystem>,
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem> JobsModalComponent<TFileSystem> {
+    pub fn new(props: JobsModalComponentProps) -> Self {
+        let mut state = JobsModalComponentState::default();
+        state.list_state.select(Some(0));
+        JobsModalComponent {
+            base: ComponentBase::new(Some(props), Some(state)),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Replaces the displayed jobs while keeping the current selection.
+    pub fn update(&mut self, props: JobsModalComponentProps) {
+        let mut state = self.base.get_state().unwrap_or_default();
+        let selected = state.list_state.selected().unwrap_or(0);
+        state
+            .list_state
+            .select(Some(selected.min(props.jobs.len().saturating_sub(1))));
+        self.base = ComponentBase::new(Some(props), Some(state));
+    }
+
+    fn selected_job(&self) -> Option<Job> {
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+        let selected = local_state.list_state.selected().unwrap_or(0);
+        props.jobs.get(selected).cloned()
+    }
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem>
+    Component<Event, AppState<TFileSystem>, FileManagerActions>
+    for JobsModalComponent<TFileSystem>
+{
+    fn handle_event(
+        &mut self,
+        event: Event,
+        store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    ) -> bool {
+        let state = store.get_state();
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+        if let Event::Keyboard(key_evt) = event {
+            if state.config.keyboard_cfg.move_up.is_pressed(key_evt) && !props.jobs.is_empty() {
+                let next_item = match local_state.list_state.selected() {
+                    Some(current) if current > 0 => current - 1,
+                    _ => props.jobs.len() - 1,
+                };
+                self.base.set_state(|mut current_state| {
+                    current_state.list_state.select(Some(next_item));
+                    current_state
+                });
+                return true;
+            }
+
+            if state.config.keyboard_cfg.move_down.is_pressed(key_evt) && !props.jobs.is_empty() {
+                let next_item = match local_state.list_state.selected() {
+                    Some(current) if current + 1 < props.jobs.len() => current + 1,
+                    _ => 0,
+                };
+                self.base.set_state(|mut current_state| {
+                    current_state.list_state.select(Some(next_item));
+                    current_state
+                });
+                return true;
+            }
+
+            if state.config.keyboard_cfg.pause_job.is_pressed(key_evt) {
+                if let Some(job) = self.selected_job() {
+                    match job.status {
+                        JobStatus::Running => {
+                            store.dispatch(FileManagerActions::Job(JobAction::Pause { id: job.id }))
+                        }
+                        JobStatus::Paused => store
+                            .dispatch(FileManagerActions::Job(JobAction::Resume { id: job.id })),
+                    }
+                }
+                return true;
+            }
+
+            if state.config.keyboard_cfg.cancel_job.is_pressed(key_evt) {
+                if let Some(job) = self.selected_job() {
+                    store.dispatch(FileManagerActions::Job(JobAction::Cancel { id: job.id }));
+                }
+                return true;
+            }
+
+            if state.config.keyboard_cfg.close.is_pressed(key_evt) {
+                store.dispatch(FileManagerActions::App(AppAction::CloseModal));
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn render<TBackend: Backend>(&self, frame: &mut Frame<TBackend>, area: Option<Rect>) {
+        let layout = if let Some(area) = area {
+            create_modal_layout(60, 40, area)
+        } else {
+            create_modal_layout(60, 40, frame.size())
+        };
+
+        let props = self.base.get_props().unwrap();
+        let mut local_state = self.base.get_state().unwrap();
+
+        let items: Vec<ListItem> = if props.jobs.is_empty() {
+            vec![ListItem::new(Spans::from(vec![Span::from(
+                "No running jobs",
+            )]))]
+        } else {
+            props
+                .jobs
+                .iter()
+                .map(|job| {
+                    ListItem::new(vec![
+                        Spans::from(vec![Span::from(job.to_string())]),
+                        Spans::from(vec![Span::from(progress_line(job))]),
+                    ])
+                })
+                .collect()
+        };
+
+        let block = Block::default()
+            .title(Spans::from(vec![
+                Span::from("| "),
+                Span::from(if props.job_id.is_some() {
+                    "Job progress"
+                } else {
+                    "Jobs"
+                }),
+                Span::from(" |"),
+            ]))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(props.modal_style.border_color))
+            .border_type(tui::widgets::BorderType::Thick)
+            .style(Style::default().bg(tui::style::Color::Reset));
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(
+                Style::default()
+                    .bg(props.modal_style.selected_element_background)
+                    .fg(props.modal_style.selected_element_foreground),
+            )
+            .highlight_symbol(props.list_selector.as_str());
+
+        frame.render_widget(Clear, layout);
+        frame.render_stateful_widget(list, layout, &mut local_state.list_state);
+    }
+}
+
+fn progress_line(job: &Job) -> String {
+    let percent = job.progress.percent() as u64;
+    let filled = percent * PROGRESS_BAR_WIDTH / 100;
+    let mut line = format!(
+        "[{}{}] {:>3}% {}/{} files {}/{}",
+        "#".repeat(filled as usize),
+        ".".repeat((PROGRESS_BAR_WIDTH - filled) as usize),
+        percent,
+        job.progress.done_files,
+        job.progress.total_files,
+        format_size(job.progress.done_bytes),
+        format_size(job.progress.total_bytes),
+    );
+    if job.status == JobStatus::Paused {
+        line.push_str(" paused");
+    }
+    line
+}
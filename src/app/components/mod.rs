@@ -3,12 +3,19 @@ use tui::{
     style::Color,
 };
 
+pub mod conflict_modal;
 pub mod create_modal;
+pub mod find_modal;
+pub mod grep_modal;
+pub mod jobs_modal;
 pub mod messagebox_modal;
+pub mod multi_rename_modal;
 pub mod not_empty_dir_delete_modal;
 pub mod panel;
+pub mod purge_modal;
 pub mod rename_modal;
 pub mod root;
+pub mod sync_modal;
 pub mod tab;
 
 fn create_modal_layout(x_percent: u16, y_percent: u16, rect: Rect) -> Rect {
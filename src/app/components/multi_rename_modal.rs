@@ -0,0 +1,380 @@
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
+
+use crossterm::event::KeyCode;
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::{AppAction, FileAction, FileManagerActions, PanelInfo, PanelSide},
+        file_system::FileSystem,
+        multi_rename::{preview_renames, PreviewEntry, RenamePattern},
+        state::{AppState, TabIdx},
+    },
+    core::{
+        events::Event,
+        store::Store,
+        ui::{component::Component, component_base::ComponentBase},
+    },
+};
+
+use super::{create_modal_layout, ModalStyle};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    NameMask,
+    ExtensionMask,
+    Search,
+    Replace,
+    Case,
+}
+
+const FIELDS: [Field; 5] = [
+    Field::NameMask,
+    Field::ExtensionMask,
+    Field::Search,
+    Field::Replace,
+    Field::Case,
+];
+
+impl Field {
+    fn label(self) -> &'static str {
+        match self {
+            Field::NameMask => "Name mask",
+            Field::ExtensionMask => "Extension mask",
+            Field::Search => "Search (regex)",
+            Field::Replace => "Replace with",
+            Field::Case => "Case",
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct MultiRenameModalComponentProps<TFileSystem: Clone + Debug + Default + FileSystem> {
+    panel_side: Option<PanelSide>,
+    panel_tab: TabIdx,
+    dir: PathBuf,
+    items: Vec<PathBuf>,
+    file_system: TFileSystem,
+    list_selector: String,
+    modal_style: ModalStyle,
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem>
+    MultiRenameModalComponentProps<TFileSystem>
+{
+    pub fn new(
+        panel_side: Option<PanelSide>,
+        panel_tab: TabIdx,
+        dir: PathBuf,
+        items: Vec<PathBuf>,
+        file_system: TFileSystem,
+        list_selector: String,
+        modal_style: ModalStyle,
+    ) -> Self {
+        MultiRenameModalComponentProps {
+            panel_side,
+            panel_tab,
+            dir,
+            items,
+            file_system,
+            list_selector,
+            modal_style,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct MultiRenameModalComponentState {
+    pattern: RenamePattern,
+    focused_field: usize,
+    preview: Result<Vec<PreviewEntry>, String>,
+    list_state: ListState,
+}
+
+impl Default for MultiRenameModalComponentState {
+    fn default() -> Self {
+        MultiRenameModalComponentState {
+            pattern: RenamePattern::default(),
+            focused_field: 0,
+            preview: Ok(Vec::new()),
+            list_state: ListState::default(),
+        }
+    }
+}
+
+pub struct MultiRenameModalComponent<TFileSystem: Clone + Debug + Default + FileSystem> {
+    base:
+        ComponentBase<MultiRenameModalComponentProps<TFileSystem>, MultiRenameModalComponentState>,
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem> MultiRenameModalComponent<TFileSystem> {
+    pub fn with_props(props: MultiRenameModalComponentProps<TFileSystem>) -> Self {
+        let mut state = MultiRenameModalComponentState::default();
+        state.list_state.select(Some(0));
+        state.preview = preview(&props, &state.pattern);
+        MultiRenameModalComponent {
+            base: ComponentBase::new(Some(props), Some(state)),
+        }
+    }
+
+    /// Changes the pattern and updates the preview to match.
+    fn edit_pattern(&mut self, edit: impl FnOnce(&mut RenamePattern, Field)) {
+        let props = self.base.get_props().unwrap();
+        let mut state = self.base.get_state().unwrap();
+        edit(&mut state.pattern, FIELDS[state.focused_field]);
+        state.preview = preview(&props, &state.pattern);
+        self.base = ComponentBase::new(Some(props), Some(state));
+    }
+
+    fn focus_field(&mut self, forward: bool) {
+        self.base.set_state(|mut current_state| {
+            current_state.focused_field = if forward {
+                (current_state.focused_field + 1) % FIELDS.len()
+            } else {
+                (current_state.focused_field + FIELDS.len() - 1) % FIELDS.len()
+            };
+            current_state
+        });
+    }
+
+    fn scroll_preview(&mut self, forward: bool) {
+        let props = self.base.get_props().unwrap();
+        self.base.set_state(|mut current_state| {
+            let current = current_state.list_state.selected().unwrap_or(0);
+            let next = if forward {
+                (current + 1).min(props.items.len().saturating_sub(1))
+            } else {
+                current.saturating_sub(1)
+            };
+            current_state.list_state.select(Some(next));
+            current_state
+        });
+    }
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem>
+    Component<Event, AppState<TFileSystem>, FileManagerActions>
+    for MultiRenameModalComponent<TFileSystem>
+{
+    fn handle_event(
+        &mut self,
+        event: Event,
+        store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    ) -> bool {
+        let state = store.get_state();
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+        if let Event::Keyboard(key_evt) = event {
+            if state.config.keyboard_cfg.accept.is_pressed(key_evt) {
+                let renames = local_state
+                    .preview
+                    .as_ref()
+                    .ok()
+                    .and_then(|entries| preview_renames(entries));
+                if let Some(renames) = renames {
+                    if !renames.is_empty() {
+                        store.dispatch(FileManagerActions::File(FileAction::MultiRename {
+                            panel: PanelInfo {
+                                path: props.dir.clone(),
+                                tab: props.panel_tab,
+                                side: props.panel_side.unwrap(),
+                            },
+                            renames,
+                        }));
+                    }
+                    store.dispatch(FileManagerActions::App(AppAction::CloseModal));
+                }
+                return true;
+            }
+
+            if state.config.keyboard_cfg.close.is_pressed(key_evt) {
+                store.dispatch(FileManagerActions::App(AppAction::CloseModal));
+                return true;
+            }
+
+            let on_case = FIELDS[local_state.focused_field] == Field::Case;
+            match key_evt.code {
+                KeyCode::Tab | KeyCode::Down => self.focus_field(true),
+                KeyCode::BackTab | KeyCode::Up => self.focus_field(false),
+                KeyCode::PageDown => self.scroll_preview(true),
+                KeyCode::PageUp => self.scroll_preview(false),
+                KeyCode::Right | KeyCode::Char(' ') if on_case => {
+                    self.edit_pattern(|pattern, _| pattern.case = pattern.case.next())
+                }
+                KeyCode::Left if on_case => {
+                    self.edit_pattern(|pattern, _| pattern.case = pattern.case.prev())
+                }
+                KeyCode::Char(c) => self.edit_pattern(|pattern, field| {
+                    if let Some(text) = field_text(pattern, field) {
+                        text.push(c);
+                    }
+                }),
+                KeyCode::Backspace => self.edit_pattern(|pattern, field| {
+                    if let Some(text) = field_text(pattern, field) {
+                        text.pop();
+                    }
+                }),
+                _ => return false,
+            };
+            return true;
+        }
+
+        false
+    }
+
+    fn render<TBackend: Backend>(&self, frame: &mut Frame<TBackend>, area: Option<Rect>) {
+        let layout = if let Some(area) = area {
+            create_modal_layout(80, 80, area)
+        } else {
+            create_modal_layout(80, 80, frame.size())
+        };
+
+        let props = self.base.get_props().unwrap();
+        let mut local_state = self.base.get_state().unwrap();
+
+        let block = Block::default()
+            .title(Spans::from(vec![
+                Span::from("| "),
+                Span::from(format!("Rename {} items", props.items.len())),
+                Span::from(" |"),
+            ]))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(props.modal_style.border_color))
+            .border_type(tui::widgets::BorderType::Thick)
+            .style(Style::default().bg(Color::Reset));
+
+        let inner_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Length(FIELDS.len() as u16 + 1),
+                    Constraint::Min(0),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(block.inner(layout));
+
+        let fields: Vec<Spans> = FIELDS
+            .iter()
+            .enumerate()
+            .map(|(idx, field)| {
+                let value = match field {
+                    Field::NameMask => local_state.pattern.name_mask.clone(),
+                    Field::ExtensionMask => local_state.pattern.extension_mask.clone(),
+                    Field::Search => local_state.pattern.search.clone(),
+                    Field::Replace => local_state.pattern.replace.clone(),
+                    Field::Case => format!("< {} >", local_state.pattern.case),
+                };
+                let style = if idx == local_state.focused_field {
+                    Style::default()
+                        .bg(props.modal_style.selected_element_background)
+                        .fg(props.modal_style.selected_element_foreground)
+                } else {
+                    Style::default()
+                };
+                Spans::from(vec![
+                    Span::from(format!("{:>16}: ", field.label())),
+                    Span::styled(value, style),
+                ])
+            })
+            .collect();
+
+        let (items, status) = match local_state.preview {
+            Ok(ref entries) => {
+                let items: Vec<ListItem> = entries
+                    .iter()
+                    .map(|entry| {
+                        let from = file_name(&entry.from);
+                        let to = file_name(&entry.to);
+                        match entry.problem {
+                            Some(ref problem) => ListItem::new(Spans::from(vec![Span::styled(
+                                format!("{} -> {} ({})", from, to, problem),
+                                Style::default().fg(Color::Red),
+                            )])),
+                            None if entry.is_renamed() => {
+                                ListItem::new(Spans::from(vec![Span::from(format!(
+                                    "{} -> {}",
+                                    from, to
+                                ))]))
+                            }
+                            None => ListItem::new(Spans::from(vec![Span::styled(
+                                from,
+                                Style::default().fg(Color::DarkGray),
+                            )])),
+                        }
+                    })
+                    .collect();
+                let problems = entries
+                    .iter()
+                    .filter(|entry| entry.problem.is_some())
+                    .count();
+                let status = if problems > 0 {
+                    format!("{} items can't be renamed, fix the pattern first", problems)
+                } else {
+                    format!(
+                        "{} of {} items will be renamed, enter applies, esc cancels",
+                        entries.iter().filter(|entry| entry.is_renamed()).count(),
+                        entries.len()
+                    )
+                };
+                (items, status)
+            }
+            Err(ref err) => (Vec::new(), err.clone()),
+        };
+
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::TOP).title("Preview"))
+            .highlight_style(Style::default().fg(props.modal_style.selected_element_background))
+            .highlight_symbol(props.list_selector.as_str());
+
+        frame.render_widget(Clear, layout);
+        frame.render_widget(block, layout);
+        frame.render_widget(Paragraph::new(fields), inner_layout[0]);
+        frame.render_stateful_widget(list, inner_layout[1], &mut local_state.list_state);
+        frame.render_widget(Paragraph::new(status), inner_layout[2]);
+    }
+}
+
+fn preview<TFileSystem: Clone + Debug + Default + FileSystem>(
+    props: &MultiRenameModalComponentProps<TFileSystem>,
+    pattern: &RenamePattern,
+) -> Result<Vec<PreviewEntry>, String> {
+    pattern
+        .preview(&props.file_system, &props.items)
+        .map_err(|err| {
+            // Syntax errors span several lines with the reason on the last.
+            let err = err.to_string();
+            let reason = err.lines().last().unwrap_or_default();
+            format!(
+                "invalid search regex: {}",
+                reason.trim_start_matches("error: ")
+            )
+        })
+}
+
+fn field_text(pattern: &mut RenamePattern, field: Field) -> Option<&mut String> {
+    match field {
+        Field::NameMask => Some(&mut pattern.name_mask),
+        Field::ExtensionMask => Some(&mut pattern.extension_mask),
+        Field::Search => Some(&mut pattern.search),
+        Field::Replace => Some(&mut pattern.replace),
+        Field::Case => None,
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -31,6 +31,7 @@ pub struct NotEmptyDirDeleteModalComponentProps {
     panel_side: Option<PanelSide>,
     panel_tab: TabIdx,
     path: PathBuf,
+    permanent: bool,
     list_selector: String,
     modal_style: ModalStyle,
 }
@@ -40,6 +41,7 @@ impl NotEmptyDirDeleteModalComponentProps {
         panel_side: Option<PanelSide>,
         panel_tab: TabIdx,
         path: PathBuf,
+        permanent: bool,
         list_selector: String,
         modal_style: ModalStyle,
     ) -> Self {
@@ -47,6 +49,7 @@ impl NotEmptyDirDeleteModalComponentProps {
             panel_side,
             panel_tab,
             path,
+            permanent,
             list_selector,
             modal_style,
         }
@@ -170,17 +173,21 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                 if let Some(selected) = local_state.list_state.selected() {
                     let props = self.base.get_props().unwrap();
                     let option = Options::from(selected);
-                    match option {
-                        Options::Ok => store.dispatch(FileManagerActions::Directory(
-                            DirectoryAction::DeleteWithContent {
-                                panel: PanelInfo {
-                                    side: props.panel_side.unwrap(),
-                                    tab: props.panel_tab,
-                                    path: props.path,
-                                },
-                            },
-                        )),
-                        _ => {}
+                    if option == Options::Ok {
+                        let panel = PanelInfo {
+                            side: props.panel_side.unwrap(),
+                            tab: props.panel_tab,
+                            path: props.path,
+                        };
+                        if props.permanent {
+                            store.dispatch(FileManagerActions::Directory(
+                                DirectoryAction::DeleteWithContentPermanently { panel },
+                            ))
+                        } else {
+                            store.dispatch(FileManagerActions::Directory(
+                                DirectoryAction::DeleteWithContent { panel },
+                            ))
+                        }
                     }
                     store.dispatch(FileManagerActions::App(AppAction::CloseModal));
                 }
@@ -212,7 +219,11 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
         let block = Block::default()
             .title(Spans::from(vec![
                 Span::from("| "),
-                Span::from("This directory is not empty do you want to remove it ?"),
+                Span::from(if props.permanent {
+                    "This directory is not empty do you want to remove it permanently ?"
+                } else {
+                    "This directory is not empty do you want to move it to trash ?"
+                }),
                 Span::from(" |"),
             ]))
             .borders(Borders::ALL)
@@ -134,6 +134,7 @@ impl<TFileSystem: Clone + Default + Debug + FileSystem> PanelComponent<TFileSyst
                 side,
                 icons.use_icons,
                 core.list_arrow.clone(),
+                core.columns.clone(),
             )),
             Some(TabStyle::new_style_from_config(core)),
         );
@@ -0,0 +1,222 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::{AppAction, FileManagerActions, PanelInfo, PanelSide, TrashAction},
+        file_system::{trash::TrashEntry, FileSystem},
+        state::{AppState, TabIdx},
+    },
+    core::{
+        events::Event,
+        store::Store,
+        ui::{component::Component, component_base::ComponentBase},
+    },
+};
+use std::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+};
+
+use super::{create_modal_layout, ModalStyle};
+
+#[derive(Clone, Default)]
+pub struct PurgeModalComponentProps {
+    panel_side: Option<PanelSide>,
+    panel_tab: TabIdx,
+    entries: Vec<TrashEntry>,
+    list_selector: String,
+    modal_style: ModalStyle,
+}
+
+impl PurgeModalComponentProps {
+    pub fn new(
+        panel_side: Option<PanelSide>,
+        panel_tab: TabIdx,
+        entries: Vec<TrashEntry>,
+        list_selector: String,
+        modal_style: ModalStyle,
+    ) -> Self {
+        PurgeModalComponentProps {
+            panel_side,
+            panel_tab,
+            entries,
+            list_selector,
+            modal_style,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Options {
+    Ok,
+    Cancel,
+}
+
+impl fmt::Display for Options {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Options::Ok => write!(f, "Ok"),
+            Options::Cancel => write!(f, "Cancel"),
+        }
+    }
+}
+
+impl From<usize> for Options {
+    fn from(source: usize) -> Self {
+        match source {
+            0 => Options::Ok,
+            1 => Options::Cancel,
+            _ => panic!(""),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PurgeModalComponentState {
+    list_state: ListState,
+}
+
+/// Asks before items are removed from the trash for good, listing where
+/// they were deleted from.
+pub struct PurgeModalComponent<TFileSystem: Clone + Debug + Default + FileSystem> {
+    base: ComponentBase<PurgeModalComponentProps, PurgeModalComponentState>,
+    _marker: PhantomData<TFileSystem>,
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem> PurgeModalComponent<TFileSystem> {
+    pub fn new(props: PurgeModalComponentProps) -> Self {
+        PurgeModalComponent {
+            base: ComponentBase::new(Some(props), Some(PurgeModalComponentState::default())),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem>
+    Component<Event, AppState<TFileSystem>, FileManagerActions>
+    for PurgeModalComponent<TFileSystem>
+{
+    fn handle_event(
+        &mut self,
+        event: Event,
+        store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    ) -> bool {
+        let state = store.get_state();
+        let local_state = self.base.get_state().unwrap();
+        if let Event::Keyboard(key_evt) = event {
+            if state.config.keyboard_cfg.move_up.is_pressed(key_evt)
+                || state.config.keyboard_cfg.move_down.is_pressed(key_evt)
+            {
+                let next_item = match local_state.list_state.selected() {
+                    Some(current) => 1 - current,
+                    None => 0,
+                };
+                self.base.set_state(|mut current_state| {
+                    current_state.list_state.select(Some(next_item));
+                    PurgeModalComponentState {
+                        list_state: current_state.list_state,
+                    }
+                });
+                return true;
+            }
+
+            if state.config.keyboard_cfg.accept.is_pressed(key_evt) {
+                if let Some(selected) = local_state.list_state.selected() {
+                    let props = self.base.get_props().unwrap();
+                    if Options::from(selected) == Options::Ok {
+                        for entry in props.entries {
+                            store.dispatch(FileManagerActions::Trash(TrashAction::Purge {
+                                panel: PanelInfo {
+                                    side: props.panel_side.unwrap(),
+                                    tab: props.panel_tab,
+                                    path: entry.trashed_path,
+                                },
+                            }));
+                        }
+                    }
+                    store.dispatch(FileManagerActions::App(AppAction::CloseModal));
+                }
+                return true;
+            }
+
+            if state.config.keyboard_cfg.close.is_pressed(key_evt) {
+                store.dispatch(FileManagerActions::App(AppAction::CloseModal));
+                return true;
+            }
+        }
+        false
+    }
+
+    fn render<TBackend: Backend>(&self, frame: &mut Frame<TBackend>, area: Option<Rect>) {
+        let layout = if let Some(area) = area {
+            create_modal_layout(60, 40, area)
+        } else {
+            create_modal_layout(60, 40, frame.size())
+        };
+
+        let props = self.base.get_props().unwrap();
+        let mut local_state = self.base.get_state().unwrap();
+
+        let block = Block::default()
+            .title(Spans::from(vec![
+                Span::from("| "),
+                Span::from(format!(
+                    "Remove {} item(s) from trash permanently ?",
+                    props.entries.len()
+                )),
+                Span::from(" |"),
+            ]))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(props.modal_style.border_color))
+            .border_type(tui::widgets::BorderType::Thick)
+            .style(Style::default().bg(tui::style::Color::Reset));
+        let inner = block.inner(layout);
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(2)].as_ref())
+            .split(inner);
+
+        let visible = parts[0].height as usize;
+        let mut lines: Vec<ListItem> = props
+            .entries
+            .iter()
+            .map(|entry| {
+                ListItem::new(Spans::from(vec![Span::from(
+                    entry.original_path.to_string_lossy().to_string(),
+                )]))
+            })
+            .collect();
+        if lines.len() > visible {
+            let hidden = lines.len() + 1 - visible;
+            lines.truncate(visible.saturating_sub(1));
+            lines.push(ListItem::new(Spans::from(vec![Span::from(format!(
+                "... and {} more",
+                hidden
+            ))])));
+        }
+
+        let options = vec![
+            ListItem::new(Spans::from(vec![Span::from(Options::Ok.to_string())])),
+            ListItem::new(Spans::from(vec![Span::from(Options::Cancel.to_string())])),
+        ];
+        let options_list = List::new(options)
+            .highlight_style(
+                Style::default()
+                    .bg(props.modal_style.selected_element_background)
+                    .fg(props.modal_style.selected_element_foreground),
+            )
+            .highlight_symbol(props.list_selector.as_str());
+
+        frame.render_widget(Clear, layout);
+        frame.render_widget(block, layout);
+        frame.render_widget(List::new(lines), parts[0]);
+        frame.render_stateful_widget(options_list, parts[1], &mut local_state.list_state);
+    }
+}
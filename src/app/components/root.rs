@@ -6,9 +6,9 @@ use tui::{
 
 use crate::{
     app::{
-        actions::{AppAction, FileManagerActions, PanelSide},
+        actions::{AppAction, FileManagerActions, PanelSide, SyncAction},
         file_system::FileSystem,
-        state::{AppState, ModalType},
+        state::{AppState, ModalType, TabKind},
     },
     core::{
         events::Event,
@@ -18,13 +18,20 @@ use crate::{
 };
 
 use super::{
+    conflict_modal::{ConflictModalComponent, ConflictModalComponentProps},
     create_modal::{CreateModalComponent, CreateModalProps},
+    find_modal::{FindModalComponent, FindModalComponentProps},
+    grep_modal::{GrepModalComponent, GrepModalComponentProps},
+    jobs_modal::{JobsModalComponent, JobsModalComponentProps},
     messagebox_modal::{MessageboxModalComponent, MessageboxModalComponentProps},
+    multi_rename_modal::{MultiRenameModalComponent, MultiRenameModalComponentProps},
     not_empty_dir_delete_modal::{
         NotEmptyDirDeleteModalComponent, NotEmptyDirDeleteModalComponentProps,
     },
     panel::PanelComponent,
+    purge_modal::{PurgeModalComponent, PurgeModalComponentProps},
     rename_modal::{RenameModalComponent, RenameModalComponentProps},
+    sync_modal::{SyncModalComponent, SyncModalComponentProps},
     ModalStyle,
 };
 
@@ -39,8 +46,15 @@ pub struct RootComponent<TFileSystem: Clone + Debug + Default + FileSystem> {
     right_panel: PanelComponent<TFileSystem>,
     create_modal: Option<CreateModalComponent<TFileSystem>>,
     rename_modal: Option<RenameModalComponent<TFileSystem>>,
+    multi_rename_modal: Option<MultiRenameModalComponent<TFileSystem>>,
+    find_modal: Option<FindModalComponent<TFileSystem>>,
+    grep_modal: Option<GrepModalComponent<TFileSystem>>,
     messagebox_modal: Option<MessageboxModalComponent<TFileSystem>>,
     non_empty_dir_delete_modal: Option<NotEmptyDirDeleteModalComponent<TFileSystem>>,
+    purge_modal: Option<PurgeModalComponent<TFileSystem>>,
+    jobs_modal: Option<JobsModalComponent<TFileSystem>>,
+    conflict_modal: Option<ConflictModalComponent<TFileSystem>>,
+    sync_modal: Option<SyncModalComponent<TFileSystem>>,
     _maker: std::marker::PhantomData<TFileSystem>,
 }
 
@@ -52,8 +66,15 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> RootComponent<TFileSyste
             right_panel: PanelComponent::empty(),
             create_modal: None,
             rename_modal: None,
+            multi_rename_modal: None,
+            find_modal: None,
+            grep_modal: None,
             messagebox_modal: None,
             non_empty_dir_delete_modal: None,
+            purge_modal: None,
+            jobs_modal: None,
+            conflict_modal: None,
+            sync_modal: None,
             _maker: std::marker::PhantomData,
         }
     }
@@ -144,10 +165,50 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> RootComponent<TFileSyste
                         ));
                     }
                 }
+                ModalType::MultiRenameModal {
+                    panel_side,
+                    panel_tab,
+                    dir,
+                    items,
+                } => {
+                    if self.multi_rename_modal.is_none() {
+                        self.multi_rename_modal = Some(MultiRenameModalComponent::with_props(
+                            MultiRenameModalComponentProps::new(
+                                Some(panel_side),
+                                panel_tab,
+                                dir,
+                                items,
+                                state.file_system.clone(),
+                                state.config.core_cfg.list_arrow.clone(),
+                                ModalStyle::new(
+                                    state.config.core_cfg.color_scheme.normal_yellow,
+                                    state.config.core_cfg.color_scheme.light_cyan,
+                                    state.config.core_cfg.color_scheme.normal_black,
+                                ),
+                            ),
+                        ));
+                    }
+                }
+                ModalType::FindModal { panel_side, path } => {
+                    if self.find_modal.is_none() {
+                        self.find_modal = Some(FindModalComponent::with_props(
+                            FindModalComponentProps::new(
+                                Some(panel_side),
+                                path,
+                                ModalStyle::new(
+                                    state.config.core_cfg.color_scheme.normal_yellow,
+                                    state.config.core_cfg.color_scheme.light_cyan,
+                                    state.config.core_cfg.color_scheme.normal_black,
+                                ),
+                            ),
+                        ));
+                    }
+                }
                 ModalType::DeleteDirWithContent {
                     panel_side,
                     panel_tab,
                     path,
+                    permanent,
                 } => {
                     if self.non_empty_dir_delete_modal.is_none() {
                         self.non_empty_dir_delete_modal =
@@ -156,6 +217,7 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> RootComponent<TFileSyste
                                     Some(panel_side),
                                     panel_tab,
                                     path,
+                                    permanent,
                                     state.config.core_cfg.list_arrow.clone(),
                                     ModalStyle::new(
                                         state.config.core_cfg.color_scheme.normal_yellow,
@@ -166,6 +228,93 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> RootComponent<TFileSyste
                             ));
                     }
                 }
+                ModalType::PurgeFromTrash {
+                    panel_side,
+                    panel_tab,
+                    entries,
+                } => {
+                    if self.purge_modal.is_none() {
+                        self.purge_modal =
+                            Some(PurgeModalComponent::new(PurgeModalComponentProps::new(
+                                Some(panel_side),
+                                panel_tab,
+                                entries,
+                                state.config.core_cfg.list_arrow.clone(),
+                                ModalStyle::new(
+                                    state.config.core_cfg.color_scheme.normal_yellow,
+                                    state.config.core_cfg.color_scheme.light_cyan,
+                                    state.config.core_cfg.color_scheme.normal_black,
+                                ),
+                            )));
+                    }
+                }
+                ModalType::JobsModal { job_id } => {
+                    let props = JobsModalComponentProps::new(
+                        state.jobs.clone(),
+                        job_id,
+                        state.config.core_cfg.list_arrow.clone(),
+                        ModalStyle::new(
+                            state.config.core_cfg.color_scheme.normal_yellow,
+                            state.config.core_cfg.color_scheme.light_cyan,
+                            state.config.core_cfg.color_scheme.normal_black,
+                        ),
+                    );
+                    if let Some(ref mut jobs_modal) = self.jobs_modal {
+                        jobs_modal.update(props);
+                    } else {
+                        self.jobs_modal = Some(JobsModalComponent::new(props));
+                    }
+                }
+                ModalType::ConflictModal => {
+                    let props = ConflictModalComponentProps::new(
+                        state.conflicts.first().cloned(),
+                        state.conflicts.len(),
+                        state.config.core_cfg.list_arrow.clone(),
+                        ModalStyle::new(
+                            state.config.core_cfg.color_scheme.normal_yellow,
+                            state.config.core_cfg.color_scheme.light_cyan,
+                            state.config.core_cfg.color_scheme.normal_black,
+                        ),
+                    );
+                    if let Some(ref mut conflict_modal) = self.conflict_modal {
+                        conflict_modal.update(props);
+                    } else {
+                        self.conflict_modal = Some(ConflictModalComponent::new(props));
+                    }
+                }
+                ModalType::SyncModal => {
+                    let props = SyncModalComponentProps::new(
+                        state.sync.clone(),
+                        state.config.core_cfg.list_arrow.clone(),
+                        ModalStyle::new(
+                            state.config.core_cfg.color_scheme.normal_yellow,
+                            state.config.core_cfg.color_scheme.light_cyan,
+                            state.config.core_cfg.color_scheme.normal_black,
+                        ),
+                    );
+                    if let Some(ref mut sync_modal) = self.sync_modal {
+                        sync_modal.update(props);
+                    } else {
+                        self.sync_modal = Some(SyncModalComponent::new(props));
+                    }
+                }
+                ModalType::GrepModal { path } => {
+                    let props = GrepModalComponentProps::new(
+                        path,
+                        state.grep.clone(),
+                        state.config.core_cfg.list_arrow.clone(),
+                        ModalStyle::new(
+                            state.config.core_cfg.color_scheme.normal_yellow,
+                            state.config.core_cfg.color_scheme.light_cyan,
+                            state.config.core_cfg.color_scheme.normal_black,
+                        ),
+                    );
+                    if let Some(ref mut grep_modal) = self.grep_modal {
+                        grep_modal.update(props);
+                    } else {
+                        self.grep_modal = Some(GrepModalComponent::new(props));
+                    }
+                }
             };
         }
         if self.create_modal.is_some() && state.modal.is_none() {
@@ -176,6 +325,14 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> RootComponent<TFileSyste
             self.rename_modal = None;
         }
 
+        if self.multi_rename_modal.is_some() && state.modal.is_none() {
+            self.multi_rename_modal = None;
+        }
+
+        if self.find_modal.is_some() && state.modal.is_none() {
+            self.find_modal = None;
+        }
+
         if self.messagebox_modal.is_some() && state.modal.is_none() {
             self.messagebox_modal = None;
         }
@@ -183,6 +340,28 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> RootComponent<TFileSyste
         if self.non_empty_dir_delete_modal.is_some() && state.modal.is_none() {
             self.non_empty_dir_delete_modal = None;
         }
+
+        if self.purge_modal.is_some() && state.modal.is_none() {
+            self.purge_modal = None;
+        }
+
+        // Conflicts can replace the progress modal, so these two are dropped
+        // as soon as another modal is shown.
+        if self.jobs_modal.is_some() && !matches!(state.modal, Some(ModalType::JobsModal { .. })) {
+            self.jobs_modal = None;
+        }
+
+        if self.conflict_modal.is_some() && !matches!(state.modal, Some(ModalType::ConflictModal)) {
+            self.conflict_modal = None;
+        }
+
+        if self.sync_modal.is_some() && !matches!(state.modal, Some(ModalType::SyncModal)) {
+            self.sync_modal = None;
+        }
+
+        if self.grep_modal.is_some() && !matches!(state.modal, Some(ModalType::GrepModal { .. })) {
+            self.grep_modal = None;
+        }
     }
 }
 
@@ -235,6 +414,46 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                     return result;
                 }
 
+                if let Some(ref mut purge_modal) = self.purge_modal {
+                    let result = purge_modal.handle_event(event, store);
+                    self.map_state(store);
+                    store.clean();
+
+                    return result;
+                }
+
+                if let Some(ref mut conflict_modal) = self.conflict_modal {
+                    let result = conflict_modal.handle_event(event, store);
+                    self.map_state(store);
+                    store.clean();
+
+                    return result;
+                }
+
+                if let Some(ref mut sync_modal) = self.sync_modal {
+                    let result = sync_modal.handle_event(event, store);
+                    self.map_state(store);
+                    store.clean();
+
+                    return result;
+                }
+
+                if let Some(ref mut grep_modal) = self.grep_modal {
+                    let result = grep_modal.handle_event(event, store);
+                    self.map_state(store);
+                    store.clean();
+
+                    return result;
+                }
+
+                if let Some(ref mut jobs_modal) = self.jobs_modal {
+                    let result = jobs_modal.handle_event(event, store);
+                    self.map_state(store);
+                    store.clean();
+
+                    return result;
+                }
+
                 if let Some(ref mut create_modal) = self.create_modal {
                     let result = create_modal.handle_event(event, store);
                     self.map_state(store);
@@ -249,6 +468,22 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
 
                     return result;
                 }
+
+                if let Some(ref mut multi_rename_modal) = self.multi_rename_modal {
+                    let result = multi_rename_modal.handle_event(event, store);
+                    self.map_state(store);
+                    store.clean();
+
+                    return result;
+                }
+
+                if let Some(ref mut find_modal) = self.find_modal {
+                    let result = find_modal.handle_event(event, store);
+                    self.map_state(store);
+                    store.clean();
+
+                    return result;
+                }
                 if state
                     .config
                     .keyboard_cfg
@@ -274,6 +509,35 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
 
                     return true;
                 }
+                if state.config.keyboard_cfg.show_jobs.is_pressed(key_evt) {
+                    store.dispatch(FileManagerActions::App(AppAction::ShowModal(Box::new(
+                        ModalType::JobsModal { job_id: None },
+                    ))));
+                    self.map_state(store);
+                    store.clean();
+
+                    return true;
+                }
+
+                if state.config.keyboard_cfg.sync_dirs.is_pressed(key_evt) {
+                    let left_tab = &state.left_panel.tabs[state.left_panel.current_tab];
+                    let right_tab = &state.right_panel.tabs[state.right_panel.current_tab];
+                    // Only real directories can be synchronized.
+                    if matches!(left_tab.kind, TabKind::Directory)
+                        && matches!(right_tab.kind, TabKind::Directory)
+                        && left_tab.path != right_tab.path
+                    {
+                        store.dispatch(FileManagerActions::Sync(SyncAction::Compare {
+                            left_root: left_tab.path.clone(),
+                            right_root: right_tab.path.clone(),
+                        }));
+                    }
+                    self.map_state(store);
+                    store.clean();
+
+                    return true;
+                }
+
                 if state
                     .config
                     .keyboard_cfg
@@ -337,6 +601,14 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
             }
         }
 
+        if let Some(ref multi_rename_modal) = self.multi_rename_modal {
+            multi_rename_modal.render(frame, None);
+        }
+
+        if let Some(ref find_modal) = self.find_modal {
+            find_modal.render(frame, None);
+        }
+
         if let Some(ref non_empty_dir_delete_modal) = self.non_empty_dir_delete_modal {
             if let Some(focused_panel) = local_state.focused_panel.clone() {
                 match focused_panel {
@@ -348,6 +620,40 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
             }
         }
 
+        if let Some(ref purge_modal) = self.purge_modal {
+            if let Some(focused_panel) = local_state.focused_panel.clone() {
+                match focused_panel {
+                    PanelSide::Left => purge_modal.render(frame, Some(layout[0])),
+                    PanelSide::Right => purge_modal.render(frame, Some(layout[1])),
+                };
+            } else {
+                purge_modal.render(frame, None);
+            }
+        }
+
+        if let Some(ref jobs_modal) = self.jobs_modal {
+            jobs_modal.render(frame, None);
+        }
+
+        if let Some(ref sync_modal) = self.sync_modal {
+            sync_modal.render(frame, None);
+        }
+
+        if let Some(ref grep_modal) = self.grep_modal {
+            grep_modal.render(frame, None);
+        }
+
+        if let Some(ref conflict_modal) = self.conflict_modal {
+            if let Some(focused_panel) = local_state.focused_panel.clone() {
+                match focused_panel {
+                    PanelSide::Left => conflict_modal.render(frame, Some(layout[0])),
+                    PanelSide::Right => conflict_modal.render(frame, Some(layout[1])),
+                };
+            } else {
+                conflict_modal.render(frame, None);
+            }
+        }
+
         if let Some(ref messagebox_modal) = self.messagebox_modal {
             if let Some(focused_panel) = local_state.focused_panel.clone() {
                 match focused_panel {
@@ -0,0 +1,273 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    app::{
+        actions::{FileManagerActions, SyncAction},
+        file_system::{functions::format_size, FileSystem},
+        state::AppState,
+        sync::{SyncEntry, SyncItem, SyncOperation, SyncPlan},
+    },
+    core::{
+        events::Event,
+        store::Store,
+        ui::{component::Component, component_base::ComponentBase},
+    },
+};
+use std::{fmt::Debug, marker::PhantomData};
+
+use super::{create_modal_layout, ModalStyle};
+
+#[derive(Clone, Default)]
+pub struct SyncModalComponentProps {
+    plan: Option<SyncPlan>,
+    list_selector: String,
+    modal_style: ModalStyle,
+}
+
+impl SyncModalComponentProps {
+    pub fn new(plan: Option<SyncPlan>, list_selector: String, modal_style: ModalStyle) -> Self {
+        SyncModalComponentProps {
+            plan,
+            list_selector,
+            modal_style,
+        }
+    }
+
+    fn entries(&self) -> &[SyncEntry] {
+        self.plan
+            .as_ref()
+            .and_then(|plan| plan.entries.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SyncModalComponentState {
+    list_state: ListState,
+}
+
+pub struct SyncModalComponent<TFileSystem: Clone + Debug + Default + FileSystem> {
+    base: ComponentBase<SyncModalComponentProps, SyncModalComponentState>,
+    _marker: PhantomData<TFileSystem>,
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem> SyncModalComponent<TFileSystem> {
+    pub fn new(props: SyncModalComponentProps) -> Self {
+        let mut state = SyncModalComponentState::default();
+        state.list_state.select(Some(0));
+        SyncModalComponent {
+            base: ComponentBase::new(Some(props), Some(state)),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Shows the changed plan while keeping the current selection.
+    pub fn update(&mut self, props: SyncModalComponentProps) {
+        let mut state = self.base.get_state().unwrap_or_default();
+        let selected = state.list_state.selected().unwrap_or(0);
+        state
+            .list_state
+            .select(Some(selected.min(props.entries().len().saturating_sub(1))));
+        self.base = ComponentBase::new(Some(props), Some(state));
+    }
+
+    /// Sets the operation of the selected entry, or skips the entry when the
+    /// operation is already set.
+    fn toggle_operation(
+        &self,
+        operation: SyncOperation,
+        store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    ) {
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+        let index = local_state.list_state.selected().unwrap_or(0);
+        if let Some(entry) = props.entries().get(index) {
+            let operation = if entry.operation == operation {
+                SyncOperation::Skip
+            } else {
+                operation
+            };
+            store.dispatch(FileManagerActions::Sync(SyncAction::SetOperation {
+                index,
+                operation,
+            }));
+        }
+    }
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem>
+    Component<Event, AppState<TFileSystem>, FileManagerActions>
+    for SyncModalComponent<TFileSystem>
+{
+    fn handle_event(
+        &mut self,
+        event: Event,
+        store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    ) -> bool {
+        let state = store.get_state();
+        let props = self.base.get_props().unwrap();
+        let local_state = self.base.get_state().unwrap();
+        let entries_count = props.entries().len();
+        if let Event::Keyboard(key_evt) = event {
+            if state.config.keyboard_cfg.move_up.is_pressed(key_evt) && entries_count > 0 {
+                let next_item = match local_state.list_state.selected() {
+                    Some(current) if current > 0 => current - 1,
+                    _ => entries_count - 1,
+                };
+                self.base.set_state(|mut current_state| {
+                    current_state.list_state.select(Some(next_item));
+                    current_state
+                });
+                return true;
+            }
+
+            if state.config.keyboard_cfg.move_down.is_pressed(key_evt) && entries_count > 0 {
+                let next_item = match local_state.list_state.selected() {
+                    Some(current) if current + 1 < entries_count => current + 1,
+                    _ => 0,
+                };
+                self.base.set_state(|mut current_state| {
+                    current_state.list_state.select(Some(next_item));
+                    current_state
+                });
+                return true;
+            }
+
+            if state.config.keyboard_cfg.copy_to_right.is_pressed(key_evt) {
+                self.toggle_operation(SyncOperation::CopyToRight, store);
+                return true;
+            }
+
+            if state.config.keyboard_cfg.copy_to_left.is_pressed(key_evt) {
+                self.toggle_operation(SyncOperation::CopyToLeft, store);
+                return true;
+            }
+
+            if state.config.keyboard_cfg.delete.is_pressed(key_evt) {
+                self.toggle_operation(SyncOperation::Delete, store);
+                return true;
+            }
+
+            if state.config.keyboard_cfg.accept.is_pressed(key_evt) {
+                if props.plan.is_some_and(|plan| plan.entries.is_some()) {
+                    store.dispatch(FileManagerActions::Sync(SyncAction::Run));
+                }
+                return true;
+            }
+
+            if state.config.keyboard_cfg.close.is_pressed(key_evt) {
+                store.dispatch(FileManagerActions::Sync(SyncAction::Cancel));
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn render<TBackend: Backend>(&self, frame: &mut Frame<TBackend>, area: Option<Rect>) {
+        let layout = if let Some(area) = area {
+            create_modal_layout(80, 70, area)
+        } else {
+            create_modal_layout(80, 70, frame.size())
+        };
+
+        let props = self.base.get_props().unwrap();
+        let mut local_state = self.base.get_state().unwrap();
+
+        let items: Vec<ListItem> = match props.plan.as_ref().map(|plan| &plan.entries) {
+            None | Some(None) => vec![ListItem::new(Spans::from(vec![Span::from("Comparing...")]))],
+            Some(Some(entries)) if entries.is_empty() => {
+                vec![ListItem::new(Spans::from(vec![Span::from(
+                    "The directories are already in sync",
+                )]))]
+            }
+            Some(Some(entries)) => entries
+                .iter()
+                .map(|entry| ListItem::new(Spans::from(vec![Span::from(entry_line(entry))])))
+                .collect(),
+        };
+
+        let block = Block::default()
+            .title(Spans::from(vec![
+                Span::from("| "),
+                Span::from("Synchronize directories"),
+                Span::from(" |"),
+            ]))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(props.modal_style.border_color))
+            .border_type(tui::widgets::BorderType::Thick)
+            .style(Style::default().bg(tui::style::Color::Reset));
+
+        let inner_layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(block.inner(layout));
+
+        let header = match props.plan {
+            Some(ref plan) => vec![
+                Spans::from(vec![Span::from(format!(
+                    "Left:  {}",
+                    plan.left_root.to_string_lossy()
+                ))]),
+                Spans::from(vec![Span::from(format!(
+                    "Right: {}",
+                    plan.right_root.to_string_lossy()
+                ))]),
+                Spans::from(vec![Span::from(summary(props.entries()))]),
+            ],
+            None => Vec::new(),
+        };
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(props.modal_style.selected_element_background)
+                    .fg(props.modal_style.selected_element_foreground),
+            )
+            .highlight_symbol(props.list_selector.as_str());
+
+        frame.render_widget(Clear, layout);
+        frame.render_widget(block, layout);
+        frame.render_widget(Paragraph::new(header), inner_layout[0]);
+        frame.render_stateful_widget(list, inner_layout[1], &mut local_state.list_state);
+    }
+}
+
+fn entry_line(entry: &SyncEntry) -> String {
+    let size = |item: &Option<SyncItem>| match item {
+        Some(item) if !item.is_dir => format_size(item.size),
+        _ => String::new(),
+    };
+    format!(
+        "[{}] {}{}  {} {:>9} | {:<9}",
+        entry.operation,
+        entry.relative.to_string_lossy(),
+        if entry.is_dir() { "/" } else { "" },
+        entry.reason(),
+        size(&entry.left),
+        size(&entry.right),
+    )
+}
+
+fn summary(entries: &[SyncEntry]) -> String {
+    let count = |operation: SyncOperation| {
+        entries
+            .iter()
+            .filter(|entry| entry.operation == operation)
+            .count()
+    };
+    format!(
+        "{} to the right, {} to the left, {} to delete, {} skipped",
+        count(SyncOperation::CopyToRight),
+        count(SyncOperation::CopyToLeft),
+        count(SyncOperation::Delete),
+        count(SyncOperation::Skip),
+    )
+}
@@ -1,12 +1,13 @@
 use crossterm::event::KeyCode;
 use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 
 use std::fmt::Debug;
 use tui::{
     backend::Backend,
     layout::{Constraint, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Span, Spans},
     widgets::List,
     widgets::ListItem,
@@ -16,15 +17,23 @@ use tui::{
 use crate::{
     app::{
         actions::{
-            AppAction, DirectoryAction, FileAction, FileManagerActions, PanelInfo, PanelSide,
-            SearchAction, SymlinkAction, TabAction,
+            AppAction, CompareAction, DirSizeAction, DirectoryAction, FileAction,
+            FileManagerActions, JournalAction, ListingAction, PanelInfo, PanelSide, SearchAction,
+            SymlinkAction, TabAction, TrashAction,
         },
-        // config::tab_config::SortEnum,
-        file_system::{file_system_item::FileSystemItem, FileSystem},
-        state::{AppState, ModalType, TabState},
+        columns::ColumnLayout,
+        compare::Difference,
+        config::tab_config::{SortEnum, SortKey},
+        file_system::{
+            file_system_item::FileSystemItem,
+            functions::{format_size, is_empty_dir},
+            trash::TrashEntry,
+            FileSystem,
+        },
+        state::{AppState, ModalType, TabKind, TabState},
     },
     core::{
-        config::CoreConfig,
+        config::{Column, CoreConfig},
         events::Event,
         store::Store,
         ui::{component::Component, component_base::ComponentBase},
@@ -40,6 +49,7 @@ pub struct TabComponentProps<TFileSystem: Clone + Debug + Default + FileSystem>
     panel_side: Option<PanelSide>,
     show_icons: bool,
     list_arrow: String,
+    columns: Vec<Column>,
 }
 
 impl<TFileSystem: Clone + Debug + Default + FileSystem> Default for TabComponentProps<TFileSystem> {
@@ -51,6 +61,7 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> Default for TabComponent
             panel_side: None,
             show_icons: false,
             list_arrow: "".to_string(),
+            columns: Vec::new(),
         }
     }
 }
@@ -63,6 +74,7 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> TabComponentProps<TFileS
         panel_side: PanelSide,
         show_icons: bool,
         list_arrow: String,
+        columns: Vec<Column>,
     ) -> Self {
         TabComponentProps {
             state: Some(state),
@@ -71,6 +83,7 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> TabComponentProps<TFileS
             panel_side: Some(panel_side),
             show_icons,
             list_arrow,
+            columns,
         }
     }
 }
@@ -101,6 +114,10 @@ pub struct TabStyle {
     pub cursor_link_foreground: Color,
     pub select_link_foreground: Color,
 
+    pub compare_unique_foreground: Color,
+    pub compare_newer_foreground: Color,
+    pub compare_differs_foreground: Color,
+
     pub colors_files: HashMap<String, Color>,
 }
 
@@ -135,6 +152,10 @@ impl Default for TabStyle {
             cursor_link_foreground: Color::White,
             select_link_foreground: Color::Black,
 
+            compare_unique_foreground: Color::LightCyan,
+            compare_newer_foreground: Color::LightGreen,
+            compare_differs_foreground: Color::LightYellow,
+
             colors_files,
         }
     }
@@ -164,6 +185,9 @@ impl TabStyle {
             normal_link_foreground: config.color_scheme.normal_link_foreground,
             cursor_link_foreground: config.color_scheme.cursor_link_foreground,
             select_link_foreground: config.color_scheme.select_link_foreground,
+            compare_unique_foreground: config.color_scheme.compare_unique_foreground,
+            compare_newer_foreground: config.color_scheme.compare_newer_foreground,
+            compare_differs_foreground: config.color_scheme.compare_differs_foreground,
 
             colors_files: config.colors_files.colors_files.clone(),
         }
@@ -187,6 +211,67 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> TabComponent<TFileSystem
         TabComponent::new(None, None)
     }
 
+    fn item_to_spans<'a>(
+        &self,
+        state: &TabState<TFileSystem>,
+        item: &'a FileSystemItem,
+        area: Rect,
+        columns: Option<&ColumnLayout>,
+        show_icons: bool,
+    ) -> Spans<'a> {
+        let spans = if let TabKind::DiskUsage { total } = state.kind {
+            disk_usage_spans(item, total)
+        } else if state.is_find() {
+            find_result_spans(item, &state.path)
+        } else {
+            self.listing_spans(state, item, area, columns, show_icons)
+        };
+        match state.filter_match(item) {
+            Some(filter_match) if !state.phrase.is_empty() => {
+                highlight_filter_match(spans, &item.get_name(), &filter_match.positions)
+            }
+            _ => spans,
+        }
+    }
+
+    fn listing_spans<'a>(
+        &self,
+        state: &TabState<TFileSystem>,
+        item: &'a FileSystemItem,
+        area: Rect,
+        columns: Option<&ColumnLayout>,
+        show_icons: bool,
+    ) -> Spans<'a> {
+        let mut spans = match state.trash_entry(item.get_path().as_path()) {
+            Some(entry) => Spans::from(vec![
+                Span::from("   "),
+                Span::from(item.get_name()),
+                Span::from("  "),
+                Span::from(entry.deletion_date.replace('T', " ")),
+                Span::from("  "),
+                Span::from(entry.original_path.to_string_lossy().to_string()),
+            ]),
+            None => match columns {
+                Some(columns) => columns.row(item),
+                None => item.to_spans(area, show_icons),
+            },
+        };
+        let compared_color = match state.differences.get(&item.get_path()) {
+            Some(Difference::Unique) => Some(self.style.compare_unique_foreground),
+            Some(Difference::Newer) => Some(self.style.compare_newer_foreground),
+            Some(Difference::Older) | Some(Difference::Differs) => {
+                Some(self.style.compare_differs_foreground)
+            }
+            None => None,
+        };
+        if let Some(color) = compared_color {
+            for span in spans.0.iter_mut() {
+                span.style = span.style.fg(color);
+            }
+        }
+        spans
+    }
+
     fn current_item(&self) -> Option<FileSystemItem> {
         let props = self.base.get_props().unwrap();
         let state = props.state.unwrap();
@@ -206,13 +291,48 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
         let local_state = props.state.unwrap();
         let global_state = store.get_state();
 
-        for item in local_state.items.iter() {
-            if global_state.file_system.exist(item.get_path().as_path()) == false {
-                store.dispatch(FileManagerActions::Tab(TabAction::ReloadTab {
-                    panel_side: props.panel_side.unwrap(),
-                    path: local_state.path.clone(),
+        if local_state.is_listing_pending() {
+            store.dispatch(FileManagerActions::Listing(ListingAction::Start {
+                panel_side: props.panel_side.unwrap(),
+                path: local_state.path.clone(),
+            }));
+        }
+
+        // Directories are watched, find results come from too many of them
+        // and are checked here instead.
+        if local_state.is_find() {
+            for item in local_state.items.iter() {
+                if global_state.file_system.exist(item.get_path().as_path()) == false {
+                    store.dispatch(FileManagerActions::Tab(TabAction::ReloadTab {
+                        panel_side: props.panel_side.unwrap(),
+                        path: local_state.path.clone(),
+                    }));
+                    return;
+                }
+            }
+        }
+
+        if (global_state.config.core_cfg.scan_dir_sizes || local_state.is_disk_usage())
+            && !local_state.is_trash()
+        {
+            let paths: Vec<PathBuf> = local_state
+                .items
+                .iter()
+                .filter_map(|item| match item {
+                    FileSystemItem::Directory(dir)
+                        if dir.content.is_none()
+                            && !global_state.dir_sizes.is_scanning(&dir.get_path()) =>
+                    {
+                        Some(dir.get_path())
+                    }
+                    _ => None,
+                })
+                .collect();
+            if !paths.is_empty() {
+                store.dispatch(FileManagerActions::DirSize(DirSizeAction::Scan {
+                    paths,
+                    force: false,
                 }));
-                return;
             }
         }
     }
@@ -317,8 +437,14 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                 }
 
                 if state.config.keyboard_cfg.navigate_up.is_pressed(key_evt) && props.is_focused {
-                    let current_path = tab_state.path;
-                    if let Some(parent) = current_path.parent() {
+                    // Find results go back to listing the directory searched.
+                    let current_path = tab_state.path.clone();
+                    let parent = if tab_state.is_find() {
+                        Some(current_path.as_path())
+                    } else {
+                        current_path.parent()
+                    };
+                    if let Some(parent) = parent {
                         store.dispatch(FileManagerActions::Directory(DirectoryAction::Open {
                             panel: PanelInfo {
                                 path: parent.into(),
@@ -556,6 +682,146 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                     return true;
                 }
 
+                if state.config.keyboard_cfg.undo.is_pressed(key_evt) && props.is_focused {
+                    store.dispatch(FileManagerActions::Journal(JournalAction::Undo));
+                    return true;
+                }
+
+                if state.config.keyboard_cfg.redo.is_pressed(key_evt) && props.is_focused {
+                    store.dispatch(FileManagerActions::Journal(JournalAction::Redo));
+                    return true;
+                }
+
+                if state.config.keyboard_cfg.compare_panels.is_pressed(key_evt) && props.is_focused
+                {
+                    let compare_action = if state.comparison.is_some() {
+                        CompareAction::Stop
+                    } else {
+                        CompareAction::Start { by_content: false }
+                    };
+                    store.dispatch(FileManagerActions::Compare(compare_action));
+                    return true;
+                }
+
+                if state
+                    .config
+                    .keyboard_cfg
+                    .compare_panels_by_content
+                    .is_pressed(key_evt)
+                    && props.is_focused
+                {
+                    store.dispatch(FileManagerActions::Compare(CompareAction::Start {
+                        by_content: true,
+                    }));
+                    return true;
+                }
+
+                if state
+                    .config
+                    .keyboard_cfg
+                    .select_different
+                    .is_pressed(key_evt)
+                    && props.is_focused
+                    && state.comparison.is_some()
+                {
+                    store.dispatch(FileManagerActions::Compare(
+                        CompareAction::SelectDifferent {
+                            panel_side: tab_side,
+                        },
+                    ));
+                    return true;
+                }
+
+                if state.config.keyboard_cfg.disk_usage.is_pressed(key_evt)
+                    && props.is_focused
+                    && !tab_state.is_trash()
+                {
+                    store.dispatch(FileManagerActions::Tab(TabAction::OpenDiskUsage {
+                        panel_side: tab_side,
+                        path: tab_state.path.clone(),
+                    }));
+                    return true;
+                }
+
+                if state.config.keyboard_cfg.find_files.is_pressed(key_evt)
+                    && props.is_focused
+                    && !tab_state.is_trash()
+                {
+                    store.dispatch(FileManagerActions::App(AppAction::ShowModal(Box::new(
+                        ModalType::FindModal {
+                            panel_side: tab_side,
+                            path: tab_state.path.clone(),
+                        },
+                    ))));
+                    return true;
+                }
+
+                if state.config.keyboard_cfg.grep_files.is_pressed(key_evt)
+                    && props.is_focused
+                    && !tab_state.is_trash()
+                {
+                    store.dispatch(FileManagerActions::App(AppAction::ShowModal(Box::new(
+                        ModalType::GrepModal {
+                            path: tab_state.path.clone(),
+                        },
+                    ))));
+                    return true;
+                }
+
+                if state.config.keyboard_cfg.open_trash.is_pressed(key_evt) && props.is_focused {
+                    store.dispatch(FileManagerActions::Trash(TrashAction::Open {
+                        panel_side: tab_side,
+                    }));
+                    return true;
+                }
+
+                if (state.config.keyboard_cfg.delete.is_pressed(key_evt)
+                    || state
+                        .config
+                        .keyboard_cfg
+                        .delete_permanently
+                        .is_pressed(key_evt))
+                    && props.is_focused
+                    && tab_state.is_trash()
+                {
+                    let entries: Vec<TrashEntry> = tab_state
+                        .selected
+                        .iter()
+                        .filter_map(|item| tab_state.trash_entry(item.get_path().as_path()))
+                        .cloned()
+                        .collect();
+                    if !entries.is_empty() {
+                        store.dispatch(FileManagerActions::App(AppAction::ShowModal(Box::new(
+                            ModalType::PurgeFromTrash {
+                                panel_side: tab_side,
+                                panel_tab: tab_idx,
+                                entries,
+                            },
+                        ))));
+                    }
+                    return true;
+                }
+
+                if state
+                    .config
+                    .keyboard_cfg
+                    .restore_from_trash
+                    .is_pressed(key_evt)
+                    && props.is_focused
+                    && tab_state.is_trash()
+                {
+                    for item in tab_state.selected.iter() {
+                        store.dispatch(FileManagerActions::Trash(TrashAction::Restore {
+                            panel: PanelInfo {
+                                path: item.get_path(),
+                                tab: tab_idx,
+                                side: tab_side,
+                            },
+                        }));
+                    }
+                    return true;
+                }
+
                 if state.config.keyboard_cfg.delete.is_pressed(key_evt) && props.is_focused {
                     for item in tab_state.selected.iter() {
                         match item {
@@ -567,7 +833,7 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                             tab: tab_idx,
                                             side: tab_side.clone(),
                                         },
-                                        is_empty: dir.is_empty(),
+                                        is_empty: is_empty_dir(&state.file_system, &dir.get_path()),
                                     },
                                 ));
                             }
@@ -597,6 +863,53 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                     return true;
                 }
 
+                if state
+                    .config
+                    .keyboard_cfg
+                    .delete_permanently
+                    .is_pressed(key_evt)
+                    && props.is_focused
+                {
+                    for item in tab_state.selected.iter() {
+                        match item {
+                            FileSystemItem::Directory(dir) => {
+                                store.dispatch(FileManagerActions::Directory(
+                                    DirectoryAction::DeletePermanently {
+                                        panel: PanelInfo {
+                                            path: dir.get_path(),
+                                            tab: tab_idx,
+                                            side: tab_side.clone(),
+                                        },
+                                        is_empty: is_empty_dir(&state.file_system, &dir.get_path()),
+                                    },
+                                ));
+                            }
+                            FileSystemItem::File(file) => store.dispatch(FileManagerActions::File(
+                                FileAction::DeletePermanently {
+                                    panel: PanelInfo {
+                                        path: file.get_path(),
+                                        tab: tab_idx,
+                                        side: tab_side.clone(),
+                                    },
+                                },
+                            )),
+                            FileSystemItem::Symlink(symlink) => {
+                                store.dispatch(FileManagerActions::Symlink(SymlinkAction::Delete {
+                                    panel: PanelInfo {
+                                        path: symlink.get_path(),
+                                        tab: tab_idx,
+                                        side: tab_side.clone(),
+                                    },
+                                }))
+                            }
+                            _ => {}
+                        };
+                    }
+                    store.dispatch(FileManagerActions::Tab(TabAction::SelectNext));
+
+                    return true;
+                }
+
                 if state.config.keyboard_cfg.move_left.is_pressed(key_evt)
                     && props.is_focused
                     && tab_side == PanelSide::Right
@@ -611,11 +924,11 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                     .clone();
                                 if dir.get_path() == to_path {
                                     store.dispatch(FileManagerActions::App(AppAction::ShowModal(
-                                        ModalType::MessageboxModal(format!(
+                                        Box::new(ModalType::MessageboxModal(format!(
                                             "Can't move \n {} \n into \n {}",
                                             dir.get_path().to_str().unwrap_or(""),
                                             to_path.to_str().unwrap_or("")
-                                        )),
+                                        ))),
                                     )));
                                 } else {
                                     to_path.push(name);
@@ -677,11 +990,11 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                     .clone();
                                 if dir.get_path() == to_path {
                                     store.dispatch(FileManagerActions::App(AppAction::ShowModal(
-                                        ModalType::MessageboxModal(format!(
+                                        Box::new(ModalType::MessageboxModal(format!(
                                             "Can't move \n {} \n into \n {}",
                                             dir.get_path().to_str().unwrap_or(""),
                                             to_path.to_str().unwrap_or("")
-                                        )),
+                                        ))),
                                     )));
                                 } else {
                                     to_path.push(name);
@@ -741,12 +1054,12 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                         .clone();
                                     if dir.get_path() == to_path {
                                         store.dispatch(FileManagerActions::App(
-                                            AppAction::ShowModal(ModalType::MessageboxModal(
-                                                format!(
+                                            AppAction::ShowModal(Box::new(
+                                                ModalType::MessageboxModal(format!(
                                                     "Can't move \n {} \n into \n {}",
                                                     dir.get_path().to_str().unwrap_or(""),
                                                     to_path.to_str().unwrap_or("")
-                                                ),
+                                                )),
                                             )),
                                         ));
                                     } else {
@@ -804,12 +1117,12 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                         .clone();
                                     if dir.get_path() == to_path {
                                         store.dispatch(FileManagerActions::App(
-                                            AppAction::ShowModal(ModalType::MessageboxModal(
-                                                format!(
+                                            AppAction::ShowModal(Box::new(
+                                                ModalType::MessageboxModal(format!(
                                                     "Can't move \n {} \n into \n {}",
                                                     dir.get_path().to_str().unwrap_or(""),
                                                     to_path.to_str().unwrap_or("")
-                                                ),
+                                                )),
                                             )),
                                         ));
                                     } else {
@@ -1083,9 +1396,9 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                     && props.is_focused
                 {
                     /*
-                    store.dispatch(FileManagerActions::App(AppAction::ShowModal(
+                    store.dispatch(FileManagerActions::App(AppAction::ShowModal(Box::new(
                         ModalType::MessageboxModal(String::new("message")),
-                    )));
+                    ))));
                     */
                     println!("{:?}", state.config.tab_config);
                     return true;
@@ -1098,7 +1411,7 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                     && props.is_focused
                 {
                     for item in tab_state.selected.iter() {
-                        store.dispatch(FileManagerActions::App(AppAction::ShowModal(
+                        store.dispatch(FileManagerActions::App(AppAction::ShowModal(Box::new(
                                                     ModalType::MessageboxModal(format!(
                                                         "Created: {}\nModified: {}\nAccessed: {}\nSize: {} bytes\n Mode: {:0}\nInode: {}\nNumber on links: {}\n(Owner:Group): {}:{}\nBlocks: {}\nNumber of block: {}",
                                                         item.get_created().format("%Y-%m-%d %H:%M:%S"),
@@ -1114,30 +1427,148 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                         item.get_blocks(),
 
                                                     )),
-                                                )));
+                                                ))));
                     }
                     return true;
                 }
-                /*
-                                if state
-                                    .config
-                                    .keyboard_cfg
-                                    .sort_by_name_asc
-                                    .is_pressed(key_evt)
-                                    && props.is_focused
-                                {
-                                    state.config.tab_config.sort_by_attr = SortEnum::NONE;
-                                    state.config.tab_config.sort_by_date = SortEnum::NONE;
-                                    match state.config.tab_config.sort_by_name {
-                                        SortEnum::ASC => state.config.tab_config.sort_by_name = SortEnum::DESC,
-                                        SortEnum::DESC => state.config.tab_config.sort_by_name = SortEnum::NONE,
-                                        SortEnum::NONE => state.config.tab_config.sort_by_name = SortEnum::ASC,
-                                    }
-                                    //store.st
-                                    println!("{:?}", state.config.tab_config.sort_by_name);
-                                    return true;
-                                }
-                */
+                let sort_bindings = [
+                    (
+                        &state.config.keyboard_cfg.sort_by_name_asc,
+                        SortKey::Name,
+                        SortEnum::ASC,
+                    ),
+                    (
+                        &state.config.keyboard_cfg.sort_by_name_desc,
+                        SortKey::Name,
+                        SortEnum::DESC,
+                    ),
+                    (
+                        &state.config.keyboard_cfg.sort_by_date_asc,
+                        SortKey::Modified,
+                        SortEnum::ASC,
+                    ),
+                    (
+                        &state.config.keyboard_cfg.sort_by_date_desc,
+                        SortKey::Modified,
+                        SortEnum::DESC,
+                    ),
+                    (
+                        &state.config.keyboard_cfg.sort_by_attr_asc,
+                        SortKey::Size,
+                        SortEnum::ASC,
+                    ),
+                    (
+                        &state.config.keyboard_cfg.sort_by_attr_desc,
+                        SortKey::Size,
+                        SortEnum::DESC,
+                    ),
+                ];
+                let pressed_sort = sort_bindings
+                    .iter()
+                    .find(|(binding, _, _)| binding.is_pressed(key_evt));
+                if let Some((_, key, order)) = pressed_sort {
+                    if props.is_focused && tab_state.is_sortable() {
+                        store.dispatch(FileManagerActions::Tab(TabAction::SetSort {
+                            panel_side: tab_side,
+                            key: *key,
+                            order: *order,
+                        }));
+                        return true;
+                    }
+                }
+                if state
+                    .config
+                    .keyboard_cfg
+                    .toggle_detail_view
+                    .is_pressed(key_evt)
+                    && props.is_focused
+                    && matches!(tab_state.kind, TabKind::Directory)
+                {
+                    store.dispatch(FileManagerActions::Tab(TabAction::ToggleDetailView {
+                        panel_side: tab_side,
+                    }));
+                    return true;
+                }
+                if state
+                    .config
+                    .keyboard_cfg
+                    .calculate_dir_size
+                    .is_pressed(key_evt)
+                    && props.is_focused
+                    && !tab_state.is_trash()
+                {
+                    // The selected directories, the one under the cursor or
+                    // else every directory in the tab.
+                    let candidates: Vec<FileSystemItem> = if !tab_state.selected.is_empty() {
+                        tab_state.selected.clone()
+                    } else {
+                        match self.current_item() {
+                            Some(item) if item.is_dir() => vec![item],
+                            _ => tab_state.items.clone(),
+                        }
+                    };
+                    let paths: Vec<PathBuf> = candidates
+                        .iter()
+                        .filter(|item| item.is_dir())
+                        .map(|item| item.get_path())
+                        .collect();
+                    if !paths.is_empty() {
+                        store.dispatch(FileManagerActions::DirSize(DirSizeAction::Scan {
+                            paths,
+                            force: true,
+                        }));
+                    }
+                    return true;
+                }
+
+                if state.config.keyboard_cfg.multi_rename.is_pressed(key_evt)
+                    && props.is_focused
+                    && !tab_state.is_trash()
+                {
+                    let items: Vec<PathBuf> = if tab_state.selected.is_empty() {
+                        self.current_item()
+                            .map(|item| vec![item.get_path()])
+                            .unwrap_or_default()
+                    } else {
+                        tab_state
+                            .selected
+                            .iter()
+                            .map(|item| item.get_path())
+                            .collect()
+                    };
+                    if !items.is_empty() {
+                        store.dispatch(FileManagerActions::App(AppAction::ShowModal(Box::new(
+                            ModalType::MultiRenameModal {
+                                panel_side: tab_side,
+                                panel_tab: tab_idx,
+                                dir: tab_state.path.clone(),
+                                items,
+                            },
+                        ))));
+                    }
+                    return true;
+                }
+
+                if state.config.keyboard_cfg.rename.is_pressed(key_evt)
+                    && props.is_focused
+                    && tab_state.selected.len() > 1
+                    && !tab_state.is_trash()
+                {
+                    store.dispatch(FileManagerActions::File(FileAction::BulkRename {
+                        panel: PanelInfo {
+                            path: tab_state.path.clone(),
+                            tab: tab_idx,
+                            side: tab_side,
+                        },
+                        items: tab_state
+                            .selected
+                            .iter()
+                            .map(|item| item.get_path())
+                            .collect(),
+                    }));
+                    return true;
+                }
+
                 if tab_state.selected.len() == 1 || tab_state.tab_state.selected().is_none() {
                     if let Some(current_item) = self.current_item() {
                         if state.config.keyboard_cfg.rename.is_pressed(key_evt) && props.is_focused
@@ -1147,11 +1578,11 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                 PanelSide::Right => state.right_panel.current_tab,
                             };
                             store.dispatch(FileManagerActions::App(AppAction::ShowModal(
-                                ModalType::RenameModal {
+                                Box::new(ModalType::RenameModal {
                                     panel_side: tab_side,
                                     panel_tab: tab_idx,
                                     item: current_item,
-                                },
+                                }),
                             )));
                             return true;
                         }
@@ -1162,14 +1593,14 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                             PanelSide::Left => state.left_panel.current_tab,
                             PanelSide::Right => state.right_panel.current_tab,
                         };
-                        store.dispatch(FileManagerActions::App(AppAction::ShowModal(
+                        store.dispatch(FileManagerActions::App(AppAction::ShowModal(Box::new(
                             ModalType::CreateModal {
                                 item_index: tab_state.tab_state.selected(),
                                 panel_side: tab_side,
                                 panel_tab: tab_idx,
                                 panel_tab_path: tab_state.path.clone(),
                             },
-                        )));
+                        ))));
                         return true;
                     }
                 }
@@ -1191,7 +1622,26 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                 } else {
                     vec![area.unwrap()]
                 };
+                // Rows lose the borders and, in the focused tab, the width of
+                // the highlight symbol.
+                let arrow_width = if tab_props.is_focused {
+                    tab_props.list_arrow.chars().count()
+                } else {
+                    0
+                };
+                let columns = if state.detail_view && matches!(state.kind, TabKind::Directory) {
+                    let row_width = (layout[0].width as usize).saturating_sub(2 + arrow_width);
+                    Some(ColumnLayout::fit(&tab_props.columns, row_width))
+                } else {
+                    None
+                };
 
+                // Owned, the rows borrow from them while the list state changes.
+                let filtered_items: Vec<FileSystemItem> = if state.phrase.is_empty() {
+                    Vec::new()
+                } else {
+                    state.filtered_items().into_iter().cloned().collect()
+                };
                 let list_items: Vec<ListItem> = if state.phrase.is_empty() {
                     state
                         .items
@@ -1203,33 +1653,53 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                 .any(|i| i.get_path() == item.get_path())
                             {
                                 match item {
-                                    FileSystemItem::Directory(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
-                                    .style(
-                                        Style::default()
-                                            .bg(self.style.cursor_dir_background)
-                                            .fg(self.style.cursor_dir_foreground),
-                                    ),
-                                    FileSystemItem::File(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
+                                    FileSystemItem::Directory(_) => {
+                                        ListItem::new(self.item_to_spans(
+                                            &state,
+                                            item,
+                                            area.unwrap_or(frame.size()),
+                                            columns.as_ref(),
+                                            show_icons,
+                                        ))
+                                        .style(
+                                            Style::default()
+                                                .bg(self.style.cursor_dir_background)
+                                                .fg(self.style.cursor_dir_foreground),
+                                        )
+                                    }
+                                    FileSystemItem::File(_) => ListItem::new(self.item_to_spans(
+                                        &state,
+                                        item,
+                                        area.unwrap_or(frame.size()),
+                                        columns.as_ref(),
+                                        show_icons,
+                                    ))
                                     .style(
                                         Style::default()
                                             .bg(self.style.cursor_file_background)
                                             .fg(self.style.cursor_file_foreground),
                                     ),
-                                    FileSystemItem::Symlink(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
-                                    .style(
-                                        Style::default()
-                                            .bg(self.style.cursor_link_background)
-                                            .fg(self.style.cursor_link_foreground),
-                                    ),
-                                    FileSystemItem::Unknown => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
+                                    FileSystemItem::Symlink(_) => {
+                                        ListItem::new(self.item_to_spans(
+                                            &state,
+                                            item,
+                                            area.unwrap_or(frame.size()),
+                                            columns.as_ref(),
+                                            show_icons,
+                                        ))
+                                        .style(
+                                            Style::default()
+                                                .bg(self.style.cursor_link_background)
+                                                .fg(self.style.cursor_link_foreground),
+                                        )
+                                    }
+                                    FileSystemItem::Unknown => ListItem::new(self.item_to_spans(
+                                        &state,
+                                        item,
+                                        area.unwrap_or(frame.size()),
+                                        columns.as_ref(),
+                                        show_icons,
+                                    ))
                                     .style(
                                         Style::default()
                                             .bg(self.style.selected_element_background)
@@ -1238,14 +1708,20 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                 }
                             } else {
                                 match item {
-                                    FileSystemItem::Directory(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
-                                    .style(
-                                        Style::default()
-                                            .bg(self.style.normal_dir_background)
-                                            .fg(self.style.normal_dir_foreground),
-                                    ),
+                                    FileSystemItem::Directory(_) => {
+                                        ListItem::new(self.item_to_spans(
+                                            &state,
+                                            item,
+                                            area.unwrap_or(frame.size()),
+                                            columns.as_ref(),
+                                            show_icons,
+                                        ))
+                                        .style(
+                                            Style::default()
+                                                .bg(self.style.normal_dir_background)
+                                                .fg(self.style.normal_dir_foreground),
+                                        )
+                                    }
                                     FileSystemItem::File(file) => {
                                         let file_extension = file.get_path();
                                         let file_extension = file_extension
@@ -1260,40 +1736,48 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                             fg_file = *color;
                                         }
 
-                                        ListItem::new(
-                                            item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                        )
+                                        ListItem::new(self.item_to_spans(
+                                            &state,
+                                            item,
+                                            area.unwrap_or(frame.size()),
+                                            columns.as_ref(),
+                                            show_icons,
+                                        ))
                                         .style(
                                             Style::default()
                                                 .bg(self.style.normal_file_background)
                                                 .fg(fg_file),
                                         )
                                     }
-                                    FileSystemItem::Symlink(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
-                                    .style(
-                                        Style::default()
-                                            .bg(self.style.normal_link_background)
-                                            .fg(self.style.normal_link_foreground),
-                                    ),
-                                    FileSystemItem::Unknown => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
+                                    FileSystemItem::Symlink(_) => {
+                                        ListItem::new(self.item_to_spans(
+                                            &state,
+                                            item,
+                                            area.unwrap_or(frame.size()),
+                                            columns.as_ref(),
+                                            show_icons,
+                                        ))
+                                        .style(
+                                            Style::default()
+                                                .bg(self.style.normal_link_background)
+                                                .fg(self.style.normal_link_foreground),
+                                        )
+                                    }
+                                    FileSystemItem::Unknown => ListItem::new(self.item_to_spans(
+                                        &state,
+                                        item,
+                                        area.unwrap_or(frame.size()),
+                                        columns.as_ref(),
+                                        show_icons,
+                                    ))
                                     .style(Style::default()),
                                 }
                             }
                         })
                         .collect()
                 } else {
-                    state
-                        .items
+                    filtered_items
                         .iter()
-                        .filter(|item| {
-                            item.get_name()
-                                .to_lowercase()
-                                .contains(&state.phrase.to_lowercase())
-                        })
                         .map(|item| {
                             if state
                                 .selected
@@ -1301,33 +1785,53 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                 .any(|i| i.get_path() == item.get_path())
                             {
                                 match item {
-                                    FileSystemItem::Directory(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
-                                    .style(
-                                        Style::default()
-                                            .bg(self.style.cursor_dir_background)
-                                            .fg(self.style.cursor_dir_foreground),
-                                    ),
-                                    FileSystemItem::File(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
+                                    FileSystemItem::Directory(_) => {
+                                        ListItem::new(self.item_to_spans(
+                                            &state,
+                                            item,
+                                            area.unwrap_or(frame.size()),
+                                            columns.as_ref(),
+                                            show_icons,
+                                        ))
+                                        .style(
+                                            Style::default()
+                                                .bg(self.style.cursor_dir_background)
+                                                .fg(self.style.cursor_dir_foreground),
+                                        )
+                                    }
+                                    FileSystemItem::File(_) => ListItem::new(self.item_to_spans(
+                                        &state,
+                                        item,
+                                        area.unwrap_or(frame.size()),
+                                        columns.as_ref(),
+                                        show_icons,
+                                    ))
                                     .style(
                                         Style::default()
                                             .bg(self.style.cursor_file_background)
                                             .fg(self.style.cursor_file_foreground),
                                     ),
-                                    FileSystemItem::Symlink(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
-                                    .style(
-                                        Style::default()
-                                            .bg(self.style.cursor_link_background)
-                                            .fg(self.style.cursor_link_foreground),
-                                    ),
-                                    FileSystemItem::Unknown => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
+                                    FileSystemItem::Symlink(_) => {
+                                        ListItem::new(self.item_to_spans(
+                                            &state,
+                                            item,
+                                            area.unwrap_or(frame.size()),
+                                            columns.as_ref(),
+                                            show_icons,
+                                        ))
+                                        .style(
+                                            Style::default()
+                                                .bg(self.style.cursor_link_background)
+                                                .fg(self.style.cursor_link_foreground),
+                                        )
+                                    }
+                                    FileSystemItem::Unknown => ListItem::new(self.item_to_spans(
+                                        &state,
+                                        item,
+                                        area.unwrap_or(frame.size()),
+                                        columns.as_ref(),
+                                        show_icons,
+                                    ))
                                     .style(
                                         Style::default()
                                             .bg(self.style.selected_element_background)
@@ -1336,33 +1840,53 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                 }
                             } else {
                                 match item {
-                                    FileSystemItem::Directory(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
-                                    .style(
-                                        Style::default()
-                                            .bg(self.style.normal_dir_background)
-                                            .fg(self.style.normal_dir_foreground),
-                                    ),
-                                    FileSystemItem::File(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
+                                    FileSystemItem::Directory(_) => {
+                                        ListItem::new(self.item_to_spans(
+                                            &state,
+                                            item,
+                                            area.unwrap_or(frame.size()),
+                                            columns.as_ref(),
+                                            show_icons,
+                                        ))
+                                        .style(
+                                            Style::default()
+                                                .bg(self.style.normal_dir_background)
+                                                .fg(self.style.normal_dir_foreground),
+                                        )
+                                    }
+                                    FileSystemItem::File(_) => ListItem::new(self.item_to_spans(
+                                        &state,
+                                        item,
+                                        area.unwrap_or(frame.size()),
+                                        columns.as_ref(),
+                                        show_icons,
+                                    ))
                                     .style(
                                         Style::default()
                                             .bg(self.style.normal_file_background)
                                             .fg(self.style.normal_file_foreground),
                                     ),
-                                    FileSystemItem::Symlink(_) => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
-                                    .style(
-                                        Style::default()
-                                            .bg(self.style.normal_link_background)
-                                            .fg(self.style.normal_link_foreground),
-                                    ),
-                                    FileSystemItem::Unknown => ListItem::new(
-                                        item.to_spans(area.unwrap_or(frame.size()), show_icons),
-                                    )
+                                    FileSystemItem::Symlink(_) => {
+                                        ListItem::new(self.item_to_spans(
+                                            &state,
+                                            item,
+                                            area.unwrap_or(frame.size()),
+                                            columns.as_ref(),
+                                            show_icons,
+                                        ))
+                                        .style(
+                                            Style::default()
+                                                .bg(self.style.normal_link_background)
+                                                .fg(self.style.normal_link_foreground),
+                                        )
+                                    }
+                                    FileSystemItem::Unknown => ListItem::new(self.item_to_spans(
+                                        &state,
+                                        item,
+                                        area.unwrap_or(frame.size()),
+                                        columns.as_ref(),
+                                        show_icons,
+                                    ))
                                     .style(Style::default()),
                                 }
                             }
@@ -1391,7 +1915,13 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                 */
                         Spans::from(vec![
                             Span::from("| "),
-                            Span::from(state.name),
+                            Span::from(state.name.clone()),
+                            Span::from(if state.listing.is_some() { " ..." } else { "" }),
+                            Span::from(if state.is_sortable() {
+                                format!(" [{}]", state.sort)
+                            } else {
+                                String::new()
+                            }),
                             Span::from(" |"),
                         ]), // }
                     )
@@ -1400,7 +1930,21 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                     .border_type(tui::widgets::BorderType::Rounded)
                     .style(Style::default());
 
-                let list = List::new(list_items).block(block);
+                let mut list_area = block.inner(layout[0]);
+                frame.render_widget(block, layout[0]);
+                if let Some(columns) = columns.as_ref().filter(|_| list_area.height > 0) {
+                    frame.render_widget(
+                        Paragraph::new(columns.header(arrow_width)),
+                        Rect {
+                            height: 1,
+                            ..list_area
+                        },
+                    );
+                    list_area.y += 1;
+                    list_area.height -= 1;
+                }
+
+                let list = List::new(list_items);
                 if tab_props.is_focused {
                     let focused_list = List::from(list)
                         .highlight_style(
@@ -1409,9 +1953,9 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
                                 .fg(self.style.selected_element_foreground),
                         )
                         .highlight_symbol(tab_props.list_arrow.as_str());
-                    frame.render_stateful_widget(focused_list, layout[0], &mut state.tab_state);
+                    frame.render_stateful_widget(focused_list, list_area, &mut state.tab_state);
                 } else {
-                    frame.render_widget(list, layout[0]);
+                    frame.render_widget(list, list_area);
                 }
 
                 if (state.search_mode || state.phrase.is_empty() == false) && tab_props.is_focused {
@@ -1434,3 +1978,104 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem>
         }
     }
 }
+
+/// Emphasizes the characters of `name` matched by the panel filter, in the
+/// span holding just the name or else the last span showing it.
+fn highlight_filter_match<'a>(mut spans: Spans<'a>, name: &str, positions: &[usize]) -> Spans<'a> {
+    let found = spans
+        .0
+        .iter()
+        .position(|span| span.content == name)
+        .map(|idx| (idx, 0))
+        .or_else(|| {
+            spans.0.iter().enumerate().rev().find_map(|(idx, span)| {
+                span.content
+                    .rfind(name)
+                    .map(|byte_offset| (idx, span.content[..byte_offset].chars().count()))
+            })
+        });
+    let (idx, offset) = match found {
+        Some(found) => found,
+        None => return spans,
+    };
+    let span = spans.0[idx].clone();
+    let mut runs: Vec<(bool, String)> = Vec::new();
+    for (char_idx, c) in span.content.chars().enumerate() {
+        let matched = char_idx >= offset && positions.contains(&(char_idx - offset));
+        match runs.last_mut() {
+            Some((highlighted, text)) if *highlighted == matched => text.push(c),
+            _ => runs.push((matched, c.to_string())),
+        }
+    }
+    let parts = runs.into_iter().map(|(matched, text)| {
+        if matched {
+            Span::styled(
+                text,
+                span.style
+                    .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            )
+        } else {
+            Span::styled(text, span.style)
+        }
+    });
+    spans.0.splice(idx..=idx, parts);
+    spans
+}
+
+const DISK_USAGE_BAR_WIDTH: u64 = 20;
+
+/// A row of the disk usage view, directories which are still being scanned
+/// have no share yet.
+fn disk_usage_spans(item: &FileSystemItem, total: u64) -> Spans<'static> {
+    let scanned = match item {
+        FileSystemItem::Directory(dir) => dir.content.is_some(),
+        _ => true,
+    };
+    let (bar, share, size) = if scanned {
+        let size = item.get_size();
+        let filled = (size * DISK_USAGE_BAR_WIDTH)
+            .checked_div(total)
+            .unwrap_or(0);
+        (
+            format!(
+                "{}{}",
+                "#".repeat(filled as usize),
+                " ".repeat((DISK_USAGE_BAR_WIDTH - filled) as usize)
+            ),
+            format!(
+                "{:5.1}%",
+                (size * 1000).checked_div(total).unwrap_or(0) as f64 / 10.0
+            ),
+            format_size(size),
+        )
+    } else {
+        (
+            " ".repeat(DISK_USAGE_BAR_WIDTH as usize),
+            String::from("     -"),
+            String::from("..."),
+        )
+    };
+    let name = if item.is_dir() {
+        format!("/{}", item.get_name())
+    } else {
+        item.get_name()
+    };
+    Spans::from(vec![
+        Span::from(format!("  [{}] {} {:>10}  ", bar, share, size)),
+        Span::from(name),
+    ])
+}
+
+/// Found items are shown with their path below the directory searched.
+fn find_result_spans(item: &FileSystemItem, root: &Path) -> Spans<'static> {
+    let path = item.get_path();
+    let relative = path.strip_prefix(root).unwrap_or(&path);
+    Spans::from(vec![
+        Span::from("   "),
+        Span::from(format!(
+            "{}{}",
+            relative.to_string_lossy(),
+            if item.is_dir() { "/" } else { "" }
+        )),
+    ])
+}
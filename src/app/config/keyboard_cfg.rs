@@ -17,10 +17,27 @@ pub struct KeyboardConfig {
     pub open_as_tab: KeyBinding,
     pub navigate_up: KeyBinding,
     pub delete: KeyBinding,
+    pub delete_permanently: KeyBinding,
+    pub open_trash: KeyBinding,
+    pub disk_usage: KeyBinding,
+    pub find_files: KeyBinding,
+    pub grep_files: KeyBinding,
+    pub compare_panels: KeyBinding,
+    pub compare_panels_by_content: KeyBinding,
+    pub select_different: KeyBinding,
+    pub restore_from_trash: KeyBinding,
+    pub undo: KeyBinding,
+    pub redo: KeyBinding,
+    pub show_jobs: KeyBinding,
+    pub sync_dirs: KeyBinding,
+    pub pause_job: KeyBinding,
+    pub cancel_job: KeyBinding,
     pub move_left: KeyBinding,
     pub move_right: KeyBinding,
     pub move_fs_item: KeyBinding,
     pub rename: KeyBinding,
+    pub multi_rename: KeyBinding,
+    pub calculate_dir_size: KeyBinding,
     pub create: KeyBinding,
     pub accept: KeyBinding,
     pub copy_to_left: KeyBinding,
@@ -50,6 +67,7 @@ pub struct KeyboardConfig {
     pub sort_by_date_desc: KeyBinding,
     pub sort_by_attr_asc: KeyBinding,
     pub sort_by_attr_desc: KeyBinding,
+    pub toggle_detail_view: KeyBinding,
 }
 
 impl KeyboardConfig {
@@ -211,6 +229,204 @@ impl KeyboardConfig {
                     }
                 }
 
+                if let Some(delete_permanently) = keyboard_cfg.get("delete_permanently") {
+                    if let Value::Table(key_binding) = delete_permanently {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.delete_permanently = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(open_trash) = keyboard_cfg.get("open_trash") {
+                    if let Value::Table(key_binding) = open_trash {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.open_trash = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(disk_usage) = keyboard_cfg.get("disk_usage") {
+                    if let Value::Table(key_binding) = disk_usage {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.disk_usage = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(find_files) = keyboard_cfg.get("find_files") {
+                    if let Value::Table(key_binding) = find_files {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.find_files = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(grep_files) = keyboard_cfg.get("grep_files") {
+                    if let Value::Table(key_binding) = grep_files {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.grep_files = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(compare_panels) = keyboard_cfg.get("compare_panels") {
+                    if let Value::Table(key_binding) = compare_panels {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.compare_panels = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(compare_panels_by_content) =
+                    keyboard_cfg.get("compare_panels_by_content")
+                {
+                    if let Value::Table(key_binding) = compare_panels_by_content {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.compare_panels_by_content =
+                            KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(select_different) = keyboard_cfg.get("select_different") {
+                    if let Value::Table(key_binding) = select_different {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.select_different = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(restore_from_trash) = keyboard_cfg.get("restore_from_trash") {
+                    if let Value::Table(key_binding) = restore_from_trash {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.restore_from_trash = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(undo) = keyboard_cfg.get("undo") {
+                    if let Value::Table(key_binding) = undo {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.undo = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(redo) = keyboard_cfg.get("redo") {
+                    if let Value::Table(key_binding) = redo {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.redo = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(show_jobs) = keyboard_cfg.get("show_jobs") {
+                    if let Value::Table(key_binding) = show_jobs {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.show_jobs = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(sync_dirs) = keyboard_cfg.get("sync_dirs") {
+                    if let Value::Table(key_binding) = sync_dirs {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.sync_dirs = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(pause_job) = keyboard_cfg.get("pause_job") {
+                    if let Value::Table(key_binding) = pause_job {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.pause_job = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(cancel_job) = keyboard_cfg.get("cancel_job") {
+                    if let Value::Table(key_binding) = cancel_job {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.cancel_job = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
                 if let Some(move_left) = keyboard_cfg.get("move_left") {
                     if let Value::Table(key_binding) = move_left {
                         let key_code = map_key(key_binding["key"].as_str().unwrap());
@@ -262,6 +478,32 @@ impl KeyboardConfig {
                     }
                 }
 
+                if let Some(multi_rename) = keyboard_cfg.get("multi_rename") {
+                    if let Value::Table(key_binding) = multi_rename {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.multi_rename = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
+                if let Some(calculate_dir_size) = keyboard_cfg.get("calculate_dir_size") {
+                    if let Value::Table(key_binding) = calculate_dir_size {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.calculate_dir_size = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
+
                 if let Some(create) = keyboard_cfg.get("create") {
                     if let Value::Table(key_binding) = create {
                         let key_code = map_key(key_binding["key"].as_str().unwrap());
@@ -566,6 +808,18 @@ impl KeyboardConfig {
                         self.sort_by_attr_desc = KeyBinding::with_modifiers(key_code, modifier);
                     }
                 }
+                if let Some(command_string) = keyboard_cfg.get("toggle_detail_view") {
+                    if let Value::Table(key_binding) = command_string {
+                        let key_code = map_key(key_binding["key"].as_str().unwrap());
+                        let modifier = if key_binding.contains_key("modifier") {
+                            map_modifier(key_binding["modifier"].as_str().unwrap())
+                        } else {
+                            KeyModifiers::empty()
+                        };
+
+                        self.toggle_detail_view = KeyBinding::with_modifiers(key_code, modifier);
+                    }
+                }
 
                 if let Some(command_string) = keyboard_cfg.get("filesystem_item_props") {
                     if let Value::Table(key_binding) = command_string {
@@ -599,10 +853,30 @@ impl Default for KeyboardConfig {
             open_as_tab: KeyBinding::with_modifiers(KeyCode::Char('o'), KeyModifiers::CONTROL),
             navigate_up: KeyBinding::new(KeyCode::Backspace),
             delete: KeyBinding::with_modifiers(KeyCode::Char('d'), KeyModifiers::CONTROL),
+            delete_permanently: KeyBinding::with_modifiers(KeyCode::Delete, KeyModifiers::SHIFT),
+            open_trash: KeyBinding::new(KeyCode::F(8)),
+            disk_usage: KeyBinding::with_modifiers(KeyCode::Char('d'), KeyModifiers::ALT),
+            find_files: KeyBinding::new(KeyCode::F(7)),
+            grep_files: KeyBinding::with_modifiers(KeyCode::F(7), KeyModifiers::ALT),
+            compare_panels: KeyBinding::with_modifiers(KeyCode::Char('C'), KeyModifiers::SHIFT),
+            compare_panels_by_content: KeyBinding::with_modifiers(
+                KeyCode::Char('c'),
+                KeyModifiers::ALT,
+            ),
+            select_different: KeyBinding::with_modifiers(KeyCode::Char('S'), KeyModifiers::SHIFT),
+            restore_from_trash: KeyBinding::with_modifiers(KeyCode::Char('R'), KeyModifiers::SHIFT),
+            undo: KeyBinding::new(KeyCode::Char('u')),
+            redo: KeyBinding::with_modifiers(KeyCode::Char('U'), KeyModifiers::SHIFT),
+            show_jobs: KeyBinding::with_modifiers(KeyCode::Char('J'), KeyModifiers::SHIFT),
+            sync_dirs: KeyBinding::new(KeyCode::F(9)),
+            pause_job: KeyBinding::with_modifiers(KeyCode::Char('p'), KeyModifiers::ALT),
+            cancel_job: KeyBinding::with_modifiers(KeyCode::Char('x'), KeyModifiers::ALT),
             move_left: KeyBinding::with_modifiers(KeyCode::Char('h'), KeyModifiers::CONTROL),
             move_right: KeyBinding::with_modifiers(KeyCode::Char('l'), KeyModifiers::CONTROL),
             move_fs_item: KeyBinding::with_modifiers(KeyCode::Char('m'), KeyModifiers::CONTROL),
             rename: KeyBinding::with_modifiers(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            multi_rename: KeyBinding::with_modifiers(KeyCode::Char('M'), KeyModifiers::SHIFT),
+            calculate_dir_size: KeyBinding::new(KeyCode::Char(' ')),
             create: KeyBinding::with_modifiers(KeyCode::Char('c'), KeyModifiers::CONTROL),
             accept: KeyBinding::new(KeyCode::Enter),
             copy_to_right: KeyBinding::with_modifiers(KeyCode::Char('x'), KeyModifiers::CONTROL),
@@ -628,6 +902,7 @@ impl Default for KeyboardConfig {
             sort_by_date_desc: KeyBinding::with_modifiers(KeyCode::F(2), KeyModifiers::CONTROL),
             sort_by_attr_asc: KeyBinding::new(KeyCode::F(3)),
             sort_by_attr_desc: KeyBinding::with_modifiers(KeyCode::F(3), KeyModifiers::CONTROL),
+            toggle_detail_view: KeyBinding::new(KeyCode::F(4)),
 
             print_test_info: KeyBinding::with_modifiers(KeyCode::Char('t'), KeyModifiers::CONTROL),
 
@@ -655,6 +930,7 @@ fn map_key(key: &str) -> KeyCode {
         "delete" => KeyCode::Delete,
         "insert" => KeyCode::Insert,
         "esc" => KeyCode::Esc,
+        "space" => KeyCode::Char(' '),
         "f1" => KeyCode::F(1),
         "f2" => KeyCode::F(2),
         "f3" => KeyCode::F(3),
@@ -682,3 +958,106 @@ fn map_modifier(modifier: &str) -> KeyModifiers {
         _ => KeyModifiers::NONE,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use toml::Value;
+
+    use crate::core::key_binding::KeyBinding;
+
+    use super::KeyboardConfig;
+
+    fn bindings(config: &KeyboardConfig) -> Vec<(&'static str, KeyBinding)> {
+        vec![
+            ("quit", config.quit),
+            ("focus_left_panel", config.focus_left_panel),
+            ("focus_right_panel", config.focus_right_panel),
+            ("move_down", config.move_down),
+            ("move_up", config.move_up),
+            ("next_tab", config.next_tab),
+            ("prev_tab", config.prev_tab),
+            ("close", config.close),
+            ("open", config.open),
+            ("open_as_tab", config.open_as_tab),
+            ("navigate_up", config.navigate_up),
+            ("delete", config.delete),
+            ("delete_permanently", config.delete_permanently),
+            ("open_trash", config.open_trash),
+            ("disk_usage", config.disk_usage),
+            ("find_files", config.find_files),
+            ("grep_files", config.grep_files),
+            ("compare_panels", config.compare_panels),
+            (
+                "compare_panels_by_content",
+                config.compare_panels_by_content,
+            ),
+            ("select_different", config.select_different),
+            ("restore_from_trash", config.restore_from_trash),
+            ("undo", config.undo),
+            ("redo", config.redo),
+            ("show_jobs", config.show_jobs),
+            ("sync_dirs", config.sync_dirs),
+            ("pause_job", config.pause_job),
+            ("cancel_job", config.cancel_job),
+            ("move_left", config.move_left),
+            ("move_right", config.move_right),
+            ("move_fs_item", config.move_fs_item),
+            ("rename", config.rename),
+            ("multi_rename", config.multi_rename),
+            ("calculate_dir_size", config.calculate_dir_size),
+            ("create", config.create),
+            ("accept", config.accept),
+            ("copy_to_left", config.copy_to_left),
+            ("copy_to_right", config.copy_to_right),
+            ("copy_fs_item", config.copy_fs_item),
+            ("search_in_panel", config.search_in_panel),
+            ("select_prev", config.select_prev),
+            ("select_next", config.select_next),
+            ("change_focus_panels", config.change_focus_panels),
+            ("command_1", config.command_1),
+            ("command_2", config.command_2),
+            ("command_3", config.command_3),
+            ("command_4", config.command_4),
+            ("command_5", config.command_5),
+            ("command_6", config.command_6),
+            ("command_7", config.command_7),
+            ("command_8", config.command_8),
+            ("command_9", config.command_9),
+            ("print_test_info", config.print_test_info),
+            ("filesystem_item_props", config.filesystem_item_props),
+            ("sort_by_name_asc", config.sort_by_name_asc),
+            ("sort_by_name_desc", config.sort_by_name_desc),
+            ("sort_by_date_asc", config.sort_by_date_asc),
+            ("sort_by_date_desc", config.sort_by_date_desc),
+            ("sort_by_attr_asc", config.sort_by_attr_asc),
+            ("sort_by_attr_desc", config.sort_by_attr_desc),
+            ("toggle_detail_view", config.toggle_detail_view),
+        ]
+    }
+
+    fn shared_bindings(config: &KeyboardConfig) -> Vec<(&'static str, &'static str)> {
+        let bindings = bindings(config);
+        let mut shared = Vec::new();
+        for (idx, (name, binding)) in bindings.iter().enumerate() {
+            for (other_name, other_binding) in bindings.iter().skip(idx + 1) {
+                if binding == other_binding {
+                    shared.push((*name, *other_name));
+                }
+            }
+        }
+        shared
+    }
+
+    #[test]
+    fn default_bindings_are_unique() {
+        assert_eq!(shared_bindings(&KeyboardConfig::default()), vec![]);
+    }
+
+    #[test]
+    fn example_config_bindings_are_unique() {
+        let cfg: Value = include_str!("../../../rufus.toml").parse().unwrap();
+        let mut config = KeyboardConfig::default();
+        config.update_from_file(&cfg);
+        assert_eq!(shared_bindings(&config), vec![]);
+    }
+}
@@ -1,14 +1,20 @@
 use toml::Value;
 
 use crate::core::config::{ColorsFiles, CoreConfig};
-use std::path::Path;
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
 
 use self::{
     commands::HotkeyCommandsPrograms, icon_cfg::IconsConfig, keyboard_cfg::KeyboardConfig,
     program_associations::FileAssociatedPrograms, tab_config::TabConfig,
 };
 
-use super::file_system::{functions::expand_if_contains_tilde, FileSystem};
+use super::{
+    file_system::{functions::expand_if_contains_tilde, FileSystem},
+    state::{AppError, FileOperation},
+};
 
 pub mod commands;
 pub mod icon_cfg;
@@ -24,6 +30,9 @@ pub struct Config {
     pub file_associated_programs: FileAssociatedPrograms,
     pub hotkey_commands_programs: HotkeyCommandsPrograms,
     pub tab_config: TabConfig,
+    /// Entries of the config file which could not be used, reported once the
+    /// app starts.
+    pub errors: Vec<AppError>,
 }
 
 impl Default for Config {
@@ -35,6 +44,7 @@ impl Default for Config {
             file_associated_programs: FileAssociatedPrograms::default(),
             hotkey_commands_programs: HotkeyCommandsPrograms::default(),
             tab_config: TabConfig::default(),
+            errors: Vec::new(),
         }
     }
 }
@@ -45,16 +55,30 @@ impl Config {
         file_system: &TFileSystem,
     ) -> Self {
         let mut cfg = Config::default();
-        if let Some(config_content) = read_config_file_to_string(paths, file_system) {
+        if let Some((config_path, config_content)) = read_config_file_to_string(paths, file_system)
+        {
             if let Ok(toml_mapped_values) = config_content.parse::<Value>() {
+                let mut errors = Vec::new();
                 cfg.icons.update_from_file(&toml_mapped_values);
                 cfg.keyboard_cfg.update_from_file(&toml_mapped_values);
                 cfg.file_associated_programs
                     .update_from_file(&toml_mapped_values);
-                cfg.core_cfg.update_from_file(&toml_mapped_values);
+                cfg.core_cfg
+                    .update_from_file(&toml_mapped_values, &mut errors);
                 cfg.hotkey_commands_programs
                     .update_from_file(&toml_mapped_values);
-                cfg.tab_config.update_from_file(&toml_mapped_values);
+                cfg.tab_config
+                    .update_from_file(&toml_mapped_values, &mut errors);
+                cfg.errors = errors
+                    .into_iter()
+                    .map(|error| {
+                        AppError::new(
+                            FileOperation::ReadConfig,
+                            vec![config_path.clone()],
+                            &io::Error::new(io::ErrorKind::InvalidData, error),
+                        )
+                    })
+                    .collect();
             }
         }
         cfg
@@ -64,14 +88,59 @@ impl Config {
 fn read_config_file_to_string<TPath: AsRef<Path>, TFileSystem: FileSystem>(
     paths: Vec<TPath>,
     file_system: &TFileSystem,
-) -> Option<String> {
+) -> Option<(PathBuf, String)> {
     for path in paths {
         if let Some(path) = expand_if_contains_tilde(path) {
             match file_system.read_to_string(&path) {
-                Some(content) => return Some(content.clone()),
+                Some(content) => return Some((path, content)),
                 None => continue,
             }
         }
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        app::{
+            config::tab_config::{SortEnum, SortField, SortKey},
+            file_system::in_memory::InMemoryFileSystem,
+        },
+        core::config::Column,
+    };
+
+    use super::Config;
+
+    #[test]
+    fn unknown_sort_entries_and_columns_are_reported() {
+        let file_system = InMemoryFileSystem::default();
+        file_system
+            .add_file(
+                "/rufus.toml",
+                b"[core]\nsort = [\"mtime desc\", \"mtime sideways\", \"colour\", 3]\ncolumns = [\"Name\", \"colour\"]\n",
+            )
+            .unwrap();
+
+        let cfg = Config::load_or_default(vec!["/rufus.toml"], &file_system);
+
+        assert_eq!(
+            cfg.tab_config.sort_fields,
+            vec![SortField::new(SortKey::Modified, SortEnum::DESC)]
+        );
+        assert_eq!(cfg.core_cfg.columns, vec![Column::Name]);
+        let errors: Vec<String> = cfg.errors.iter().map(|error| error.to_string()).collect();
+        assert_eq!(errors.len(), 4, "{:?}", errors);
+        assert!(
+            errors[0].contains("unknown column \"colour\""),
+            "{:?}",
+            errors
+        );
+        assert!(errors[1].contains("\"mtime sideways\""), "{:?}", errors);
+        assert!(
+            errors[0].starts_with("Cannot read config /rufus.toml"),
+            "{:?}",
+            errors
+        );
+    }
+}
@@ -1,8 +1,10 @@
 // use std::collections::HashMap;
 
+use std::fmt::{self, Display};
+
 use toml::Value;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SortEnum {
     ASC,
     DESC,
@@ -15,6 +17,11 @@ pub struct TabConfig {
     pub sort_by_name: SortEnum,
     pub sort_by_date: SortEnum,
     pub sort_by_attr: SortEnum,
+    /// Keys from the `sort` option, when it is set the `sort_by_*` options
+    /// and `directory_first` are not used.
+    pub sort_fields: Vec<SortField>,
+    pub natural_sort: bool,
+    pub sort_ignore_case: bool,
 }
 
 impl Default for TabConfig {
@@ -24,16 +31,163 @@ impl Default for TabConfig {
             sort_by_name: SortEnum::NONE,
             sort_by_date: SortEnum::NONE,
             sort_by_attr: SortEnum::NONE,
+            sort_fields: Vec::new(),
+            natural_sort: true,
+            sort_ignore_case: false,
         }
     }
 }
-/*
+
 impl TabConfig {
-    pub fn set_sort_by_name(&mut self, sort: SortEnum) {
-        self.sort_by_name = sort.clone();
+    /// The sort new tabs start with.
+    pub fn sort(&self) -> TabSort {
+        let fields = if self.sort_fields.is_empty() {
+            let mut fields = Vec::new();
+            if self.directory_first {
+                fields.push(SortField::new(SortKey::Type, SortEnum::ASC));
+            }
+            fields.extend(
+                [
+                    (SortKey::Name, self.sort_by_name),
+                    (SortKey::Size, self.sort_by_attr),
+                    (SortKey::Modified, self.sort_by_date),
+                ]
+                .iter()
+                .filter(|(_, order)| *order != SortEnum::NONE)
+                .map(|&(key, order)| SortField::new(key, order)),
+            );
+            fields
+        } else {
+            self.sort_fields.clone()
+        };
+        TabSort {
+            fields,
+            natural: self.natural_sort,
+            ignore_case: self.sort_ignore_case,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Directories, then symlinks, then files.
+    Type,
+    Name,
+    Extension,
+    Size,
+    Modified,
+    Created,
+    Owner,
+    Permissions,
+}
+
+impl SortKey {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "type" => Some(SortKey::Type),
+            "name" => Some(SortKey::Name),
+            "extension" | "ext" => Some(SortKey::Extension),
+            "size" => Some(SortKey::Size),
+            "mtime" => Some(SortKey::Modified),
+            "ctime" => Some(SortKey::Created),
+            "owner" => Some(SortKey::Owner),
+            "permissions" | "perms" => Some(SortKey::Permissions),
+            _ => None,
+        }
+    }
+}
+
+impl Display for SortKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SortKey::Type => write!(f, "type"),
+            SortKey::Name => write!(f, "name"),
+            SortKey::Extension => write!(f, "extension"),
+            SortKey::Size => write!(f, "size"),
+            SortKey::Modified => write!(f, "mtime"),
+            SortKey::Created => write!(f, "ctime"),
+            SortKey::Owner => write!(f, "owner"),
+            SortKey::Permissions => write!(f, "permissions"),
+        }
+    }
+}
+
+/// One key of a sort, with its own direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortField {
+    pub key: SortKey,
+    pub order: SortEnum,
+}
+
+impl SortField {
+    pub fn new(key: SortKey, order: SortEnum) -> Self {
+        SortField { key, order }
+    }
+
+    /// Parses `"mtime"`, `"mtime asc"` or `"mtime desc"`.
+    fn parse(field: &str) -> Option<Self> {
+        let mut words = field.split_whitespace();
+        let key = SortKey::parse(&words.next()?.to_lowercase())?;
+        let order = match words.next().map(|order| order.to_lowercase()).as_deref() {
+            None | Some("asc") => SortEnum::ASC,
+            Some("desc") => SortEnum::DESC,
+            Some(_) => return None,
+        };
+        Some(SortField::new(key, order))
+    }
+}
+
+impl Display for SortField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.order {
+            SortEnum::DESC => write!(f, "{} desc", self.key),
+            _ => write!(f, "{} asc", self.key),
+        }
+    }
+}
+
+/// How a tab sorts its items, each tab can switch it at runtime. Items are
+/// compared by the first field and ties go on to the next one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabSort {
+    pub fields: Vec<SortField>,
+    /// Compares runs of digits in names by their value, so `file2` comes
+    /// before `file10` and `v1.9` before `v1.10`.
+    pub natural: bool,
+    pub ignore_case: bool,
+}
+
+impl TabSort {
+    /// Sorts by `key` alone, keeping directories first when they were. Ties
+    /// are sorted by name.
+    pub fn by(&self, key: SortKey, order: SortEnum) -> Self {
+        let mut fields: Vec<SortField> = self
+            .fields
+            .first()
+            .filter(|field| field.key == SortKey::Type && key != SortKey::Type)
+            .cloned()
+            .into_iter()
+            .collect();
+        fields.push(SortField::new(key, order));
+        if key != SortKey::Name {
+            fields.push(SortField::new(SortKey::Name, SortEnum::ASC));
+        }
+        TabSort {
+            fields,
+            ..self.clone()
+        }
+    }
+}
+
+impl Display for TabSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.fields.is_empty() {
+            return write!(f, "unsorted");
+        }
+        let fields: Vec<String> = self.fields.iter().map(|field| field.to_string()).collect();
+        write!(f, "{}", fields.join(", "))
     }
 }
-*/
 
 fn get_bool_by_name(cfg: &Value, section: String, name: String) -> Value {
     if let Some(core) = cfg.get(section) {
@@ -56,7 +210,8 @@ fn get_sort_by_name(cfg: &Value, section: String, name: String) -> SortEnum {
     return SortEnum::NONE;
 }
 impl TabConfig {
-    pub fn update_from_file(&mut self, cfg: &Value) {
+    /// Entries which can't be used are described in `errors`.
+    pub fn update_from_file(&mut self, cfg: &Value, errors: &mut Vec<String>) {
         self.directory_first =
             get_bool_by_name(cfg, String::from("core"), String::from("directory_first"))
                 .as_bool()
@@ -67,5 +222,27 @@ impl TabConfig {
             get_sort_by_name(cfg, String::from("core"), String::from("sort_by_date"));
         self.sort_by_attr =
             get_sort_by_name(cfg, String::from("core"), String::from("sort_by_attr"));
+
+        if let Some(core) = cfg.get("core") {
+            if let Some(Value::Array(fields)) = core.get("sort") {
+                let mut sort_fields = Vec::new();
+                for field in fields {
+                    match field.as_str().and_then(SortField::parse) {
+                        Some(sort_field) => sort_fields.push(sort_field),
+                        None => errors.push(format!(
+                            "unknown sort entry {}, expected a key like \"mtime desc\"",
+                            field
+                        )),
+                    }
+                }
+                self.sort_fields = sort_fields;
+            }
+            if let Some(Value::Boolean(natural_sort)) = core.get("natural_sort") {
+                self.natural_sort = *natural_sort;
+            }
+            if let Some(Value::Boolean(sort_ignore_case)) = core.get("sort_ignore_case") {
+                self.sort_ignore_case = *sort_ignore_case;
+            }
+        }
     }
 }
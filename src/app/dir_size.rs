@@ -0,0 +1,138 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    thread,
+    time::SystemTime,
+};
+
+use super::{
+    actions::{DirSizeAction, FileManagerActions},
+    file_system::{functions::format_size, FileSystem},
+};
+
+/// Recursive totals of a directory. Hard links are counted once and symlinks
+/// are not followed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DirSize {
+    pub bytes: u64,
+    pub files: u64,
+    pub dirs: u64,
+}
+
+impl Display for DirSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} in {} {}",
+            format_size(self.bytes),
+            self.files,
+            if self.files == 1 { "file" } else { "files" }
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CachedDirSize {
+    modified: SystemTime,
+    size: DirSize,
+}
+
+/// Sizes computed by background scans, keyed by path. An entry is used only
+/// while the directory has not been modified since it was scanned, so
+/// changes deeper in the tree need a rescan unless rufus made them.
+#[derive(Clone, Debug, Default)]
+pub struct DirSizes {
+    entries: HashMap<PathBuf, CachedDirSize>,
+    scanning: HashSet<PathBuf>,
+}
+
+impl DirSizes {
+    pub fn get(&self, path: &Path, modified: SystemTime) -> Option<DirSize> {
+        self.entries
+            .get(path)
+            .filter(|entry| entry.modified >= modified)
+            .map(|entry| entry.size)
+    }
+
+    pub fn is_scanning(&self, path: &Path) -> bool {
+        self.scanning.contains(path)
+    }
+
+    pub fn start_scan(&mut self, paths: &[PathBuf]) {
+        self.scanning.extend(paths.iter().cloned());
+    }
+
+    pub fn insert(&mut self, path: PathBuf, modified: SystemTime, size: DirSize) {
+        self.scanning.remove(&path);
+        self.entries.insert(path, CachedDirSize { modified, size });
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.scanning.remove(path);
+        self.entries.remove(path);
+    }
+
+    /// Drops the sizes of `path` and of every directory above it, which
+    /// all include whatever changed at `path`.
+    pub fn forget_with_ancestors(&mut self, path: &Path) {
+        for dir in path.ancestors() {
+            self.entries.remove(dir);
+        }
+    }
+}
+
+/// Scans `paths` one after another on a worker thread and sends the size of
+/// each directory as soon as it is known.
+pub fn scan_dir_sizes<TFileSystem: Clone + FileSystem + Send + 'static>(
+    file_system: TFileSystem,
+    paths: Vec<PathBuf>,
+    sender: Sender<FileManagerActions>,
+) {
+    thread::spawn(move || {
+        for path in paths {
+            let action = match file_system.metadata(&path) {
+                Ok(metadata) => DirSizeAction::Calculated {
+                    size: dir_size(&file_system, &path),
+                    path,
+                    modified: metadata.modified,
+                },
+                Err(_) => DirSizeAction::Failed { path },
+            };
+            if sender.send(FileManagerActions::DirSize(action)).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Walks the tree below `path`, skipping whatever can't be read.
+pub fn dir_size<TFileSystem: FileSystem>(file_system: &TFileSystem, path: &Path) -> DirSize {
+    let mut size = DirSize::default();
+    let mut linked = HashSet::new();
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let children = match file_system.read_dir(&dir) {
+            Ok(children) => children,
+            Err(_) => continue,
+        };
+        for child in children {
+            let metadata = match file_system.metadata(&child) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            if metadata.is_dir() {
+                size.dirs += 1;
+                pending.push(child);
+                continue;
+            }
+            if metadata.nlink > 1 && !linked.insert((metadata.dev, metadata.inode)) {
+                continue;
+            }
+            size.files += 1;
+            size.bytes += metadata.size;
+        }
+    }
+    size
+}
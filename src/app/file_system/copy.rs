@@ -0,0 +1,326 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    item_metadata::{ItemMetadata, ItemType},
+    FileSystem,
+};
+
+/// What a recursive copy does with FIFOs and device nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpecialFiles {
+    Skip,
+    Recreate,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CopyOptions {
+    /// Copies what symlinks point to instead of the links themselves.
+    pub dereference_symlinks: bool,
+    pub special_files: SpecialFiles,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        CopyOptions {
+            dereference_symlinks: false,
+            special_files: SpecialFiles::Recreate,
+        }
+    }
+}
+
+/// Hooks into `copy_recursive`, used by jobs to report progress, stop the
+/// copy and resolve conflicts.
+pub trait CopyObserver {
+    /// Called before an item is copied. Returns the path the item should be
+    /// copied to, or `None` to skip it together with its content.
+    fn before_item(
+        &mut self,
+        from: &Path,
+        to: &Path,
+        metadata: &ItemMetadata,
+    ) -> io::Result<Option<PathBuf>>;
+
+    /// Called once an item other than a directory has been written, with the
+    /// number of bytes written for it.
+    fn item_copied(&mut self, from: &Path, metadata: &ItemMetadata, bytes: u64);
+}
+
+/// Observer which lets every item be copied to the requested path.
+pub struct CopyEverything;
+
+impl CopyObserver for CopyEverything {
+    fn before_item(
+        &mut self,
+        _from: &Path,
+        to: &Path,
+        _metadata: &ItemMetadata,
+    ) -> io::Result<Option<PathBuf>> {
+        Ok(Some(to.to_path_buf()))
+    }
+
+    fn item_copied(&mut self, _from: &Path, _metadata: &ItemMetadata, _bytes: u64) {}
+}
+
+/// Copies `from` to `to` recursively and returns the number of bytes written.
+///
+/// Symlinks are recreated as symlinks unless `dereference_symlinks` is set,
+/// FIFOs and device nodes are recreated or skipped and sockets are always
+/// skipped. Mode, times and, where permitted, the owner are copied as well.
+/// Content is merged into directories which already exist.
+pub fn copy_recursive<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    from: &Path,
+    to: &Path,
+    options: &CopyOptions,
+    observer: &mut dyn CopyObserver,
+) -> io::Result<u64> {
+    if to.starts_with(from) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("can't copy {} into itself", from.display()),
+        ));
+    }
+
+    Copier {
+        file_system,
+        options,
+        observer,
+        ancestors: Vec::new(),
+    }
+    .copy(from, to)
+}
+
+/// Checks that `to` has the same tree as `from` and that every file has the
+/// same size. Items the copy skips on purpose are not compared, they are
+/// added to `skipped` instead.
+pub fn verify_copy<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    from: &Path,
+    to: &Path,
+    options: &CopyOptions,
+    skipped: &mut Vec<PathBuf>,
+) -> io::Result<()> {
+    let source = file_system.metadata(from)?;
+    let is_skipped = match source.item_type {
+        ItemType::Socket | ItemType::Unknown => true,
+        _ => source.is_special() && options.special_files == SpecialFiles::Skip,
+    };
+    if is_skipped {
+        skipped.push(from.to_path_buf());
+        return Ok(());
+    }
+    let copy = file_system.metadata(to)?;
+    if source.item_type != copy.item_type || (source.is_file() && source.size != copy.size) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{} differs from {}", to.display(), from.display()),
+        ));
+    }
+    if source.is_dir() {
+        for child in file_system.read_dir(from)? {
+            if let Some(name) = child.file_name() {
+                verify_copy(file_system, &child, &to.join(name), options, skipped)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Deletes `path` with its content, the `skipped` items and the directories
+/// holding them are kept.
+pub fn delete_except<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    path: &Path,
+    skipped: &[PathBuf],
+) -> io::Result<()> {
+    if skipped.iter().any(|item| item == path) {
+        return Ok(());
+    }
+    let is_dir = file_system.metadata(path)?.is_dir();
+    if !is_dir {
+        return file_system.delete_file(path);
+    }
+    if !skipped.iter().any(|item| item.starts_with(path)) {
+        return file_system.delete_dir(path);
+    }
+    for child in file_system.read_dir(path)? {
+        delete_except(file_system, &child, skipped)?;
+    }
+    Ok(())
+}
+
+/// Moves `from` to the free path `to` on another device: the item is copied,
+/// the copy is compared with the source and only then the source is deleted.
+/// When anything goes wrong, or an item can't be copied, the copy is removed
+/// and the source is left untouched.
+pub fn move_by_copy<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    from: &Path,
+    to: &Path,
+) -> io::Result<()> {
+    if file_system.exist(to) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", to.display()),
+        ));
+    }
+    let options = CopyOptions {
+        dereference_symlinks: false,
+        special_files: SpecialFiles::Recreate,
+    };
+    let mut skipped = Vec::new();
+    let copied = copy_recursive(file_system, from, to, &options, &mut CopyEverything)
+        .and_then(|_| verify_copy(file_system, from, to, &options, &mut skipped))
+        .and_then(|_| match skipped.first() {
+            Some(item) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("{} can't be copied", item.display()),
+            )),
+            None => Ok(()),
+        });
+    if let Err(err) = copied {
+        if file_system.exist(to) {
+            let _ = delete_except(file_system, to, &[]);
+        }
+        return Err(err);
+    }
+    delete_except(file_system, from, &[])
+}
+
+struct Copier<'a, TFileSystem: FileSystem> {
+    file_system: &'a mut TFileSystem,
+    options: &'a CopyOptions,
+    observer: &'a mut dyn CopyObserver,
+    /// Device and inode of the directories being copied, used to stop on
+    /// dereferenced symlinks which point back to one of them.
+    ancestors: Vec<(u64, u64)>,
+}
+
+impl<'a, TFileSystem: FileSystem> Copier<'a, TFileSystem> {
+    fn copy(&mut self, from: &Path, to: &Path) -> io::Result<u64> {
+        let metadata = self.metadata(from).map_err(|err| with_path(err, from))?;
+        let to = match self.observer.before_item(from, to, &metadata)? {
+            Some(to) => to,
+            None => return Ok(0),
+        };
+
+        let bytes = match metadata.item_type {
+            ItemType::Directory => return self.copy_dir(from, &to, &metadata),
+            ItemType::File => self
+                .file_system
+                .copy_file(from, &to)
+                .map_err(|err| with_path(err, &to))?,
+            ItemType::Symlink => {
+                let target = self
+                    .file_system
+                    .read_link(from)
+                    .map_err(|err| with_path(err, from))?;
+                self.remove_existing(&to)?;
+                self.file_system
+                    .create_symlink(target.as_path(), &to)
+                    .map_err(|err| with_path(err, &to))?;
+                0
+            }
+            ItemType::Fifo | ItemType::BlockDevice | ItemType::CharDevice => {
+                if self.options.special_files == SpecialFiles::Skip {
+                    return Ok(0);
+                }
+                self.remove_existing(&to)?;
+                self.file_system
+                    .create_special_file(&to, &metadata)
+                    .map_err(|err| with_path(err, &to))?;
+                0
+            }
+            // Sockets only make sense while a process listens on them.
+            ItemType::Socket | ItemType::Unknown => return Ok(0),
+        };
+
+        self.preserve_metadata(&to, &metadata)
+            .map_err(|err| with_path(err, &to))?;
+        self.observer.item_copied(from, &metadata, bytes);
+        Ok(bytes)
+    }
+
+    fn copy_dir(&mut self, from: &Path, to: &Path, metadata: &ItemMetadata) -> io::Result<u64> {
+        let id = (metadata.dev, metadata.inode);
+        if self.ancestors.contains(&id) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} points back to a parent directory", from.display()),
+            ));
+        }
+        if !self.file_system.exist(to) {
+            self.file_system
+                .create_dir(to)
+                .map_err(|err| with_path(err, to))?;
+        }
+        let children = self
+            .file_system
+            .read_dir(from)
+            .map_err(|err| with_path(err, from))?;
+
+        self.ancestors.push(id);
+        let mut bytes = 0;
+        for child in children {
+            if let Some(name) = child.file_name() {
+                bytes += self.copy(&child, &to.join(name))?;
+            }
+        }
+        self.ancestors.pop();
+
+        // Writing the content updates the times, so they are set last.
+        self.preserve_metadata(to, metadata)
+            .map_err(|err| with_path(err, to))?;
+        Ok(bytes)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<ItemMetadata> {
+        let metadata = self.file_system.metadata(path)?;
+        if metadata.is_symlink() && self.options.dereference_symlinks {
+            let target = self.file_system.canonicalize(path)?;
+            self.file_system.metadata(target)
+        } else {
+            Ok(metadata)
+        }
+    }
+
+    fn remove_existing(&mut self, path: &Path) -> io::Result<()> {
+        let is_dir = match self.file_system.metadata(path) {
+            Ok(metadata) => metadata.is_dir(),
+            Err(_) => return Ok(()),
+        };
+        if is_dir {
+            Err(io::Error::new(
+                io::ErrorKind::IsADirectory,
+                format!("{} is a directory", path.display()),
+            ))
+        } else {
+            self.file_system
+                .delete_file(path)
+                .map_err(|err| with_path(err, path))
+        }
+    }
+
+    fn preserve_metadata(&mut self, path: &Path, metadata: &ItemMetadata) -> io::Result<()> {
+        // Only root can give items away, so a refused owner change is fine.
+        // It goes first as it can clear the setuid and setgid bits.
+        match self.file_system.set_owner(path, metadata.uid, metadata.gid) {
+            Err(err) if err.kind() != io::ErrorKind::PermissionDenied => return Err(err),
+            _ => {}
+        }
+        // The mode of a symlink itself can't be changed on Linux.
+        if !metadata.is_symlink() {
+            self.file_system
+                .set_permissions(path, metadata.mode & 0o7777)?;
+        }
+        self.file_system
+            .set_times(path, metadata.accessed, metadata.modified)
+    }
+}
+
+fn with_path(err: io::Error, path: &Path) -> io::Error {
+    io::Error::new(err.kind(), format!("{}: {}", path.display(), err))
+}
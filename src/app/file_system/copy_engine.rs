@@ -0,0 +1,186 @@
+use std::{io, path::Path};
+
+/// Copies the content and the permissions of a regular file, the same as
+/// `fs::copy`, with the cheapest mechanism the file systems support. A
+/// reflink shares the blocks on copy-on-write file systems, `copy_file_range`
+/// keeps the data in the kernel and buffered copying works everywhere. Holes
+/// of sparse files are kept.
+#[cfg(target_os = "linux")]
+pub fn copy_file(from: &Path, to: &Path) -> io::Result<u64> {
+    linux::copy_file(from, to)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn copy_file(from: &Path, to: &Path) -> io::Result<u64> {
+    std::fs::copy(from, to)
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        fs::{self, File, OpenOptions},
+        io,
+        os::unix::{
+            fs::{FileExt, MetadataExt, OpenOptionsExt},
+            io::AsRawFd,
+        },
+        path::Path,
+    };
+
+    /// `_IOW(0x94, 9, int)` from `linux/fs.h`.
+    const FICLONE: libc::c_ulong = 0x4004_9409;
+    const MAX_RANGE_CHUNK: u64 = 1 << 30;
+    const BUFFER_SIZE: usize = 128 * 1024;
+
+    pub fn copy_file(from: &Path, to: &Path) -> io::Result<u64> {
+        let source = File::open(from)?;
+        let metadata = source.metadata()?;
+        if !metadata.is_file() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a regular file", from.display()),
+            ));
+        }
+        // Opening the target would truncate the source.
+        if let Ok(existing) = fs::metadata(to) {
+            if existing.dev() == metadata.dev() && existing.ino() == metadata.ino() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} and {} are the same file", from.display(), to.display()),
+                ));
+            }
+        }
+
+        let target = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(metadata.mode())
+            .open(to)?;
+        target.set_permissions(metadata.permissions())?;
+
+        let len = metadata.len();
+        if reflink(&source, &target).is_ok() {
+            return Ok(len);
+        }
+
+        let mut copier = RangeCopier {
+            source: &source,
+            target: &target,
+            use_copy_file_range: true,
+        };
+        if metadata.blocks() * 512 < len {
+            for (start, end) in data_segments(&source, len)? {
+                copier.copy(start, end)?;
+            }
+        } else {
+            copier.copy(0, len)?;
+        }
+        // Recreates the hole at the end of a sparse file.
+        target.set_len(len)?;
+        Ok(len)
+    }
+
+    fn reflink(source: &File, target: &File) -> io::Result<()> {
+        let result = unsafe { libc::ioctl(target.as_raw_fd(), FICLONE as _, source.as_raw_fd()) };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    /// Ranges of the file which hold data, found with `SEEK_DATA` and
+    /// `SEEK_HOLE`. The whole file is one range when the file system can't
+    /// tell where the holes are.
+    fn data_segments(file: &File, len: u64) -> io::Result<Vec<(u64, u64)>> {
+        let fd = file.as_raw_fd();
+        let mut segments = Vec::new();
+        let mut offset = 0;
+        while offset < len {
+            let data = unsafe { libc::lseek(fd, offset as libc::off_t, libc::SEEK_DATA) };
+            if data < 0 {
+                let err = io::Error::last_os_error();
+                match err.raw_os_error() {
+                    // Nothing but a hole up to the end of the file.
+                    Some(libc::ENXIO) => break,
+                    Some(libc::EINVAL) => {
+                        segments.push((offset, len));
+                        break;
+                    }
+                    _ => return Err(err),
+                }
+            }
+            let hole = unsafe { libc::lseek(fd, data, libc::SEEK_HOLE) };
+            if hole < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            segments.push((data as u64, (hole as u64).min(len)));
+            offset = hole as u64;
+        }
+        Ok(segments)
+    }
+
+    struct RangeCopier<'a> {
+        source: &'a File,
+        target: &'a File,
+        /// Cleared once the kernel refuses `copy_file_range` for this pair of
+        /// files, e.g. between file systems on kernels older than 5.3.
+        use_copy_file_range: bool,
+    }
+
+    impl<'a> RangeCopier<'a> {
+        fn copy(&mut self, mut offset: u64, end: u64) -> io::Result<()> {
+            while offset < end && self.use_copy_file_range {
+                match self.copy_file_range(offset, (end - offset).min(MAX_RANGE_CHUNK)) {
+                    Ok(0) => return Ok(()),
+                    Ok(copied) => offset += copied,
+                    Err(err) => match err.raw_os_error() {
+                        Some(libc::ENOSYS)
+                        | Some(libc::EXDEV)
+                        | Some(libc::EINVAL)
+                        | Some(libc::EOPNOTSUPP)
+                        | Some(libc::EPERM) => self.use_copy_file_range = false,
+                        Some(libc::EINTR) => {}
+                        _ => return Err(err),
+                    },
+                }
+            }
+
+            let mut buffer = vec![0; BUFFER_SIZE];
+            while offset < end {
+                let wanted = ((end - offset) as usize).min(BUFFER_SIZE);
+                let read = match self.source.read_at(&mut buffer[..wanted], offset) {
+                    Ok(0) => return Ok(()),
+                    Ok(read) => read,
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(err) => return Err(err),
+                };
+                self.target.write_all_at(&buffer[..read], offset)?;
+                offset += read as u64;
+            }
+            Ok(())
+        }
+
+        fn copy_file_range(&self, offset: u64, len: u64) -> io::Result<u64> {
+            let mut offset_in = offset as libc::loff_t;
+            let mut offset_out = offset as libc::loff_t;
+            let copied = unsafe {
+                libc::syscall(
+                    libc::SYS_copy_file_range,
+                    self.source.as_raw_fd(),
+                    &mut offset_in as *mut libc::loff_t,
+                    self.target.as_raw_fd(),
+                    &mut offset_out as *mut libc::loff_t,
+                    len as libc::size_t,
+                    0 as libc::c_uint,
+                )
+            };
+            if copied < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(copied as u64)
+            }
+        }
+    }
+}
@@ -6,7 +6,7 @@ use tui::{
     text::{Span, Spans},
 };
 
-use crate::core::ToSpans;
+use crate::{app::dir_size::DirSize, core::ToSpans};
 
 #[derive(Clone, Debug)]
 pub struct DirItem {
@@ -14,7 +14,6 @@ pub struct DirItem {
     path: PathBuf,
     last_modification: DateTime<Local>,
     icon: String,
-    is_empty: bool,
 
     pub created: DateTime<Local>,
     pub modified: DateTime<Local>,
@@ -27,6 +26,9 @@ pub struct DirItem {
     pub groupname: String,
     pub blocksize: u64,
     pub blocks: u64,
+    /// Recursive totals once a scan has computed them, `size` then holds
+    /// the total bytes instead of the size of the directory entry.
+    pub content: Option<DirSize>,
 }
 
 impl DirItem {
@@ -35,7 +37,6 @@ impl DirItem {
         path: PathBuf,
         last_modification: DateTime<Local>,
         icon: String,
-        is_empty: bool,
 
         created: DateTime<Local>,
         modified: DateTime<Local>,
@@ -54,7 +55,6 @@ impl DirItem {
             path,
             last_modification,
             icon,
-            is_empty,
 
             created,
             modified,
@@ -67,6 +67,7 @@ impl DirItem {
             groupname,
             blocksize,
             blocks,
+            content: None,
         }
     }
 
@@ -81,10 +82,6 @@ impl DirItem {
     pub fn is_visible(&self) -> bool {
         self.name.starts_with('.')
     }
-
-    pub fn is_empty(&self) -> bool {
-        self.is_empty
-    }
 }
 
 impl ToSpans for DirItem {
@@ -116,6 +113,14 @@ impl ToSpans for DirItem {
             ])
         }
         */
-        Spans::from(vec![Span::from("  /"), Span::from(self.name.clone())])
+        match self.content {
+            Some(content) => Spans::from(vec![
+                Span::from("  /"),
+                Span::from(self.name.clone()),
+                Span::from("  "),
+                Span::from(content.to_string()),
+            ]),
+            None => Spans::from(vec![Span::from("  /"), Span::from(self.name.clone())]),
+        }
     }
 }
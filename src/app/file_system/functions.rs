@@ -1,5 +1,6 @@
 #[cfg(unix)]
 use std::os::unix::fs;
+use std::os::unix::fs::FileTypeExt;
 use std::os::unix::fs::MetadataExt;
 use std::os::unix::fs::PermissionsExt;
 
@@ -7,22 +8,38 @@ use std::os::unix::fs::PermissionsExt;
 use std::os::windows::fs;
 
 use std::{
-    fs::{read_link, DirEntry, Metadata},
+    cmp,
+    collections::HashMap,
+    fs::Metadata,
     io,
     path::{Path, PathBuf},
+    sync::Mutex,
     time::SystemTime,
 };
 use users::{get_group_by_gid, get_user_by_uid};
 
 use chrono::{DateTime, Local};
 
-use crate::app::config::icon_cfg::IconsConfig;
+use crate::app::config::{
+    icon_cfg::IconsConfig,
+    tab_config::{SortEnum, SortKey, TabSort},
+    Config,
+};
 
 use super::{
-    dir_item::DirItem, file_item::FileItem, file_system_item::FileSystemItem,
+    dir_item::DirItem,
+    file_item::FileItem,
+    file_system_item::FileSystemItem,
+    item_metadata::{ItemMetadata, ItemType},
     symlink_item::SymlinkItem,
+    FileSystem,
 };
 
+lazy_static! {
+    static ref USER_NAMES: Mutex<HashMap<u32, String>> = Mutex::new(HashMap::new());
+    static ref GROUP_NAMES: Mutex<HashMap<u32, String>> = Mutex::new(HashMap::new());
+}
+
 struct FileSystemItemProps {
     name: String,
     path: PathBuf,
@@ -77,16 +94,15 @@ pub fn expand_if_contains_tilde<TPath: AsRef<Path>>(input: TPath) -> Option<Path
     })
 }
 
-pub fn map_dir_entry_to_file_system_item(
-    dir_entry: DirEntry,
+pub fn map_path_to_file_system_item<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    path: PathBuf,
     icons: &IconsConfig,
 ) -> FileSystemItem {
-    if let Ok(metadata) = dir_entry.metadata() {
-        // let (name, path, modified) = get_file_system_item_props(dir_entry.clone(), &metadata);
-        let file_system_item_props = get_file_system_item_props_struct(dir_entry, &metadata);
+    if let Ok(metadata) = file_system.metadata(&path) {
+        let file_system_item_props = get_file_system_item_props_struct(path, &metadata);
 
-        let file_type = metadata.file_type();
-        if file_type.is_file() {
+        if metadata.is_file() {
             let file_extensions = file_system_item_props.name.split('.').last().unwrap_or("");
             return FileSystemItem::File(FileItem::new(
                 file_system_item_props.name.to_string(),
@@ -107,17 +123,12 @@ pub fn map_dir_entry_to_file_system_item(
             ));
         }
 
-        if file_type.is_dir() {
+        if metadata.is_dir() {
             return FileSystemItem::Directory(DirItem::new(
                 file_system_item_props.name.to_string(),
                 file_system_item_props.path.clone(),
                 file_system_item_props.modified,
                 icons.get_dir_icon(file_system_item_props.name),
-                file_system_item_props
-                    .path
-                    .read_dir()
-                    .map(|mut i| i.next().is_none())
-                    .unwrap_or(false),
                 file_system_item_props.created,
                 file_system_item_props.modified,
                 file_system_item_props.accessed,
@@ -132,16 +143,21 @@ pub fn map_dir_entry_to_file_system_item(
             ));
         }
 
-        if file_type.is_symlink() {
+        if metadata.is_symlink() {
             let file_extensions = file_system_item_props.name.split('.').last().unwrap_or("");
-            match read_link(file_system_item_props.path.clone()) {
+            match file_system.read_link(&file_system_item_props.path) {
                 Ok(target) => {
+                    let target_is_file = file_system
+                        .canonicalize(&file_system_item_props.path)
+                        .and_then(|target_path| file_system.metadata(target_path))
+                        .map(|target_metadata| target_metadata.is_file())
+                        .unwrap_or(false);
                     return FileSystemItem::Symlink(SymlinkItem::new(
                         file_system_item_props.name.to_string(),
                         file_system_item_props.path,
-                        target.clone(),
+                        target,
                         file_system_item_props.modified,
-                        if target.is_file() {
+                        if target_is_file {
                             icons.get_file_icon(file_extensions.to_string())
                         } else {
                             icons.get_dir_icon(file_system_item_props.name)
@@ -157,7 +173,7 @@ pub fn map_dir_entry_to_file_system_item(
                         file_system_item_props.groupname,
                         file_system_item_props.blocksize,
                         file_system_item_props.blocks,
-                    ))
+                    ));
                 }
                 Err(_) => {
                     return FileSystemItem::Symlink(SymlinkItem::new(
@@ -177,7 +193,7 @@ pub fn map_dir_entry_to_file_system_item(
                         file_system_item_props.groupname,
                         file_system_item_props.blocksize,
                         file_system_item_props.blocks,
-                    ))
+                    ));
                 }
             }
         }
@@ -188,97 +204,309 @@ pub fn map_dir_entry_to_file_system_item(
     }
 }
 
-fn get_file_system_item_props(
-    dir_entry: DirEntry,
-    metadata: &Metadata,
-) -> (String, PathBuf, DateTime<Local>) {
-    let modified: DateTime<Local> = if let Ok(last_modified) = metadata.modified() {
-        last_modified.into()
-    } else {
-        SystemTime::now().into()
-    };
+pub fn list_file_system_items<TFileSystem: FileSystem, TPath: AsRef<Path>>(
+    file_system: &TFileSystem,
+    path: TPath,
+    big_config: &Config,
+) -> Vec<FileSystemItem> {
+    match file_system.read_dir(path) {
+        Ok(paths) => map_paths_to_file_system_items(file_system, paths, big_config),
+        Err(_) => Vec::new(),
+    }
+}
 
-    let entry_name = dir_entry.file_name();
-    let name = if let Some(name) = entry_name.to_str() {
-        name
-    } else {
-        ""
-    };
-    let path_buffer = dir_entry.path();
+pub fn map_paths_to_file_system_items<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    paths: Vec<PathBuf>,
+    big_config: &Config,
+) -> Vec<FileSystemItem> {
+    let mut result: Vec<FileSystemItem> = paths
+        .into_iter()
+        .map(|item_path| map_path_to_file_system_item(file_system, item_path, &big_config.icons))
+        .collect();
+    sort_file_system_items(&mut result, &big_config.tab_config.sort());
+    result
+}
 
-    (name.to_string(), path_buffer, modified)
+/// Reads the directory only when asked, listing every directory of a tab
+/// just to know which of them are empty is too slow for big ones.
+pub fn is_empty_dir<TFileSystem: FileSystem>(file_system: &TFileSystem, path: &Path) -> bool {
+    file_system
+        .read_dir(path)
+        .map(|items| items.is_empty())
+        .unwrap_or(false)
 }
 
-fn get_file_system_item_props_struct(
-    dir_entry: DirEntry,
-    metadata: &Metadata,
-) -> FileSystemItemProps {
-    let modified: DateTime<Local> = if let Ok(last_modified) = metadata.modified() {
-        last_modified.into()
+pub fn map_metadata_to_item_metadata(metadata: &Metadata) -> ItemMetadata {
+    let file_type = metadata.file_type();
+    let item_type = if file_type.is_symlink() {
+        ItemType::Symlink
+    } else if file_type.is_dir() {
+        ItemType::Directory
+    } else if file_type.is_file() {
+        ItemType::File
+    } else if file_type.is_fifo() {
+        ItemType::Fifo
+    } else if file_type.is_socket() {
+        ItemType::Socket
+    } else if file_type.is_block_device() {
+        ItemType::BlockDevice
+    } else if file_type.is_char_device() {
+        ItemType::CharDevice
     } else {
-        SystemTime::now().into()
+        ItemType::Unknown
     };
 
-    let entry_name = dir_entry.file_name();
-    let name = if let Some(name) = entry_name.to_str() {
-        name
-    } else {
-        ""
-    };
-    let path_buffer = dir_entry.path();
-
-    // (name.to_string(), path_buffer, modified)
-    let size = metadata.len();
-    let mode = metadata.permissions().mode();
-
-    let inode = metadata.ino();
-    let nlink = metadata.nlink();
-    let username = get_user_by_uid(metadata.uid())
-        .unwrap()
-        .name()
-        .to_string_lossy()
-        .to_string();
-    let groupname = get_group_by_gid(metadata.gid())
-        .unwrap()
-        .name()
-        .to_string_lossy()
-        .to_string();
-    let blocksize = metadata.blksize();
-    let blocks = metadata.blocks();
-
-    let created: DateTime<Local> = if let Ok(last_modified) = metadata.created() {
-        last_modified.into()
-    } else {
-        SystemTime::now().into()
-    };
+    let username = user_name(metadata.uid());
+    let groupname = group_name(metadata.gid());
 
-    let modified: DateTime<Local> = if let Ok(last_modified) = metadata.modified() {
-        last_modified.into()
+    ItemMetadata {
+        item_type,
+        size: metadata.len(),
+        mode: metadata.permissions().mode(),
+        dev: metadata.dev(),
+        rdev: metadata.rdev(),
+        inode: metadata.ino(),
+        nlink: metadata.nlink(),
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+        username,
+        groupname,
+        blocksize: metadata.blksize(),
+        blocks: metadata.blocks(),
+        created: metadata.created().unwrap_or_else(|_| SystemTime::now()),
+        modified: metadata.modified().unwrap_or_else(|_| SystemTime::now()),
+        accessed: metadata.accessed().unwrap_or_else(|_| SystemTime::now()),
+    }
+}
+
+/// Name of the user with `uid`, looked up once per user since reading the
+/// password database for every listed item is slow.
+fn user_name(uid: u32) -> String {
+    let mut names = USER_NAMES.lock().unwrap();
+    names
+        .entry(uid)
+        .or_insert_with(|| {
+            get_user_by_uid(uid)
+                .map(|user| user.name().to_string_lossy().to_string())
+                .unwrap_or_else(|| uid.to_string())
+        })
+        .clone()
+}
+
+/// Name of the group with `gid`, cached like `user_name`.
+fn group_name(gid: u32) -> String {
+    let mut names = GROUP_NAMES.lock().unwrap();
+    names
+        .entry(gid)
+        .or_insert_with(|| {
+            get_group_by_gid(gid)
+                .map(|group| group.name().to_string_lossy().to_string())
+                .unwrap_or_else(|| gid.to_string())
+        })
+        .clone()
+}
+
+/// Sorts the items by the fields of `sort` in order, each field only decides
+/// between the items the fields before it found equal.
+pub fn sort_file_system_items(items: &mut [FileSystemItem], sort: &TabSort) {
+    if sort.fields.is_empty() {
+        return;
+    }
+    items.sort_by(|one, two| compare_items(one, two, sort));
+}
+
+fn compare_items(one: &FileSystemItem, two: &FileSystemItem, sort: &TabSort) -> cmp::Ordering {
+    sort.fields
+        .iter()
+        .map(|field| {
+            let ordering = match field.key {
+                SortKey::Type => type_rank(one).cmp(&type_rank(two)),
+                SortKey::Name => compare_names(&one.get_name(), &two.get_name(), sort),
+                SortKey::Extension => {
+                    let (one_name, two_name) = (one.get_name(), two.get_name());
+                    compare_names(extension(&one_name), extension(&two_name), sort)
+                }
+                SortKey::Size => one.get_size().cmp(&two.get_size()),
+                SortKey::Modified => one.get_modified().cmp(&two.get_modified()),
+                SortKey::Created => one.get_created().cmp(&two.get_created()),
+                SortKey::Owner => compare_names(&one.get_username(), &two.get_username(), sort),
+                SortKey::Permissions => (one.get_mode() & 0o7777).cmp(&(two.get_mode() & 0o7777)),
+            };
+            match field.order {
+                SortEnum::DESC => ordering.reverse(),
+                _ => ordering,
+            }
+        })
+        .find(|ordering| *ordering != cmp::Ordering::Equal)
+        .unwrap_or(cmp::Ordering::Equal)
+}
+
+/// Directories first, then symlinks, then files.
+fn type_rank(item: &FileSystemItem) -> u8 {
+    match item {
+        FileSystemItem::Directory(_) => 0,
+        FileSystemItem::Symlink(_) => 1,
+        FileSystemItem::File(_) => 2,
+        FileSystemItem::Unknown => 3,
+    }
+}
+
+/// The part of the name after the last dot, names starting with their only
+/// dot have none.
+fn extension(name: &str) -> &str {
+    match name.rfind('.') {
+        Some(idx) if idx > 0 => &name[idx + 1..],
+        _ => "",
+    }
+}
+
+/// Names equal when ignoring case or the value of numbers still get a fixed
+/// order from their characters.
+fn compare_names(one: &str, two: &str, sort: &TabSort) -> cmp::Ordering {
+    let ordering = if sort.natural {
+        natural_cmp(one, two, sort.ignore_case)
+    } else if sort.ignore_case {
+        one.to_lowercase().cmp(&two.to_lowercase())
     } else {
-        SystemTime::now().into()
+        cmp::Ordering::Equal
     };
+    ordering.then_with(|| one.cmp(two))
+}
 
-    let accessed: DateTime<Local> = if let Ok(last_modified) = metadata.accessed() {
-        last_modified.into()
-    } else {
-        SystemTime::now().into()
+/// Compares runs of digits by their value and everything else character by
+/// character, so `file2` comes before `file10` and `1.9.3` before `1.10.0`.
+pub fn natural_cmp(one: &str, two: &str, ignore_case: bool) -> cmp::Ordering {
+    let mut one = one.chars().peekable();
+    let mut two = two.chars().peekable();
+    loop {
+        let ordering = match (one.peek().copied(), two.peek().copied()) {
+            (None, None) => return cmp::Ordering::Equal,
+            (None, Some(_)) => return cmp::Ordering::Less,
+            (Some(_), None) => return cmp::Ordering::Greater,
+            (Some(one_char), Some(two_char))
+                if one_char.is_ascii_digit() && two_char.is_ascii_digit() =>
+            {
+                let one_number = take_number(&mut one);
+                let two_number = take_number(&mut two);
+                compare_numbers(&one_number, &two_number)
+            }
+            (Some(one_char), Some(two_char)) => {
+                one.next();
+                two.next();
+                if ignore_case {
+                    lowercase(one_char).cmp(&lowercase(two_char))
+                } else {
+                    one_char.cmp(&two_char)
+                }
+            }
+        };
+        if ordering != cmp::Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+fn take_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> String {
+    let mut number = String::new();
+    while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+        number.push(digit);
+    }
+    number
+}
+
+/// Compares numbers of any length by their digits, leading zeros don't count.
+fn compare_numbers(one: &str, two: &str) -> cmp::Ordering {
+    let one = one.trim_start_matches('0');
+    let two = two.trim_start_matches('0');
+    one.len().cmp(&two.len()).then_with(|| one.cmp(two))
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Returns `path` when nothing exists there yet, otherwise the first free
+/// `name (1).ext`, `name (2).ext` and so on.
+pub fn first_free_path<TFileSystem: FileSystem>(file_system: &TFileSystem, path: &Path) -> PathBuf {
+    let mut target = path.to_path_buf();
+    let mut counter = 1;
+    while file_system.exist(&target) {
+        target = numbered_path(path, counter);
+        counter += 1;
+    }
+    target
+}
+
+fn numbered_path(path: &Path, counter: usize) -> PathBuf {
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let new_name = match file_name.rfind('.') {
+        Some(idx) if idx > 0 => format!("{} ({}){}", &file_name[..idx], counter, &file_name[idx..]),
+        _ => format!("{} ({})", file_name, counter),
     };
+    path.with_file_name(new_name)
+}
+
+/// Formats a size in bytes with a binary unit, e.g. `1.5 MiB`.
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// Formats the permission bits of a mode the way `ls -l` does, e.g.
+/// `rwxr-xr-x`, with the setuid, setgid and sticky bits in the execute slots.
+pub fn format_permissions(mode: u32) -> String {
+    let special = [(0o4000, 's'), (0o2000, 's'), (0o1000, 't')];
+    let mut permissions = String::with_capacity(9);
+    for (idx, &(special_bit, special_char)) in special.iter().enumerate() {
+        let shift = 6 - idx * 3;
+        let bits = (mode >> shift) & 0o7;
+        permissions.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        permissions.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        permissions.push(match (bits & 0o1 != 0, mode & special_bit != 0) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+    permissions
+}
+
+fn get_file_system_item_props_struct(
+    path: PathBuf,
+    metadata: &ItemMetadata,
+) -> FileSystemItemProps {
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
 
     FileSystemItemProps {
         name: name.to_string(),
-        path: path_buffer,
-        created,
-        modified,
-        accessed,
-        size,
-        mode,
-        inode,
-        nlink,
-        username,
-        groupname,
-        blocksize,
-        blocks,
+        path,
+        created: metadata.created.into(),
+        modified: metadata.modified.into(),
+        accessed: metadata.accessed.into(),
+        size: metadata.size,
+        mode: metadata.mode,
+        inode: metadata.inode,
+        nlink: metadata.nlink,
+        username: metadata.username.clone(),
+        groupname: metadata.groupname.clone(),
+        blocksize: metadata.blocksize,
+        blocks: metadata.blocks,
     }
-
-    ////////////////////////////////////////////
 }
@@ -0,0 +1,1359 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Cursor, Read},
+    path::{Component, Path, PathBuf},
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::app::config::Config;
+
+use super::{
+    copy::{copy_recursive, CopyEverything, CopyOptions},
+    file_system_item::FileSystemItem,
+    functions::list_file_system_items,
+    item_metadata::{ItemMetadata, ItemType},
+    DirInfo, FileSystem,
+};
+
+const MAX_SYMLINK_DEPTH: usize = 40;
+const BLOCK_SIZE: u64 = 4096;
+
+#[derive(Clone, Debug)]
+enum NodeKind {
+    File(Vec<u8>),
+    Directory,
+    Symlink(PathBuf),
+    Special { item_type: ItemType, rdev: u64 },
+}
+
+#[derive(Clone, Debug)]
+struct Node {
+    kind: NodeKind,
+    mode: u32,
+    uid: u32,
+    gid: u32,
+    username: String,
+    groupname: String,
+    created: SystemTime,
+    modified: SystemTime,
+    accessed: SystemTime,
+    inode: u64,
+}
+
+#[derive(Debug)]
+struct Tree {
+    nodes: BTreeMap<PathBuf, Node>,
+    current_dir: PathBuf,
+    uid: u32,
+    gid: u32,
+    username: String,
+    groupname: String,
+    clock: SystemTime,
+    next_inode: u64,
+    mount_points: Vec<PathBuf>,
+}
+
+/// File system that keeps the whole tree in memory.
+///
+/// Clones share the same tree, the same way every `PhysicalFileSystem` value
+/// points to the same disk, so state snapshots taken by the store stay
+/// consistent with each other. Timestamps come from a logical clock which
+/// advances by one second on every mutation, which keeps runs deterministic.
+#[derive(Clone, Debug)]
+pub struct InMemoryFileSystem {
+    tree: Arc<Mutex<Tree>>,
+}
+
+impl Default for InMemoryFileSystem {
+    fn default() -> Self {
+        InMemoryFileSystem::with_owner(1000, 1000, "user", "user")
+    }
+}
+
+impl InMemoryFileSystem {
+    pub fn with_owner(uid: u32, gid: u32, username: &str, groupname: &str) -> Self {
+        let clock = UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let mut tree = Tree {
+            nodes: BTreeMap::new(),
+            current_dir: PathBuf::from("/"),
+            uid,
+            gid,
+            username: username.to_string(),
+            groupname: groupname.to_string(),
+            clock,
+            next_inode: 1,
+            mount_points: Vec::new(),
+        };
+        let root = tree.new_node(NodeKind::Directory, 0o40755);
+        tree.nodes.insert(PathBuf::from("/"), root);
+
+        InMemoryFileSystem {
+            tree: Arc::new(Mutex::new(tree)),
+        }
+    }
+
+    /// Directory used to resolve relative paths, `/` by default.
+    pub fn set_current_dir<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), true)?;
+        match tree.nodes.get(&path).map(|node| &node.kind) {
+            Some(NodeKind::Directory) => {
+                tree.current_dir = path;
+                Ok(())
+            }
+            Some(_) => Err(not_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    /// Creates a directory together with all missing parents.
+    pub fn add_dir<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.absolute(path.as_ref());
+        let mut current = PathBuf::from("/");
+        for component in path.components().skip(1) {
+            current.push(component);
+            match tree.nodes.get(&current).map(|node| &node.kind) {
+                Some(NodeKind::Directory) => {}
+                Some(_) => return Err(not_a_directory(&current)),
+                None => {
+                    let node = tree.new_node(NodeKind::Directory, 0o40755);
+                    tree.insert(current.clone(), node);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates or replaces a regular file, creating missing parent directories.
+    pub fn add_file<TPath: AsRef<Path>>(&self, path: TPath, content: &[u8]) -> io::Result<()> {
+        let path = self.lock().absolute(path.as_ref());
+        if let Some(parent) = path.parent() {
+            self.add_dir(parent)?;
+        }
+        let mut tree = self.lock();
+        let node = tree.new_node(NodeKind::File(content.to_vec()), 0o100644);
+        tree.insert(path, node);
+        Ok(())
+    }
+
+    /// Creates a symlink at `path` pointing to `target`, creating missing parent directories.
+    pub fn add_symlink<TPath: AsRef<Path>>(&self, path: TPath, target: TPath) -> io::Result<()> {
+        let path = self.lock().absolute(path.as_ref());
+        if let Some(parent) = path.parent() {
+            self.add_dir(parent)?;
+        }
+        let mut tree = self.lock();
+        let node = tree.new_node(NodeKind::Symlink(target.as_ref().to_path_buf()), 0o120777);
+        tree.insert(path, node);
+        Ok(())
+    }
+
+    /// Creates a directory which acts as the root of another device, so
+    /// renames in or out of it fail the way they do across mount points.
+    pub fn add_mount_point<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<()> {
+        self.add_dir(path.as_ref())?;
+        let mut tree = self.lock();
+        let path = tree.absolute(path.as_ref());
+        tree.mount_points.push(path);
+        Ok(())
+    }
+
+    /// Raw content of a regular file, following symlinks.
+    pub fn read<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<Vec<u8>> {
+        let tree = self.lock();
+        let path = tree.resolve(path.as_ref(), true)?;
+        match tree.nodes.get(&path).map(|node| &node.kind) {
+            Some(NodeKind::File(content)) => Ok(content.clone()),
+            Some(_) => Err(is_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Tree> {
+        match self.tree.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl Tree {
+    fn tick(&mut self) -> SystemTime {
+        self.clock += Duration::from_secs(1);
+        self.clock
+    }
+
+    fn new_node(&mut self, kind: NodeKind, mode: u32) -> Node {
+        let now = self.tick();
+        let inode = self.next_inode;
+        self.next_inode += 1;
+        Node {
+            kind,
+            mode,
+            uid: self.uid,
+            gid: self.gid,
+            username: self.username.clone(),
+            groupname: self.groupname.clone(),
+            created: now,
+            modified: now,
+            accessed: now,
+            inode,
+        }
+    }
+
+    /// Device id of an absolute path: 1 for the root device, otherwise the
+    /// index of the closest mount point above it plus 2.
+    fn device(&self, path: &Path) -> u64 {
+        self.mount_points
+            .iter()
+            .enumerate()
+            .filter(|(_, mount_point)| path.starts_with(mount_point))
+            .max_by_key(|(_, mount_point)| mount_point.components().count())
+            .map(|(idx, _)| idx as u64 + 2)
+            .unwrap_or(1)
+    }
+
+    fn insert(&mut self, path: PathBuf, node: Node) {
+        self.nodes.insert(path.clone(), node);
+        self.touch_parent(&path);
+    }
+
+    fn touch_parent(&mut self, path: &Path) {
+        let now = self.tick();
+        if let Some(parent) = path.parent() {
+            if let Some(node) = self.nodes.get_mut(parent) {
+                node.modified = now;
+            }
+        }
+    }
+
+    fn absolute(&self, path: &Path) -> PathBuf {
+        let mut result = if path.is_absolute() {
+            PathBuf::from("/")
+        } else {
+            self.current_dir.clone()
+        };
+        for component in path.components() {
+            match component {
+                Component::ParentDir => {
+                    result.pop();
+                }
+                Component::Normal(name) => result.push(name),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// Resolves symlinks in every parent component, and in the last one when
+    /// `follow_last` is set, the same way the kernel walks a path.
+    fn resolve(&self, path: &Path, follow_last: bool) -> io::Result<PathBuf> {
+        let mut pending: Vec<PathBuf> = vec![self.absolute(path)];
+        let mut depth = 0;
+        'restart: while let Some(path) = pending.pop() {
+            let components: Vec<_> = path.components().skip(1).collect();
+            let mut current = PathBuf::from("/");
+            for (idx, component) in components.iter().enumerate() {
+                current.push(component);
+                let is_last = idx == components.len() - 1;
+                if let Some(NodeKind::Symlink(target)) = self.nodes.get(&current).map(|n| &n.kind) {
+                    if is_last && !follow_last {
+                        break;
+                    }
+                    depth += 1;
+                    if depth > MAX_SYMLINK_DEPTH {
+                        return Err(io::Error::other(format!(
+                            "Too many levels of symbolic links: {:?}",
+                            path
+                        )));
+                    }
+                    let mut next = current.parent().map(Path::to_path_buf).unwrap_or_default();
+                    next.push(target);
+                    for rest in components.iter().skip(idx + 1) {
+                        next.push(rest);
+                    }
+                    pending.push(self.absolute(&next));
+                    continue 'restart;
+                }
+            }
+            return Ok(current);
+        }
+        Ok(PathBuf::from("/"))
+    }
+
+    fn children(&self, dir: &Path) -> Vec<PathBuf> {
+        self.nodes
+            .range(dir.to_path_buf()..)
+            .skip(1)
+            .take_while(|(path, _)| path.starts_with(dir))
+            .filter(|(path, _)| path.parent() == Some(dir))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    fn descendants(&self, dir: &Path) -> Vec<PathBuf> {
+        self.nodes
+            .range(dir.to_path_buf()..)
+            .take_while(|(path, _)| path.starts_with(dir))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    fn ensure_parent_dir(&self, path: &Path) -> io::Result<()> {
+        let parent = path.parent().ok_or_else(|| invalid_input(path))?;
+        match self.nodes.get(parent).map(|node| &node.kind) {
+            Some(NodeKind::Directory) => Ok(()),
+            Some(_) => Err(not_a_directory(parent)),
+            None => Err(not_found(parent)),
+        }
+    }
+
+    fn remove_tree(&mut self, path: &Path) {
+        for descendant in self.descendants(path) {
+            self.nodes.remove(&descendant);
+        }
+        self.touch_parent(path);
+    }
+}
+
+impl FileSystem for InMemoryFileSystem {
+    fn exist<TPath: AsRef<Path>>(&self, path: TPath) -> bool {
+        let tree = self.lock();
+        match tree.resolve(path.as_ref(), false) {
+            Ok(path) => tree.nodes.contains_key(&path),
+            Err(_) => false,
+        }
+    }
+
+    fn get_dir_info<TPath: AsRef<Path>>(&self, path: TPath) -> Option<DirInfo> {
+        let path = self.canonicalize(path).ok()?;
+        match self.metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => Some(DirInfo::new(path)),
+            _ => None,
+        }
+    }
+
+    fn list_dir<TPath: AsRef<Path>>(
+        &self,
+        path: TPath,
+        big_config: &Config,
+    ) -> Vec<FileSystemItem> {
+        list_file_system_items(self, path, big_config)
+    }
+
+    fn read_to_string<TPath: AsRef<Path>>(&self, path: TPath) -> Option<String> {
+        self.read(path)
+            .ok()
+            .and_then(|content| String::from_utf8(content).ok())
+    }
+
+    fn open_file<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(Cursor::new(self.read(path)?)))
+    }
+
+    fn delete_file<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        match tree.nodes.get(&path).map(|node| &node.kind) {
+            Some(NodeKind::Directory) => Err(is_a_directory(&path)),
+            Some(_) => {
+                tree.nodes.remove(&path);
+                tree.touch_parent(&path);
+                Ok(())
+            }
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn delete_dir<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        match tree.nodes.get(&path).map(|node| &node.kind) {
+            Some(NodeKind::Directory) if path == Path::new("/") => Err(invalid_input(&path)),
+            Some(NodeKind::Directory) => {
+                tree.remove_tree(&path);
+                Ok(())
+            }
+            Some(_) => Err(not_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn delete_empty_dir<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        match tree.nodes.get(&path).map(|node| &node.kind) {
+            Some(NodeKind::Directory) if !tree.children(&path).is_empty() => Err(io::Error::new(
+                io::ErrorKind::DirectoryNotEmpty,
+                format!("{:?}", path),
+            )),
+            Some(NodeKind::Directory) => {
+                tree.nodes.remove(&path);
+                tree.touch_parent(&path);
+                Ok(())
+            }
+            Some(_) => Err(not_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn rename_item<TPath: AsRef<Path>>(&mut self, source: TPath, target: TPath) -> io::Result<()> {
+        let mut tree = self.lock();
+        let source = tree.resolve(source.as_ref(), false)?;
+        let target = tree.resolve(target.as_ref(), false)?;
+        let source_is_dir = match tree.nodes.get(&source).map(|node| &node.kind) {
+            Some(kind) => matches!(kind, NodeKind::Directory),
+            None => return Err(not_found(&source)),
+        };
+        if source == target {
+            return Ok(());
+        }
+        if target.starts_with(&source) {
+            return Err(invalid_input(&target));
+        }
+        tree.ensure_parent_dir(&target)?;
+        if tree.device(&source) != tree.device(&target) {
+            return Err(io::Error::new(
+                io::ErrorKind::CrossesDevices,
+                format!("{:?}", target),
+            ));
+        }
+
+        match tree.nodes.get(&target).map(|node| &node.kind) {
+            Some(NodeKind::Directory) if !source_is_dir => return Err(is_a_directory(&target)),
+            Some(NodeKind::Directory) if !tree.children(&target).is_empty() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::DirectoryNotEmpty,
+                    format!("{:?}", target),
+                ))
+            }
+            Some(NodeKind::Directory) => {
+                tree.nodes.remove(&target);
+            }
+            Some(_) if source_is_dir => return Err(not_a_directory(&target)),
+            Some(_) => {
+                tree.nodes.remove(&target);
+            }
+            None => {}
+        }
+
+        for old_path in tree.descendants(&source) {
+            if let Some(node) = tree.nodes.remove(&old_path) {
+                let relative = old_path.strip_prefix(&source).unwrap();
+                let new_path = if relative.as_os_str().is_empty() {
+                    target.clone()
+                } else {
+                    target.join(relative)
+                };
+                tree.nodes.insert(new_path, node);
+            }
+        }
+        tree.touch_parent(&source);
+        tree.touch_parent(&target);
+        Ok(())
+    }
+
+    fn create_symlink<TPath: AsRef<Path>>(
+        &mut self,
+        source: TPath,
+        target: TPath,
+    ) -> io::Result<()> {
+        let mut tree = self.lock();
+        let link_path = tree.resolve(target.as_ref(), false)?;
+        tree.ensure_parent_dir(&link_path)?;
+        if tree.nodes.contains_key(&link_path) {
+            return Err(already_exists(&link_path));
+        }
+        let node = tree.new_node(NodeKind::Symlink(source.as_ref().to_path_buf()), 0o120777);
+        tree.insert(link_path, node);
+        Ok(())
+    }
+
+    fn create_file<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), true)?;
+        tree.ensure_parent_dir(&path)?;
+        match tree.nodes.get(&path).map(|node| &node.kind) {
+            Some(NodeKind::Directory) => Err(is_a_directory(&path)),
+            Some(_) => {
+                let now = tree.tick();
+                let node = tree.nodes.get_mut(&path).unwrap();
+                node.kind = NodeKind::File(Vec::new());
+                node.modified = now;
+                Ok(())
+            }
+            None => {
+                let node = tree.new_node(NodeKind::File(Vec::new()), 0o100644);
+                tree.insert(path, node);
+                Ok(())
+            }
+        }
+    }
+
+    fn create_dir<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        tree.ensure_parent_dir(&path)?;
+        if tree.nodes.contains_key(&path) {
+            return Err(already_exists(&path));
+        }
+        let node = tree.new_node(NodeKind::Directory, 0o40755);
+        tree.insert(path, node);
+        Ok(())
+    }
+
+    fn copy_file<TPath: AsRef<Path>>(&mut self, source: TPath, target: TPath) -> io::Result<u64> {
+        let content = self.read(source.as_ref())?;
+        let mut tree = self.lock();
+        let source = tree.resolve(source.as_ref(), true)?;
+        let target = tree.resolve(target.as_ref(), true)?;
+        tree.ensure_parent_dir(&target)?;
+        if let Some(NodeKind::Directory) = tree.nodes.get(&target).map(|node| &node.kind) {
+            return Err(is_a_directory(&target));
+        }
+        let size = content.len() as u64;
+        let mode = tree.nodes[&source].mode;
+        let node = tree.new_node(NodeKind::File(content), mode);
+        tree.insert(target, node);
+        Ok(size)
+    }
+
+    fn copy_dir<TPath: AsRef<Path>>(&mut self, source: TPath, target: TPath) -> io::Result<u64> {
+        copy_recursive(
+            self,
+            source.as_ref(),
+            target.as_ref(),
+            &CopyOptions::default(),
+            &mut CopyEverything,
+        )
+    }
+
+    fn metadata<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<ItemMetadata> {
+        let tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        let node = tree.nodes.get(&path).ok_or_else(|| not_found(&path))?;
+        let (item_type, size, nlink, rdev) = match &node.kind {
+            NodeKind::File(content) => (ItemType::File, content.len() as u64, 1, 0),
+            NodeKind::Directory => {
+                let subdirs = tree
+                    .children(&path)
+                    .iter()
+                    .filter(|child| matches!(tree.nodes[*child].kind, NodeKind::Directory))
+                    .count() as u64;
+                (ItemType::Directory, BLOCK_SIZE, 2 + subdirs, 0)
+            }
+            NodeKind::Symlink(target) => (ItemType::Symlink, target.as_os_str().len() as u64, 1, 0),
+            NodeKind::Special { item_type, rdev } => (*item_type, 0, 1, *rdev),
+        };
+        Ok(ItemMetadata {
+            item_type,
+            size,
+            mode: node.mode,
+            dev: tree.device(&path),
+            rdev,
+            inode: node.inode,
+            nlink,
+            uid: node.uid,
+            gid: node.gid,
+            username: node.username.clone(),
+            groupname: node.groupname.clone(),
+            blocksize: BLOCK_SIZE,
+            blocks: size.div_ceil(512),
+            created: node.created,
+            modified: node.modified,
+            accessed: node.accessed,
+        })
+    }
+
+    fn read_dir<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<Vec<PathBuf>> {
+        let tree = self.lock();
+        let path = tree.resolve(path.as_ref(), true)?;
+        match tree.nodes.get(&path).map(|node| &node.kind) {
+            Some(NodeKind::Directory) => Ok(tree.children(&path)),
+            Some(_) => Err(not_a_directory(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn read_link<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<PathBuf> {
+        let tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        match tree.nodes.get(&path).map(|node| &node.kind) {
+            Some(NodeKind::Symlink(target)) => Ok(target.clone()),
+            Some(_) => Err(invalid_input(&path)),
+            None => Err(not_found(&path)),
+        }
+    }
+
+    fn canonicalize<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<PathBuf> {
+        let tree = self.lock();
+        let path = tree.resolve(path.as_ref(), true)?;
+        if tree.nodes.contains_key(&path) {
+            Ok(path)
+        } else {
+            Err(not_found(&path))
+        }
+    }
+
+    fn set_permissions<TPath: AsRef<Path>>(&mut self, path: TPath, mode: u32) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), true)?;
+        let node = tree.nodes.get_mut(&path).ok_or_else(|| not_found(&path))?;
+        node.mode = (node.mode & !0o7777) | (mode & 0o7777);
+        Ok(())
+    }
+
+    fn set_times<TPath: AsRef<Path>>(
+        &mut self,
+        path: TPath,
+        accessed: SystemTime,
+        modified: SystemTime,
+    ) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        let node = tree.nodes.get_mut(&path).ok_or_else(|| not_found(&path))?;
+        node.accessed = accessed;
+        node.modified = modified;
+        Ok(())
+    }
+
+    /// Follows the rules of `chown` for an unprivileged user: only root can
+    /// change the owner and others can only pick their own group.
+    fn set_owner<TPath: AsRef<Path>>(&mut self, path: TPath, uid: u32, gid: u32) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        let (current_uid, current_gid) = (tree.uid, tree.gid);
+        let username = tree.username.clone();
+        let groupname = tree.groupname.clone();
+        let node = tree.nodes.get_mut(&path).ok_or_else(|| not_found(&path))?;
+        let allowed = current_uid == 0
+            || (node.uid == current_uid
+                && uid == current_uid
+                && (gid == node.gid || gid == current_gid));
+        if !allowed {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{:?}", path),
+            ));
+        }
+        if node.uid != uid {
+            node.username = if uid == current_uid {
+                username
+            } else {
+                uid.to_string()
+            };
+        }
+        if node.gid != gid {
+            node.groupname = if gid == current_gid {
+                groupname
+            } else {
+                gid.to_string()
+            };
+        }
+        node.uid = uid;
+        node.gid = gid;
+        Ok(())
+    }
+
+    /// Device nodes need root, the same as `mknod`.
+    fn create_special_file<TPath: AsRef<Path>>(
+        &mut self,
+        path: TPath,
+        metadata: &ItemMetadata,
+    ) -> io::Result<()> {
+        let file_type = match metadata.item_type {
+            ItemType::Fifo => 0o010000,
+            ItemType::CharDevice => 0o020000,
+            ItemType::BlockDevice => 0o060000,
+            _ => return Err(invalid_input(path.as_ref())),
+        };
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        tree.ensure_parent_dir(&path)?;
+        if tree.nodes.contains_key(&path) {
+            return Err(already_exists(&path));
+        }
+        if metadata.item_type != ItemType::Fifo && tree.uid != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{:?}", path),
+            ));
+        }
+        let node = tree.new_node(
+            NodeKind::Special {
+                item_type: metadata.item_type,
+                rdev: metadata.rdev,
+            },
+            file_type | (metadata.mode & 0o7777),
+        );
+        tree.insert(path, node);
+        Ok(())
+    }
+
+    fn write_new_file<TPath: AsRef<Path>>(
+        &mut self,
+        path: TPath,
+        content: &[u8],
+    ) -> io::Result<()> {
+        let mut tree = self.lock();
+        let path = tree.resolve(path.as_ref(), false)?;
+        tree.ensure_parent_dir(&path)?;
+        if tree.nodes.contains_key(&path) {
+            return Err(already_exists(&path));
+        }
+        let node = tree.new_node(NodeKind::File(content.to_vec()), 0o100644);
+        tree.insert(path, node);
+        Ok(())
+    }
+
+    fn data_home(&self) -> Option<PathBuf> {
+        let tree = self.lock();
+        Some(
+            PathBuf::from("/home")
+                .join(&tree.username)
+                .join(".local/share"),
+        )
+    }
+
+    /// Like on a real system, `/tmp` is only there once it has been created.
+    fn temp_dir(&self) -> PathBuf {
+        PathBuf::from("/tmp")
+    }
+
+    fn current_uid(&self) -> u32 {
+        self.lock().uid
+    }
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{:?}", path))
+}
+
+fn already_exists(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::AlreadyExists, format!("{:?}", path))
+}
+
+fn not_a_directory(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotADirectory, format!("{:?}", path))
+}
+
+fn is_a_directory(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::IsADirectory, format!("{:?}", path))
+}
+
+fn invalid_input(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("{:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        io::{self, ErrorKind},
+        path::{Path, PathBuf},
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use crate::app::{
+        actions::{
+            AppAction, DirectoryAction, FileAction, FileManagerActions, JobAction, JournalAction,
+            PanelInfo, PanelSide, TabAction, TrashAction,
+        },
+        config::{
+            tab_config::{SortEnum, SortField, SortKey},
+            Config,
+        },
+        file_system::{
+            copy::{CopyOptions, SpecialFiles},
+            FileSystem, PhysicalFileSystem,
+        },
+        jobs::{ConflictResolution, Job, JobKind, JobOutcome},
+        middlewares::{
+            compare_middleware, dir_middleware, dir_size_middleware, find_middleware,
+            grep_middleware, job_middleware, listing_middleware, symlink_middleware,
+            sync_middleware,
+        },
+        reducers::root_reducer,
+        state::{AppError, AppState, FileOperation, ModalType, TabState},
+    };
+    use crate::core::store::Store;
+
+    use super::InMemoryFileSystem;
+
+    fn panel(path: &str, side: PanelSide) -> PanelInfo {
+        PanelInfo {
+            path: PathBuf::from(path),
+            tab: 0,
+            side,
+        }
+    }
+
+    fn tab_names(state: &AppState<InMemoryFileSystem>, side: PanelSide) -> Vec<String> {
+        let panel = match side {
+            PanelSide::Left => &state.left_panel,
+            PanelSide::Right => &state.right_panel,
+        };
+        let mut names: Vec<String> = panel.tabs[0]
+            .items
+            .iter()
+            .map(|item| item.get_name())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// A store set up with the middlewares of the application.
+    fn app_store(
+        state: AppState<InMemoryFileSystem>,
+    ) -> Store<AppState<InMemoryFileSystem>, FileManagerActions> {
+        let mut store = Store::with_state(root_reducer, state);
+        store.register_middleware(symlink_middleware);
+        store.register_middleware(dir_middleware);
+        store.register_middleware(job_middleware);
+        store.register_middleware(dir_size_middleware);
+        store.register_middleware(compare_middleware);
+        store.register_middleware(sync_middleware);
+        store.register_middleware(find_middleware);
+        store.register_middleware(grep_middleware);
+        store.register_middleware(listing_middleware);
+        store
+    }
+
+    /// Dispatches `action` and waits until the jobs it started are finished.
+    fn dispatch(
+        store: &mut Store<AppState<InMemoryFileSystem>, FileManagerActions>,
+        action: FileManagerActions,
+    ) {
+        store.dispatch(action);
+        let started = Instant::now();
+        loop {
+            store.dispatch_queued();
+            if store.get_state().jobs.is_empty() {
+                return;
+            }
+            assert!(started.elapsed() < Duration::from_secs(5), "jobs hang");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn store_copies_moves_renames_and_deletes() {
+        let file_system = InMemoryFileSystem::default();
+        file_system.add_file("/left/a.txt", b"hello").unwrap();
+        file_system.add_file("/left/docs/readme", b"read").unwrap();
+        file_system.add_dir("/right").unwrap();
+        let config = Config::default();
+        let mut state = AppState::new(config.clone(), file_system.clone());
+        state.left_panel.tabs = vec![TabState::with_dir(
+            Path::new("/left"),
+            &file_system,
+            &config,
+        )];
+        state.right_panel.tabs = vec![TabState::with_dir(
+            Path::new("/right"),
+            &file_system,
+            &config,
+        )];
+        let mut store = app_store(state);
+
+        dispatch(
+            &mut store,
+            FileManagerActions::File(FileAction::Copy {
+                from: panel("/left/a.txt", PanelSide::Left),
+                to: panel("/right/a.txt", PanelSide::Right),
+            }),
+        );
+        assert_eq!(file_system.read("/right/a.txt").unwrap(), b"hello");
+        assert_eq!(
+            tab_names(&store.get_state(), PanelSide::Right),
+            vec!["a.txt"]
+        );
+
+        // Copying again asks what to do with the existing file.
+        dispatch(
+            &mut store,
+            FileManagerActions::File(FileAction::Copy {
+                from: panel("/left/a.txt", PanelSide::Left),
+                to: panel("/right/a.txt", PanelSide::Right),
+            }),
+        );
+        assert!(matches!(
+            store.get_state().modal,
+            Some(ModalType::ConflictModal)
+        ));
+        dispatch(
+            &mut store,
+            FileManagerActions::Job(JobAction::ResolveConflict {
+                resolution: ConflictResolution::Rename,
+                apply_to_all: false,
+            }),
+        );
+        assert_eq!(
+            tab_names(&store.get_state(), PanelSide::Right),
+            vec!["a (1).txt", "a.txt"]
+        );
+
+        dispatch(
+            &mut store,
+            FileManagerActions::File(FileAction::Move {
+                from: panel("/left/a.txt", PanelSide::Left),
+                to: panel("/right/b.txt", PanelSide::Right),
+            }),
+        );
+        assert!(!file_system.exist("/left/a.txt"));
+        assert_eq!(file_system.read("/right/b.txt").unwrap(), b"hello");
+
+        dispatch(
+            &mut store,
+            FileManagerActions::Directory(DirectoryAction::Copy {
+                from: panel("/left/docs", PanelSide::Left),
+                to: panel("/right/docs", PanelSide::Right),
+            }),
+        );
+        assert_eq!(file_system.read("/right/docs/readme").unwrap(), b"read");
+
+        dispatch(
+            &mut store,
+            FileManagerActions::Directory(DirectoryAction::Move {
+                from: panel("/right/docs", PanelSide::Right),
+                to: panel("/left/copy", PanelSide::Left),
+            }),
+        );
+        assert_eq!(file_system.read("/left/copy/readme").unwrap(), b"read");
+        assert!(!file_system.exist("/right/docs"));
+
+        dispatch(
+            &mut store,
+            FileManagerActions::Directory(DirectoryAction::Rename {
+                from: panel("/left/docs", PanelSide::Left),
+                to: panel("/left/notes", PanelSide::Left),
+            }),
+        );
+        assert_eq!(
+            tab_names(&store.get_state(), PanelSide::Left),
+            vec!["copy", "notes"]
+        );
+
+        dispatch(
+            &mut store,
+            FileManagerActions::File(FileAction::DeletePermanently {
+                panel: panel("/right/a.txt", PanelSide::Right),
+            }),
+        );
+        assert!(!file_system.exist("/right/a.txt"));
+
+        dispatch(
+            &mut store,
+            FileManagerActions::File(FileAction::Delete {
+                panel: panel("/right/b.txt", PanelSide::Right),
+            }),
+        );
+        assert!(!file_system.exist("/right/b.txt"));
+        assert_eq!(
+            tab_names(&store.get_state(), PanelSide::Right),
+            vec!["a (1).txt"]
+        );
+
+        // Moving to the trash is journaled, undo brings the file back.
+        dispatch(&mut store, FileManagerActions::Journal(JournalAction::Undo));
+        assert_eq!(file_system.read("/right/b.txt").unwrap(), b"hello");
+        let state = store.get_state();
+        assert!(state.errors.is_empty(), "{:?}", state.errors);
+        assert!(state.modal.is_none(), "{:?}", state.modal);
+    }
+
+    #[test]
+    fn creating_items_and_reopening_the_trash_keep_the_tab_view() {
+        let file_system = InMemoryFileSystem::default();
+        file_system.add_dir("/left").unwrap();
+        let config = Config::default();
+        let mut state = AppState::new(config.clone(), file_system.clone());
+        state.left_panel.tabs = vec![TabState::with_dir(
+            Path::new("/left"),
+            &file_system,
+            &config,
+        )];
+        let set_view = |state| {
+            let state = root_reducer(
+                state,
+                FileManagerActions::Tab(TabAction::SetSort {
+                    panel_side: PanelSide::Left,
+                    key: SortKey::Size,
+                    order: SortEnum::DESC,
+                }),
+            );
+            root_reducer(
+                state,
+                FileManagerActions::Tab(TabAction::ToggleDetailView {
+                    panel_side: PanelSide::Left,
+                }),
+            )
+        };
+
+        let state = set_view(state);
+        let sorted = state.left_panel.tabs[0].sort.fields.clone();
+        assert_eq!(sorted[0], SortField::new(SortKey::Size, SortEnum::DESC));
+        let state = root_reducer(
+            state,
+            FileManagerActions::File(FileAction::Create {
+                file_name: String::from("a.txt"),
+                panel: panel("/left", PanelSide::Left),
+            }),
+        );
+        let state = root_reducer(
+            state,
+            FileManagerActions::Directory(DirectoryAction::Create {
+                dir_name: String::from("docs"),
+                panel: panel("/left", PanelSide::Left),
+            }),
+        );
+        let tab = &state.left_panel.tabs[0];
+        assert_eq!(tab_names(&state, PanelSide::Left), vec!["a.txt", "docs"]);
+        assert_eq!(tab.sort.fields, sorted);
+        assert_ne!(tab.detail_view, config.core_cfg.detail_view);
+
+        let state = root_reducer(
+            state,
+            FileManagerActions::Trash(TrashAction::Open {
+                panel_side: PanelSide::Left,
+            }),
+        );
+        let state = set_view(state);
+        let state = root_reducer(
+            state,
+            FileManagerActions::Trash(TrashAction::Open {
+                panel_side: PanelSide::Left,
+            }),
+        );
+        let trash = &state.left_panel.tabs[state.left_panel.current_tab];
+        assert!(trash.is_trash());
+        assert_eq!(trash.sort.fields, sorted);
+        assert_ne!(trash.detail_view, config.core_cfg.detail_view);
+        assert!(state.errors.is_empty(), "{:?}", state.errors);
+    }
+
+    #[test]
+    fn bulk_rename_is_applied_only_when_the_editor_succeeds() {
+        let file_system = InMemoryFileSystem::default();
+        file_system.add_file("/dir/a.txt", b"a").unwrap();
+        file_system.add_dir("/tmp").unwrap();
+        let config = Config::default();
+        let mut state = AppState::new(config.clone(), file_system.clone());
+        state.left_panel.tabs = vec![TabState::with_dir(Path::new("/dir"), &file_system, &config)];
+        let bulk_rename = FileManagerActions::File(FileAction::BulkRename {
+            panel: panel("/dir", PanelSide::Left),
+            items: vec![PathBuf::from("/dir/a.txt")],
+        });
+
+        let state = root_reducer(state, bulk_rename.clone());
+        let list_path = state.bulk_rename.as_ref().unwrap().list_path.clone();
+        file_system.add_file(&list_path, b"b.txt\n").unwrap();
+        let error = AppError::new(
+            FileOperation::RunProgram,
+            vec![PathBuf::from("editor")],
+            &io::Error::other("it ended with exit status: 1"),
+        );
+        let state = root_reducer(
+            state,
+            FileManagerActions::App(AppAction::ChildProgramClosed { error: Some(error) }),
+        );
+        assert!(file_system.exist("/dir/a.txt"));
+        assert!(!file_system.exist("/dir/b.txt"));
+        assert!(!file_system.exist(&list_path));
+        assert!(state.bulk_rename.is_none());
+        assert!(state.child_program.is_none());
+        assert!(matches!(state.modal, Some(ModalType::MessageboxModal(_))));
+
+        let state = root_reducer(
+            AppState {
+                modal: None,
+                ..state
+            },
+            bulk_rename,
+        );
+        let list_path = state.bulk_rename.as_ref().unwrap().list_path.clone();
+        file_system.add_file(&list_path, b"b.txt\n").unwrap();
+        let state = root_reducer(
+            state,
+            FileManagerActions::App(AppAction::ChildProgramClosed { error: None }),
+        );
+        assert!(!file_system.exist("/dir/a.txt"));
+        assert!(file_system.exist("/dir/b.txt"));
+        assert!(state.errors.is_empty(), "{:?}", state.errors);
+        assert!(state.modal.is_none(), "{:?}", state.modal);
+    }
+
+    #[test]
+    fn failed_undo_and_redo_keep_the_operation() {
+        let file_system = InMemoryFileSystem::default();
+        file_system.add_file("/dir/a.txt", b"a").unwrap();
+        let config = Config::default();
+        let mut state = AppState::new(config.clone(), file_system.clone());
+        state.left_panel.tabs = vec![TabState::with_dir(Path::new("/dir"), &file_system, &config)];
+        let state = root_reducer(
+            state,
+            FileManagerActions::File(FileAction::Rename {
+                from: panel("/dir/a.txt", PanelSide::Left),
+                to: panel("/dir/b.txt", PanelSide::Left),
+            }),
+        );
+
+        // The old name is taken, so the rename can't be undone yet.
+        file_system.add_file("/dir/a.txt", b"new").unwrap();
+        let state = root_reducer(state, FileManagerActions::Journal(JournalAction::Undo));
+        assert!(matches!(state.modal, Some(ModalType::MessageboxModal(_))));
+        assert!(file_system.exist("/dir/b.txt"));
+
+        let mut fs = file_system.clone();
+        fs.delete_file("/dir/a.txt").unwrap();
+        let state = root_reducer(
+            AppState {
+                modal: None,
+                ..state
+            },
+            FileManagerActions::Journal(JournalAction::Undo),
+        );
+        assert!(state.errors.is_empty(), "{:?}", state.errors);
+        assert_eq!(file_system.read("/dir/a.txt").unwrap(), b"a");
+
+        // Same for redo with the new name taken.
+        file_system.add_file("/dir/b.txt", b"new").unwrap();
+        let state = root_reducer(state, FileManagerActions::Journal(JournalAction::Redo));
+        assert!(matches!(state.modal, Some(ModalType::MessageboxModal(_))));
+        assert!(file_system.exist("/dir/a.txt"));
+
+        fs.delete_file("/dir/b.txt").unwrap();
+        let state = root_reducer(
+            AppState {
+                modal: None,
+                ..state
+            },
+            FileManagerActions::Journal(JournalAction::Redo),
+        );
+        assert!(state.errors.is_empty(), "{:?}", state.errors);
+        assert_eq!(file_system.read("/dir/b.txt").unwrap(), b"a");
+        assert!(state.modal.is_none(), "{:?}", state.modal);
+    }
+
+    #[test]
+    fn jobs_are_journaled_unless_they_replaced_an_item() {
+        let file_system = InMemoryFileSystem::default();
+        file_system.add_file("/left/a.txt", b"a").unwrap();
+        file_system.add_file("/right/a.txt", b"a").unwrap();
+        file_system.add_file("/right/a (1).txt", b"a").unwrap();
+        file_system.add_file("/right/b.txt", b"a").unwrap();
+        let mut state = AppState::new(Config::default(), file_system.clone());
+        let options = CopyOptions {
+            dereference_symlinks: false,
+            special_files: SpecialFiles::Skip,
+        };
+        let finish = |state, job: Job, replaced_existing| {
+            let id = job.id;
+            let state = root_reducer(state, FileManagerActions::Job(JobAction::Start { job }));
+            root_reducer(
+                state,
+                FileManagerActions::Job(JobAction::Finished {
+                    id,
+                    outcome: JobOutcome::Completed {
+                        replaced_existing,
+                        moved_across_devices: false,
+                    },
+                }),
+            )
+        };
+
+        let mut overwrite = Job::new(
+            JobKind::Copy,
+            PathBuf::from("/left/a.txt"),
+            PathBuf::from("/right/a.txt"),
+            false,
+            options,
+        );
+        overwrite.on_conflict = Some(ConflictResolution::Overwrite);
+        state = finish(state, overwrite, true);
+        assert!(state.journal.pop_undo().is_none());
+
+        // A job renamed by the conflict dialog creates its target.
+        let renamed = Job::new(
+            JobKind::Copy,
+            PathBuf::from("/left/a.txt"),
+            PathBuf::from("/right/a (1).txt"),
+            false,
+            options,
+        );
+        state = finish(state, renamed, false);
+        // Sync overwrites whatever it finds, here nothing was there.
+        let mut synced = Job::new(
+            JobKind::Copy,
+            PathBuf::from("/left/a.txt"),
+            PathBuf::from("/right/b.txt"),
+            false,
+            options,
+        );
+        synced.on_conflict = Some(ConflictResolution::Overwrite);
+        state = finish(state, synced, false);
+
+        let state = root_reducer(state, FileManagerActions::Journal(JournalAction::Undo));
+        assert!(!file_system.exist("/right/b.txt"));
+        let state = root_reducer(state, FileManagerActions::Journal(JournalAction::Undo));
+        assert!(!file_system.exist("/right/a (1).txt"));
+        assert!(file_system.exist("/right/a.txt"));
+        assert!(state.errors.is_empty(), "{:?}", state.errors);
+    }
+
+    /// Runs `job` on its worker thread and hands its outcome to the reducer,
+    /// the way the store does.
+    fn run_job(
+        state: AppState<InMemoryFileSystem>,
+        job: Job,
+        file_system: &InMemoryFileSystem,
+    ) -> AppState<InMemoryFileSystem> {
+        let (sender, receiver) = mpsc::channel();
+        job.start(file_system.clone(), sender);
+        let mut state = root_reducer(state, FileManagerActions::Job(JobAction::Start { job }));
+        loop {
+            let action = receiver.recv().unwrap();
+            let finished = matches!(action, FileManagerActions::Job(JobAction::Finished { .. }));
+            state = root_reducer(state, action);
+            if finished {
+                return state;
+            }
+        }
+    }
+
+    #[test]
+    fn moves_across_mount_points_are_undone_and_redone() {
+        let file_system = InMemoryFileSystem::default();
+        file_system.add_file("/home/docs/a.txt", b"a").unwrap();
+        file_system.add_mount_point("/mnt/usb").unwrap();
+        let state = AppState::new(Config::default(), file_system.clone());
+        let job = Job::new(
+            JobKind::Move,
+            PathBuf::from("/home/docs"),
+            PathBuf::from("/mnt/usb/docs"),
+            true,
+            CopyOptions::default(),
+        );
+
+        let state = run_job(state, job, &file_system);
+        assert!(!file_system.exist("/home/docs"));
+        assert_eq!(file_system.read("/mnt/usb/docs/a.txt").unwrap(), b"a");
+
+        let state = root_reducer(state, FileManagerActions::Journal(JournalAction::Undo));
+        assert!(state.errors.is_empty(), "{:?}", state.errors);
+        assert!(state.modal.is_none(), "{:?}", state.modal);
+        assert!(!file_system.exist("/mnt/usb/docs"));
+        assert_eq!(file_system.read("/home/docs/a.txt").unwrap(), b"a");
+
+        let state = root_reducer(state, FileManagerActions::Journal(JournalAction::Redo));
+        assert!(state.errors.is_empty(), "{:?}", state.errors);
+        assert!(state.modal.is_none(), "{:?}", state.modal);
+        assert!(!file_system.exist("/home/docs"));
+        assert_eq!(file_system.read("/mnt/usb/docs/a.txt").unwrap(), b"a");
+    }
+
+    #[test]
+    fn renames_across_mount_points_fail() {
+        let mut file_system = InMemoryFileSystem::default();
+        file_system.add_file("/home/a.txt", b"a").unwrap();
+        file_system.add_mount_point("/mnt/usb").unwrap();
+        let err = file_system
+            .rename_item(Path::new("/home/a.txt"), Path::new("/mnt/usb/a.txt"))
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::CrossesDevices);
+        assert!(file_system.exist("/home/a.txt"));
+    }
+
+    #[test]
+    fn mutations_advance_the_clock() {
+        let mut file_system = InMemoryFileSystem::default();
+        file_system.add_dir("/dir").unwrap();
+        let before = file_system.metadata("/dir").unwrap().modified;
+        file_system.create_file("/dir/new.txt").unwrap();
+        let after = file_system.metadata("/dir").unwrap().modified;
+        assert!(after > before);
+        let created = file_system.metadata("/dir/new.txt").unwrap().created;
+        assert!(created > before);
+    }
+
+    /// Runs the same operations on a file system and describes how each one
+    /// went, for comparing the in-memory one against the disk.
+    fn run_operations<TFileSystem: FileSystem>(
+        file_system: &mut TFileSystem,
+        root: &Path,
+    ) -> Vec<String> {
+        let dir = root.join("dir");
+        let file = dir.join("file");
+        let other = root.join("other");
+        let link = root.join("link");
+        let outcome = |result: std::io::Result<()>| format!("{:?}", result.map_err(|e| e.kind()));
+        let mut outcomes = vec![
+            outcome(file_system.create_dir(&dir)),
+            outcome(file_system.create_dir(&dir)),
+            outcome(file_system.write_new_file(&file, b"content")),
+            outcome(file_system.write_new_file(&file, b"content")),
+            outcome(file_system.delete_empty_dir(&dir)),
+            outcome(file_system.rename_item(&dir, &dir.join("sub"))),
+            outcome(file_system.create_dir(&other)),
+            outcome(file_system.rename_item(&file, &other)),
+            outcome(file_system.rename_item(&other, &file)),
+            outcome(file_system.delete_file(&other)),
+            outcome(file_system.metadata(root.join("missing")).map(|_| ())),
+            outcome(file_system.create_symlink(&file, &link)),
+            outcome(file_system.copy_file(&file, &other).map(|_| ())),
+            outcome(file_system.copy_file(&file, &root.join("copy")).map(|_| ())),
+        ];
+
+        let metadata = file_system.metadata(&file).unwrap();
+        outcomes.push(format!("{:?} {}", metadata.item_type, metadata.size));
+        outcomes.push(format!(
+            "{:?}",
+            file_system
+                .metadata(&link)
+                .map(|metadata| metadata.item_type)
+        ));
+        outcomes.push(format!("{:?}", file_system.read_link(&link)));
+        let mut names: Vec<_> = file_system
+            .read_dir(root)
+            .unwrap()
+            .iter()
+            .map(|path| path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        names.sort();
+        outcomes.push(format!("{:?}", names));
+
+        // Only root can give a file away, both follow the same rule.
+        let current_uid = file_system.current_uid();
+        outcomes.push(outcome(file_system.set_owner(
+            &file,
+            current_uid + 1,
+            metadata.gid,
+        )));
+        outcomes.push(outcome(file_system.delete_dir(&file)));
+        outcomes.push(outcome(file_system.delete_dir(&dir)));
+        outcomes.push(format!("{}", file_system.exist(&dir)));
+        outcomes
+    }
+
+    #[test]
+    fn behaves_like_the_physical_file_system() {
+        let root = std::env::temp_dir().join(format!("rufus-in-memory-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir(&root).unwrap();
+        let mut physical = PhysicalFileSystem;
+        let gid = physical.metadata(&root).unwrap().gid;
+        let physical_outcomes = run_operations(&mut physical, &root);
+        fs::remove_dir_all(&root).unwrap();
+
+        let mut in_memory =
+            InMemoryFileSystem::with_owner(physical.current_uid(), gid, "user", "user");
+        in_memory.add_dir(&root).unwrap();
+        let in_memory_outcomes = run_operations(&mut in_memory, &root);
+
+        assert_eq!(in_memory_outcomes, physical_outcomes);
+    }
+}
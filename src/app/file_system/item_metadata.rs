@@ -0,0 +1,57 @@
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemType {
+    File,
+    Directory,
+    Symlink,
+    Fifo,
+    Socket,
+    BlockDevice,
+    CharDevice,
+    Unknown,
+}
+
+/// Metadata of a single item, read without following a trailing symlink
+/// (the same semantics as `lstat`).
+#[derive(Clone, Debug)]
+pub struct ItemMetadata {
+    pub item_type: ItemType,
+    pub size: u64,
+    pub mode: u32,
+    pub dev: u64,
+    pub rdev: u64,
+    pub inode: u64,
+    pub nlink: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub username: String,
+    pub groupname: String,
+    pub blocksize: u64,
+    pub blocks: u64,
+    pub created: SystemTime,
+    pub modified: SystemTime,
+    pub accessed: SystemTime,
+}
+
+impl ItemMetadata {
+    pub fn is_file(&self) -> bool {
+        self.item_type == ItemType::File
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.item_type == ItemType::Directory
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.item_type == ItemType::Symlink
+    }
+
+    /// FIFOs and device nodes, which have no content of their own.
+    pub fn is_special(&self) -> bool {
+        matches!(
+            self.item_type,
+            ItemType::Fifo | ItemType::BlockDevice | ItemType::CharDevice
+        )
+    }
+}
@@ -1,24 +1,32 @@
 use std::{
-    cmp,
-    cmp::Reverse,
-    fs::{self, File},
+    ffi::CString,
+    fs::{self, File, OpenOptions, Permissions},
+    io::{Read, Write},
+    os::unix::{ffi::OsStrExt, fs::PermissionsExt},
     path::Path,
+    time::{SystemTime, UNIX_EPOCH},
 };
 use std::{io, path::PathBuf};
 
 use self::{
+    copy::{copy_recursive, CopyEverything, CopyOptions},
     file_system_item::FileSystemItem,
-    functions::{create_link, map_dir_entry_to_file_system_item},
+    functions::{create_link, list_file_system_items, map_metadata_to_item_metadata},
+    item_metadata::{ItemMetadata, ItemType},
 };
 
-use super::config::tab_config::SortEnum;
 use super::config::Config;
 
+pub mod copy;
+pub mod copy_engine;
 pub mod dir_item;
 pub mod file_item;
 pub mod file_system_item;
 pub mod functions;
+pub mod in_memory;
+pub mod item_metadata;
 pub mod symlink_item;
+pub mod trash;
 
 pub trait FileSystem {
     fn exist<TPath: AsRef<Path>>(&self, path: TPath) -> bool;
@@ -26,6 +34,8 @@ pub trait FileSystem {
     fn list_dir<TPath: AsRef<Path>>(&self, path: TPath, big_config: &Config)
         -> Vec<FileSystemItem>;
     fn read_to_string<TPath: AsRef<Path>>(&self, path: TPath) -> Option<String>;
+    /// Opens a file, following symlinks, to read its content in chunks.
+    fn open_file<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<Box<dyn Read + Send>>;
     fn delete_file<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()>;
     fn delete_dir<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()>;
     fn delete_empty_dir<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()>;
@@ -35,10 +45,38 @@ pub trait FileSystem {
         source: TPath,
         target: TPath,
     ) -> io::Result<()>;
-    fn create_file<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<File>;
+    fn create_file<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()>;
     fn create_dir<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()>;
     fn copy_file<TPath: AsRef<Path>>(&mut self, source: TPath, target: TPath) -> io::Result<u64>;
+    /// Copies a directory tree with the default `CopyOptions` and returns the
+    /// number of bytes written.
     fn copy_dir<TPath: AsRef<Path>>(&mut self, source: TPath, target: TPath) -> io::Result<u64>;
+    fn metadata<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<ItemMetadata>;
+    fn read_dir<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<Vec<PathBuf>>;
+    fn read_link<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<PathBuf>;
+    fn canonicalize<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<PathBuf>;
+    fn set_permissions<TPath: AsRef<Path>>(&mut self, path: TPath, mode: u32) -> io::Result<()>;
+    /// Sets the times of the item itself, not of what a symlink points to.
+    fn set_times<TPath: AsRef<Path>>(
+        &mut self,
+        path: TPath,
+        accessed: SystemTime,
+        modified: SystemTime,
+    ) -> io::Result<()>;
+    /// Changes the owner of the item itself, not of what a symlink points to.
+    fn set_owner<TPath: AsRef<Path>>(&mut self, path: TPath, uid: u32, gid: u32) -> io::Result<()>;
+    /// Creates a FIFO or a device node with the type, mode and device
+    /// number taken from `metadata`.
+    fn create_special_file<TPath: AsRef<Path>>(
+        &mut self,
+        path: TPath,
+        metadata: &ItemMetadata,
+    ) -> io::Result<()>;
+    fn write_new_file<TPath: AsRef<Path>>(&mut self, path: TPath, content: &[u8])
+        -> io::Result<()>;
+    fn data_home(&self) -> Option<PathBuf>;
+    fn temp_dir(&self) -> PathBuf;
+    fn current_uid(&self) -> u32;
 }
 
 #[derive(Clone, Debug, Default)]
@@ -46,7 +84,7 @@ pub struct PhysicalFileSystem;
 
 impl FileSystem for PhysicalFileSystem {
     fn get_dir_info<TPath: AsRef<Path>>(&self, path: TPath) -> Option<DirInfo> {
-        DirInfo::new(&path)
+        self.canonicalize(path).ok().map(DirInfo::new)
     }
 
     fn list_dir<TPath: AsRef<Path>>(
@@ -54,93 +92,7 @@ impl FileSystem for PhysicalFileSystem {
         path: TPath,
         big_config: &Config,
     ) -> Vec<FileSystemItem> {
-        match fs::read_dir(path) {
-            Ok(mut iter) => {
-                let mut result = Vec::new();
-                while let Some(load_result) = iter.next() {
-                    if let Ok(dir_entry) = load_result {
-                        result.push(map_dir_entry_to_file_system_item(
-                            dir_entry,
-                            &big_config.icons,
-                        ));
-                    }
-                }
-                if big_config.tab_config.directory_first {
-                    match big_config.tab_config.sort_by_name {
-                        SortEnum::ASC => {
-                            result.sort_by(|one, two| match one.is_file().cmp(&two.is_file()) {
-                                cmp::Ordering::Equal => one.get_name().cmp(&two.get_name()),
-                                other => other,
-                            });
-                            return result;
-                        }
-                        SortEnum::DESC => {
-                            result.sort_by_key(|w| (w.is_file(), std::cmp::Reverse(w.get_name())));
-                            return result;
-                        }
-                        SortEnum::NONE => {}
-                    }
-                    match big_config.tab_config.sort_by_attr {
-                        SortEnum::ASC => {
-                            result.sort_by(|one, two| match one.is_file().cmp(&two.is_file()) {
-                                cmp::Ordering::Equal => one.get_size().cmp(&two.get_size()),
-                                other => other,
-                            });
-                        }
-                        SortEnum::DESC => {
-                            result.sort_by_key(|w| (w.is_file(), std::cmp::Reverse(w.get_size())));
-                            return result;
-                        }
-                        SortEnum::NONE => {}
-                    }
-                    match big_config.tab_config.sort_by_date {
-                        SortEnum::ASC => {
-                            result.sort_by(|one, two| match one.is_file().cmp(&two.is_file()) {
-                                cmp::Ordering::Equal => one.get_modified().cmp(&two.get_modified()),
-                                other => other,
-                            });
-                        }
-                        SortEnum::DESC => {
-                            result.sort_by_key(|w| {
-                                (w.is_file(), std::cmp::Reverse(w.get_modified()))
-                            });
-                            return result;
-                        }
-                        SortEnum::NONE => {}
-                    }
-                } else {
-                    match big_config.tab_config.sort_by_date {
-                        SortEnum::ASC => {
-                            result.sort_by(|one, two| one.get_modified().cmp(&two.get_modified()));
-                        }
-                        SortEnum::DESC => {
-                            result.sort_by_key(|w| std::cmp::Reverse(w.get_modified()));
-                        }
-                        SortEnum::NONE => {}
-                    }
-                    match big_config.tab_config.sort_by_name {
-                        SortEnum::ASC => {
-                            result.sort_by(|one, two| one.get_name().cmp(&two.get_name()));
-                        }
-                        SortEnum::DESC => {
-                            result.sort_by_key(|w| std::cmp::Reverse(w.get_name()));
-                        }
-                        SortEnum::NONE => {}
-                    }
-                    match big_config.tab_config.sort_by_attr {
-                        SortEnum::ASC => {
-                            result.sort_by(|one, two| one.get_size().cmp(&two.get_size()));
-                        }
-                        SortEnum::DESC => {
-                            result.sort_by_key(|w| std::cmp::Reverse(w.get_size()));
-                        }
-                        SortEnum::NONE => {}
-                    }
-                }
-                result
-            }
-            Err(_) => Vec::new(),
-        }
+        list_file_system_items(self, path, big_config)
     }
 
     fn read_to_string<TPath: AsRef<Path>>(&self, path: TPath) -> Option<String> {
@@ -150,6 +102,10 @@ impl FileSystem for PhysicalFileSystem {
         }
     }
 
+    fn open_file<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<Box<dyn Read + Send>> {
+        Ok(Box::new(File::open(path)?))
+    }
+
     fn delete_file<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()> {
         fs::remove_file(path)
     }
@@ -170,8 +126,8 @@ impl FileSystem for PhysicalFileSystem {
         create_link(target, source)
     }
 
-    fn create_file<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<File> {
-        File::create(path)
+    fn create_file<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()> {
+        File::create(path).map(|_| ())
     }
 
     fn create_dir<TPath: AsRef<Path>>(&mut self, path: TPath) -> io::Result<()> {
@@ -183,29 +139,129 @@ impl FileSystem for PhysicalFileSystem {
     }
 
     fn copy_file<TPath: AsRef<Path>>(&mut self, source: TPath, target: TPath) -> io::Result<u64> {
-        fs::copy(source, target)
+        copy_engine::copy_file(source.as_ref(), target.as_ref())
     }
 
     fn copy_dir<TPath: AsRef<Path>>(&mut self, source: TPath, target: TPath) -> io::Result<u64> {
-        fs::create_dir_all(target.as_ref())?;
-        for entry in fs::read_dir(source)? {
-            let entry = entry?;
-            let file_type = entry.file_type()?;
-            if file_type.is_dir() {
-                self.copy_dir(entry.path(), target.as_ref().join(entry.file_name()))?;
-            } else {
-                self.copy_file(entry.path(), target.as_ref().join(entry.file_name()))?;
-            }
-        }
-        Ok(0)
+        copy_recursive(
+            self,
+            source.as_ref(),
+            target.as_ref(),
+            &CopyOptions::default(),
+            &mut CopyEverything,
+        )
     }
 
     fn exist<TPath: AsRef<Path>>(&self, path: TPath) -> bool {
-        let path = path.as_ref();
-        if path.is_dir() || path.is_file() {
-            return path.exists();
+        fs::symlink_metadata(path).is_ok()
+    }
+
+    fn metadata<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<ItemMetadata> {
+        fs::symlink_metadata(path).map(|metadata| map_metadata_to_item_metadata(&metadata))
+    }
+
+    fn read_dir<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<Vec<PathBuf>> {
+        let mut result = Vec::new();
+        for entry in fs::read_dir(path)? {
+            result.push(entry?.path());
         }
-        return true;
+        Ok(result)
+    }
+
+    fn read_link<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<PathBuf> {
+        fs::read_link(path)
+    }
+
+    fn canonicalize<TPath: AsRef<Path>>(&self, path: TPath) -> io::Result<PathBuf> {
+        fs::canonicalize(path)
+    }
+
+    fn set_permissions<TPath: AsRef<Path>>(&mut self, path: TPath, mode: u32) -> io::Result<()> {
+        fs::set_permissions(path, Permissions::from_mode(mode))
+    }
+
+    fn set_times<TPath: AsRef<Path>>(
+        &mut self,
+        path: TPath,
+        accessed: SystemTime,
+        modified: SystemTime,
+    ) -> io::Result<()> {
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+        let times = [to_timespec(accessed), to_timespec(modified)];
+        // Opening the item to set its times would block on a FIFO and
+        // follow symlinks, so the path based call is used instead.
+        let result = unsafe {
+            libc::utimensat(
+                libc::AT_FDCWD,
+                path.as_ptr(),
+                times.as_ptr(),
+                libc::AT_SYMLINK_NOFOLLOW,
+            )
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    fn set_owner<TPath: AsRef<Path>>(&mut self, path: TPath, uid: u32, gid: u32) -> io::Result<()> {
+        std::os::unix::fs::lchown(path, Some(uid), Some(gid))
+    }
+
+    fn create_special_file<TPath: AsRef<Path>>(
+        &mut self,
+        path: TPath,
+        metadata: &ItemMetadata,
+    ) -> io::Result<()> {
+        let file_type = match metadata.item_type {
+            ItemType::Fifo => libc::S_IFIFO,
+            ItemType::CharDevice => libc::S_IFCHR,
+            ItemType::BlockDevice => libc::S_IFBLK,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{:?} is not a special file", metadata.item_type),
+                ))
+            }
+        };
+        let path = CString::new(path.as_ref().as_os_str().as_bytes())?;
+        let result = unsafe {
+            libc::mknod(
+                path.as_ptr(),
+                file_type | (metadata.mode & 0o7777) as libc::mode_t,
+                metadata.rdev as libc::dev_t,
+            )
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    fn write_new_file<TPath: AsRef<Path>>(
+        &mut self,
+        path: TPath,
+        content: &[u8],
+    ) -> io::Result<()> {
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)?
+            .write_all(content)
+    }
+
+    fn data_home(&self) -> Option<PathBuf> {
+        dirs::data_dir()
+    }
+
+    fn temp_dir(&self) -> PathBuf {
+        std::env::temp_dir()
+    }
+
+    fn current_uid(&self) -> u32 {
+        users::get_current_uid()
     }
 }
 
@@ -216,19 +272,24 @@ pub struct DirInfo {
 }
 
 impl DirInfo {
-    pub fn new<TPath: AsRef<Path>>(path: &TPath) -> Option<Self> {
-        if let Ok(path_buffer) = fs::canonicalize(path) {
-            let name = if let Some(file_name) = path_buffer.file_name() {
-                file_name.to_str().unwrap_or("")
-            } else {
-                ""
-            };
-            let path = path_buffer.as_path().to_str().unwrap_or("");
-            return Some(DirInfo {
-                name: name.to_string(),
-                path: PathBuf::from(path),
-            });
+    pub fn new<TPath: AsRef<Path>>(path: TPath) -> Self {
+        let path = path.as_ref();
+        let name = if let Some(file_name) = path.file_name() {
+            file_name.to_str().unwrap_or("")
+        } else {
+            ""
+        };
+        DirInfo {
+            name: name.to_string(),
+            path: path.to_path_buf(),
         }
-        None
+    }
+}
+
+fn to_timespec(time: SystemTime) -> libc::timespec {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    libc::timespec {
+        tv_sec: since_epoch.as_secs() as libc::time_t,
+        tv_nsec: since_epoch.subsec_nanos() as libc::c_long,
     }
 }
@@ -78,6 +78,10 @@ impl SymlinkItem {
         self.path.clone()
     }
 
+    pub fn get_target(&self) -> PathBuf {
+        self.target.clone()
+    }
+
     pub fn is_visible(&self) -> bool {
         self.name.starts_with('.')
     }
@@ -0,0 +1,321 @@
+use std::{
+    ffi::OsStr,
+    io,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+
+use super::{functions::first_free_path, FileSystem};
+
+// Implementation of the freedesktop.org Trash specification:
+// https://specifications.freedesktop.org/trash-spec/trashspec-latest.html
+
+const STICKY_BIT: u32 = 0o1000;
+const TRASH_DIR_MODE: u32 = 0o700;
+const TRASHINFO_EXTENSION: &str = "trashinfo";
+
+/// Moves `path` into the trash directory that lives on the same device and
+/// returns the entry describing the trashed item.
+pub fn move_to_trash<TFileSystem: FileSystem, TPath: AsRef<Path>>(
+    file_system: &mut TFileSystem,
+    path: TPath,
+) -> io::Result<TrashEntry> {
+    let path = absolute_path(file_system, path.as_ref())?;
+    let (trash_dir, top_dir) = find_trash_dir(file_system, &path)?;
+    let files_dir = trash_dir.join("files");
+    let info_dir = trash_dir.join("info");
+    create_trash_dir(file_system, &files_dir)?;
+    create_trash_dir(file_system, &info_dir)?;
+
+    let original_path = match &top_dir {
+        Some(top_dir) => path.strip_prefix(top_dir).unwrap_or(&path).to_path_buf(),
+        None => path.clone(),
+    };
+    let deletion_date = Local::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+    let trash_info = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(&original_path),
+        deletion_date
+    );
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("{:?}", path)))?;
+    let mut counter = 1;
+    loop {
+        let trash_name = trash_file_name(file_name, counter);
+        let info_path = info_dir.join(format!("{}.{}", trash_name, TRASHINFO_EXTENSION));
+        let trashed_path = files_dir.join(&trash_name);
+        counter += 1;
+        if file_system.exist(&trashed_path) {
+            continue;
+        }
+        match file_system.write_new_file(&info_path, trash_info.as_bytes()) {
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(err) => return Err(err),
+        }
+
+        return match file_system.rename_item(&path, &trashed_path) {
+            Ok(_) => Ok(TrashEntry {
+                trashed_path,
+                info_path,
+                original_path: path,
+                deletion_date,
+            }),
+            Err(err) => {
+                let _ = file_system.delete_file(&info_path);
+                Err(err)
+            }
+        };
+    }
+}
+
+/// Returns the trash directory for `path` together with the top directory of
+/// its mount point when the per-mount trash is used (paths stored in that
+/// trash are relative to it).
+fn find_trash_dir<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    path: &Path,
+) -> io::Result<(PathBuf, Option<PathBuf>)> {
+    let item_dev = file_system.metadata(path)?.dev;
+    let home_trash = file_system
+        .data_home()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Cannot find data home directory"))?
+        .join("Trash");
+    if device_of_nearest_existing(file_system, &home_trash) == Some(item_dev) {
+        return Ok((home_trash, None));
+    }
+
+    let top_dir = mount_top_dir(file_system, path, item_dev);
+    let uid = file_system.current_uid();
+
+    let admin_trash = top_dir.join(".Trash");
+    if let Ok(metadata) = file_system.metadata(&admin_trash) {
+        if metadata.is_dir() && metadata.mode & STICKY_BIT != 0 {
+            let user_trash = admin_trash.join(uid.to_string());
+            if create_trash_dir(file_system, &user_trash).is_ok() {
+                return Ok((user_trash, Some(top_dir)));
+            }
+        }
+    }
+
+    let user_trash = top_dir.join(format!(".Trash-{}", uid));
+    create_trash_dir(file_system, &user_trash)?;
+    Ok((user_trash, Some(top_dir)))
+}
+
+fn mount_top_dir<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    path: &Path,
+    dev: u64,
+) -> PathBuf {
+    let mut top_dir = path.parent().unwrap_or(path).to_path_buf();
+    while let Some(parent) = top_dir.parent() {
+        match file_system.metadata(parent) {
+            Ok(metadata) if metadata.dev == dev => top_dir = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+    top_dir
+}
+
+fn device_of_nearest_existing<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    path: &Path,
+) -> Option<u64> {
+    path.ancestors()
+        .find_map(|ancestor| file_system.metadata(ancestor).ok())
+        .map(|metadata| metadata.dev)
+}
+
+fn create_trash_dir<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    path: &Path,
+) -> io::Result<()> {
+    let missing: Vec<PathBuf> = path
+        .ancestors()
+        .take_while(|ancestor| !file_system.exist(ancestor))
+        .map(Path::to_path_buf)
+        .collect();
+    for dir in missing.iter().rev() {
+        file_system.create_dir(dir)?;
+        file_system.set_permissions(dir, TRASH_DIR_MODE)?;
+    }
+    match file_system.metadata(path) {
+        Ok(metadata) if metadata.is_dir() => Ok(()),
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::NotADirectory,
+            format!("{:?}", path),
+        )),
+        Err(err) => Err(err),
+    }
+}
+
+fn absolute_path<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    path: &Path,
+) -> io::Result<PathBuf> {
+    // The item itself must not be resolved, a trashed symlink stays a symlink.
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty());
+    let parent = file_system.canonicalize(parent.unwrap_or_else(|| Path::new(".")))?;
+    match path.file_name() {
+        Some(file_name) => Ok(parent.join(file_name)),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?}", path),
+        )),
+    }
+}
+
+fn trash_file_name(file_name: &OsStr, counter: usize) -> String {
+    let file_name = file_name.to_string_lossy().to_string();
+    if counter == 1 {
+        return file_name;
+    }
+    match file_name.rfind('.') {
+        Some(idx) if idx > 0 => format!("{}.{}{}", &file_name[..idx], counter, &file_name[idx..]),
+        _ => format!("{}.{}", file_name, counter),
+    }
+}
+
+fn encode_path(path: &Path) -> String {
+    let mut result = String::new();
+    for &byte in path.as_os_str().as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                result.push(byte as char)
+            }
+            _ => result.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    result
+}
+
+#[derive(Clone, Debug)]
+pub struct TrashEntry {
+    pub trashed_path: PathBuf,
+    pub info_path: PathBuf,
+    pub original_path: PathBuf,
+    pub deletion_date: String,
+}
+
+pub fn home_trash_dir<TFileSystem: FileSystem>(file_system: &TFileSystem) -> Option<PathBuf> {
+    file_system
+        .data_home()
+        .map(|data_home| data_home.join("Trash"))
+}
+
+/// Lists the items of the home trash which have a matching `.trashinfo` file.
+pub fn list_trash<TFileSystem: FileSystem>(file_system: &TFileSystem) -> Vec<TrashEntry> {
+    let trash_dir = match home_trash_dir(file_system) {
+        Some(trash_dir) => trash_dir,
+        None => return Vec::new(),
+    };
+    let info_paths = match file_system.read_dir(trash_dir.join("info")) {
+        Ok(info_paths) => info_paths,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut result = Vec::new();
+    for info_path in info_paths {
+        if info_path.extension() != Some(OsStr::new(TRASHINFO_EXTENSION)) {
+            continue;
+        }
+        let trashed_path = match info_path.file_stem() {
+            Some(name) => trash_dir.join("files").join(name),
+            None => continue,
+        };
+        if !file_system.exist(&trashed_path) {
+            continue;
+        }
+        if let Some(content) = file_system.read_to_string(&info_path) {
+            if let Some((original_path, deletion_date)) = parse_trash_info(&content) {
+                result.push(TrashEntry {
+                    trashed_path,
+                    info_path,
+                    original_path,
+                    deletion_date,
+                });
+            }
+        }
+    }
+    result
+}
+
+/// Moves a trashed item back to its original location. When that location is
+/// taken the item is restored as `name (1)`, `name (2)` and so on.
+pub fn restore_from_trash<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    entry: &TrashEntry,
+) -> io::Result<PathBuf> {
+    if let Some(parent) = entry.original_path.parent() {
+        let missing: Vec<PathBuf> = parent
+            .ancestors()
+            .take_while(|ancestor| !file_system.exist(ancestor))
+            .map(Path::to_path_buf)
+            .collect();
+        for dir in missing.iter().rev() {
+            file_system.create_dir(dir)?;
+        }
+    }
+
+    let target = first_free_path(file_system, &entry.original_path);
+
+    file_system.rename_item(&entry.trashed_path, &target)?;
+    file_system.delete_file(&entry.info_path)?;
+    Ok(target)
+}
+
+/// Removes a trashed item together with its `.trashinfo` file.
+pub fn purge_from_trash<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    entry: &TrashEntry,
+) -> io::Result<()> {
+    if file_system.metadata(&entry.trashed_path)?.is_dir() {
+        file_system.delete_dir(&entry.trashed_path)?;
+    } else {
+        file_system.delete_file(&entry.trashed_path)?;
+    }
+    file_system.delete_file(&entry.info_path)
+}
+
+fn parse_trash_info(content: &str) -> Option<(PathBuf, String)> {
+    let mut lines = content.lines().map(str::trim);
+    if lines.next() != Some("[Trash Info]") {
+        return None;
+    }
+    let mut original_path = None;
+    let mut deletion_date = String::new();
+    for line in lines {
+        if let Some(path) = line.strip_prefix("Path=") {
+            original_path = Some(decode_path(path));
+        } else if let Some(date) = line.strip_prefix("DeletionDate=") {
+            deletion_date = date.to_string();
+        }
+    }
+    original_path.map(|path| (path, deletion_date))
+}
+
+fn decode_path(encoded: &str) -> PathBuf {
+    let bytes = encoded.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' && idx + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[idx + 1..idx + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                result.push(byte);
+                idx += 3;
+                continue;
+            }
+        }
+        result.push(bytes[idx]);
+        idx += 1;
+    }
+    PathBuf::from(OsStr::from_bytes(&result))
+}
@@ -0,0 +1,145 @@
+use crate::core::config::FilterMode;
+
+const SCORE_MATCH: i64 = 16;
+const SCORE_GAP_START: i64 = -3;
+const SCORE_GAP_EXTENSION: i64 = -1;
+/// Matching the first character of a word, after a separator or at the
+/// start of the name.
+const BONUS_BOUNDARY: i64 = 8;
+/// Matching an uppercase character following a lowercase one.
+const BONUS_CAMEL_CASE: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 4;
+/// The first character of the phrase weighs more.
+const BONUS_FIRST_CHAR_MULTIPLIER: i64 = 2;
+
+/// How well a name matches the filter phrase, `positions` are the indices of
+/// the matched characters in the name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FilterMatch {
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+/// Matches `name` against the filter `phrase`, ignoring case. An empty phrase
+/// matches every name.
+pub fn match_name(mode: FilterMode, phrase: &str, name: &str) -> Option<FilterMatch> {
+    if phrase.is_empty() {
+        return Some(FilterMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    let name: Vec<char> = name.chars().collect();
+    let phrase: Vec<char> = phrase.chars().map(lowercase).collect();
+    match mode {
+        FilterMode::Substring => substring_match(&phrase, &name),
+        FilterMode::Fuzzy => fuzzy_match(&phrase, &name),
+    }
+}
+
+fn substring_match(phrase: &[char], name: &[char]) -> Option<FilterMatch> {
+    let lowered: Vec<char> = name.iter().copied().map(lowercase).collect();
+    let start = lowered
+        .windows(phrase.len())
+        .position(|window| window == phrase)?;
+    Some(FilterMatch {
+        score: 0,
+        positions: (start..start + phrase.len()).collect(),
+    })
+}
+
+/// Scores every way the phrase can be spread over the name as a subsequence
+/// and keeps the best, the way fzf and skim rank their matches: matched
+/// characters at word starts and runs of consecutive characters score more,
+/// gaps between them cost.
+fn fuzzy_match(phrase: &[char], name: &[char]) -> Option<FilterMatch> {
+    let lowered: Vec<char> = name.iter().copied().map(lowercase).collect();
+    if !is_subsequence(phrase, &lowered) {
+        return None;
+    }
+    let bonuses: Vec<i64> = (0..name.len()).map(|idx| bonus(name, idx)).collect();
+
+    // `scores[i][j]` is the best score of the first `i + 1` characters of the
+    // phrase with the last one matched at `j`, `from[i][j]` where the one
+    // before it was matched and `runs[i][j]` the bonus of the run of
+    // consecutive matches it ends, which every character of the run gets.
+    let columns = name.len();
+    let mut scores = vec![vec![None; columns]; phrase.len()];
+    let mut from = vec![vec![0; columns]; phrase.len()];
+    let mut runs = vec![vec![0; columns]; phrase.len()];
+    for (i, &c) in phrase.iter().enumerate() {
+        // The best previous match at least one character back, with the cost
+        // of the gap up to the current column.
+        let mut gap: Option<(i64, usize)> = None;
+        for j in i..columns {
+            if i > 0 && j >= 2 {
+                let extended = gap.map(|(score, k)| (score + SCORE_GAP_EXTENSION, k));
+                let started = scores[i - 1][j - 2].map(|score| (score + SCORE_GAP_START, j - 2));
+                gap = match (extended, started) {
+                    (Some(extended), Some(started)) if started.0 >= extended.0 => Some(started),
+                    (Some(extended), _) => Some(extended),
+                    (None, started) => started,
+                };
+            }
+            if lowered[j] != c {
+                continue;
+            }
+            runs[i][j] = bonuses[j];
+            if i == 0 {
+                scores[i][j] = Some(SCORE_MATCH + bonuses[j] * BONUS_FIRST_CHAR_MULTIPLIER);
+                continue;
+            }
+            let run = runs[i - 1][j - 1].max(bonuses[j]).max(BONUS_CONSECUTIVE);
+            let consecutive = scores[i - 1][j - 1].map(|score| (score + SCORE_MATCH + run, j - 1));
+            let after_gap = gap.map(|(score, k)| (score + SCORE_MATCH + bonuses[j], k));
+            let best = match (consecutive, after_gap) {
+                (Some(consecutive), Some(after_gap)) if after_gap.0 > consecutive.0 => {
+                    Some(after_gap)
+                }
+                (Some(consecutive), _) => {
+                    runs[i][j] = run;
+                    Some(consecutive)
+                }
+                (None, after_gap) => after_gap,
+            };
+            if let Some((score, k)) = best {
+                scores[i][j] = Some(score);
+                from[i][j] = k;
+            }
+        }
+    }
+
+    let last = phrase.len() - 1;
+    let (mut j, score) = scores[last]
+        .iter()
+        .enumerate()
+        .filter_map(|(j, score)| score.map(|score| (j, score)))
+        .max_by_key(|&(j, score)| (score, std::cmp::Reverse(j)))?;
+    let mut positions = vec![j];
+    for i in (1..=last).rev() {
+        j = from[i][j];
+        positions.push(j);
+    }
+    positions.reverse();
+    Some(FilterMatch { score, positions })
+}
+
+fn is_subsequence(phrase: &[char], name: &[char]) -> bool {
+    let mut name = name.iter();
+    phrase.iter().all(|c| name.any(|n| n == c))
+}
+
+fn bonus(name: &[char], idx: usize) -> i64 {
+    let current = name[idx];
+    match idx.checked_sub(1).map(|prev| name[prev]) {
+        None => BONUS_BOUNDARY,
+        Some(prev) if !prev.is_alphanumeric() && current.is_alphanumeric() => BONUS_BOUNDARY,
+        Some(prev) if prev.is_lowercase() && current.is_uppercase() => BONUS_CAMEL_CASE,
+        Some(prev) if !prev.is_numeric() && current.is_numeric() => BONUS_CAMEL_CASE,
+        _ => 0,
+    }
+}
+
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
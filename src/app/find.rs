@@ -0,0 +1,454 @@
+use std::{
+    fmt::{self, Display},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use chrono::{Local, NaiveDate, TimeZone};
+use regex::{Regex, RegexBuilder};
+
+use super::{
+    actions::{FileManagerActions, FindAction},
+    file_system::{
+        item_metadata::{ItemMetadata, ItemType},
+        FileSystem,
+    },
+};
+
+pub type FindId = usize;
+
+static NEXT_FIND_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// How often found items are sent to the result tab.
+pub const RESULTS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameSyntax {
+    Glob,
+    Regex,
+}
+
+impl NameSyntax {
+    pub fn next(self) -> Self {
+        match self {
+            NameSyntax::Glob => NameSyntax::Regex,
+            NameSyntax::Regex => NameSyntax::Glob,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        self.next()
+    }
+}
+
+impl Display for NameSyntax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameSyntax::Glob => write!(f, "glob"),
+            NameSyntax::Regex => write!(f, "regex"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypeFilter {
+    Any,
+    File,
+    Directory,
+    Symlink,
+}
+
+impl TypeFilter {
+    pub fn next(self) -> Self {
+        match self {
+            TypeFilter::Any => TypeFilter::File,
+            TypeFilter::File => TypeFilter::Directory,
+            TypeFilter::Directory => TypeFilter::Symlink,
+            TypeFilter::Symlink => TypeFilter::Any,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            TypeFilter::Any => TypeFilter::Symlink,
+            TypeFilter::File => TypeFilter::Any,
+            TypeFilter::Directory => TypeFilter::File,
+            TypeFilter::Symlink => TypeFilter::Directory,
+        }
+    }
+
+    fn matches(self, item_type: ItemType) -> bool {
+        match self {
+            TypeFilter::Any => true,
+            TypeFilter::File => item_type == ItemType::File,
+            TypeFilter::Directory => item_type == ItemType::Directory,
+            TypeFilter::Symlink => item_type == ItemType::Symlink,
+        }
+    }
+}
+
+impl Display for TypeFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            TypeFilter::Any => "any",
+            TypeFilter::File => "files",
+            TypeFilter::Directory => "directories",
+            TypeFilter::Symlink => "symlinks",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// What the find tool looks for, as typed by the user. Empty fields match
+/// everything.
+///
+/// Globs understand `*`, `?` and `[...]`, ignore case and can be joined with
+/// `;`. Sizes and modification times are ranges written as `FROM..TO` with
+/// either end left out. Sizes take the `k`, `M`, `G` and `T` suffixes (powers
+/// of 1024), times are dates like `2024-01-31` or ages like `30m`, `12h`,
+/// `7d` or `2w` counted back from now. A single size is matched exactly, a
+/// single date matches that day and a single age the time since then.
+#[derive(Clone, Debug)]
+pub struct FindQuery {
+    pub name: String,
+    pub name_syntax: NameSyntax,
+    pub size: String,
+    pub modified: String,
+    /// User name or id.
+    pub owner: String,
+    pub item_type: TypeFilter,
+}
+
+impl Default for FindQuery {
+    fn default() -> Self {
+        FindQuery {
+            name: String::new(),
+            name_syntax: NameSyntax::Glob,
+            size: String::new(),
+            modified: String::new(),
+            owner: String::new(),
+            item_type: TypeFilter::Any,
+        }
+    }
+}
+
+impl FindQuery {
+    /// Parses the fields, `now` is where ages are counted from.
+    pub fn criteria(&self, now: SystemTime) -> Result<FindCriteria, String> {
+        let name = match (self.name.trim(), self.name_syntax) {
+            ("", _) => None,
+            (name, NameSyntax::Glob) => Some(glob_regex(name)?),
+            (name, NameSyntax::Regex) => Some(
+                Regex::new(name)
+                    .map_err(|err| format!("invalid name regex: {}", regex_reason(&err)))?,
+            ),
+        };
+        let (min_size, max_size) =
+            parse_range(&self.size, parse_size).map_err(|err| format!("invalid size: {}", err))?;
+        let (modified_after, modified_before) = parse_modified(&self.modified, now)
+            .map_err(|err| format!("invalid modification time: {}", err))?;
+        let owner = Some(self.owner.trim().to_string()).filter(|owner| !owner.is_empty());
+
+        Ok(FindCriteria {
+            name,
+            min_size,
+            max_size,
+            modified_after,
+            modified_before,
+            owner,
+            item_type: self.item_type,
+        })
+    }
+}
+
+impl Display for FindQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if !self.name.trim().is_empty() {
+            parts.push(self.name.trim().to_string());
+        }
+        if !self.size.trim().is_empty() {
+            parts.push(format!("size {}", self.size.trim()));
+        }
+        if !self.modified.trim().is_empty() {
+            parts.push(format!("modified {}", self.modified.trim()));
+        }
+        if !self.owner.trim().is_empty() {
+            parts.push(format!("owner {}", self.owner.trim()));
+        }
+        if self.item_type != TypeFilter::Any {
+            parts.push(self.item_type.to_string());
+        }
+        if parts.is_empty() {
+            write!(f, "*")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+/// Parsed form of a `FindQuery`, bounds are inclusive.
+#[derive(Clone, Debug)]
+pub struct FindCriteria {
+    name: Option<Regex>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified_after: Option<SystemTime>,
+    modified_before: Option<SystemTime>,
+    owner: Option<String>,
+    item_type: TypeFilter,
+}
+
+impl FindCriteria {
+    pub fn matches(&self, name: &str, metadata: &ItemMetadata) -> bool {
+        self.item_type.matches(metadata.item_type)
+            && self.name.as_ref().is_none_or(|regex| regex.is_match(name))
+            && self.min_size.is_none_or(|min| metadata.size >= min)
+            && self.max_size.is_none_or(|max| metadata.size <= max)
+            && self
+                .modified_after
+                .is_none_or(|after| metadata.modified >= after)
+            && self
+                .modified_before
+                .is_none_or(|before| metadata.modified <= before)
+            && self.owner.as_ref().is_none_or(|owner| {
+                metadata.username == *owner || metadata.uid.to_string() == *owner
+            })
+    }
+}
+
+/// A running search. The walk stops once it is cancelled, which happens when
+/// no tab shows its results anymore.
+#[derive(Clone, Debug)]
+pub struct FindSearch {
+    pub id: FindId,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl FindSearch {
+    pub fn new() -> Self {
+        FindSearch {
+            id: NEXT_FIND_ID.fetch_add(1, Ordering::SeqCst),
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for FindSearch {
+    fn default() -> Self {
+        FindSearch::new()
+    }
+}
+
+/// Walks the tree below `root` on a worker thread and sends the matching
+/// items in batches as they are found. Symlinks are not followed and
+/// directories which can't be read are skipped.
+pub fn find_files<TFileSystem: Clone + FileSystem + Send + 'static>(
+    file_system: TFileSystem,
+    root: PathBuf,
+    criteria: FindCriteria,
+    search: FindSearch,
+    sender: Sender<FileManagerActions>,
+) {
+    thread::spawn(move || {
+        let send = |action: FindAction| sender.send(FileManagerActions::Find(action)).is_ok();
+        let mut found = Vec::new();
+        let mut last_sent = Instant::now();
+        let mut pending = vec![root];
+        while let Some(dir) = pending.pop() {
+            if search.is_cancelled() {
+                return;
+            }
+            let mut children = match file_system.read_dir(&dir) {
+                Ok(children) => children,
+                Err(_) => continue,
+            };
+            children.sort();
+            for child in children.into_iter().rev() {
+                let metadata = match file_system.metadata(&child) {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                };
+                let name = child
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                if criteria.matches(&name, &metadata) {
+                    found.push(child.clone());
+                }
+                if metadata.is_dir() {
+                    pending.push(child);
+                }
+            }
+            if !found.is_empty() && last_sent.elapsed() >= RESULTS_INTERVAL {
+                let paths = std::mem::take(&mut found);
+                if !send(FindAction::Found {
+                    search: search.clone(),
+                    paths,
+                }) {
+                    return;
+                }
+                last_sent = Instant::now();
+            }
+        }
+        if !found.is_empty() {
+            send(FindAction::Found {
+                search: search.clone(),
+                paths: found,
+            });
+        }
+        send(FindAction::Finished { id: search.id });
+    });
+}
+
+fn glob_regex(globs: &str) -> Result<Regex, String> {
+    let alternatives: Vec<String> = globs
+        .split(';')
+        .map(str::trim)
+        .filter(|glob| !glob.is_empty())
+        .map(glob_to_regex)
+        .collect();
+    RegexBuilder::new(&format!("^(?:{})$", alternatives.join("|")))
+        .case_insensitive(true)
+        .build()
+        .map_err(|err| format!("invalid name glob: {}", regex_reason(&err)))
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '[' => {
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
+
+fn regex_reason(err: &regex::Error) -> String {
+    // Syntax errors span several lines with the reason on the last.
+    let err = err.to_string();
+    let reason = err.lines().last().unwrap_or_default();
+    reason.trim_start_matches("error: ").to_string()
+}
+
+/// Splits `FROM..TO`, a value without `..` is both ends.
+fn parse_range<T: Copy>(
+    text: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<(Option<T>, Option<T>), String> {
+    let text = text.trim();
+    let parse_bound = |bound: &str| match bound.trim() {
+        "" => Ok(None),
+        bound => parse(bound).map(Some),
+    };
+    match text.split_once("..") {
+        Some((from, to)) => Ok((parse_bound(from)?, parse_bound(to)?)),
+        None => {
+            let value = parse_bound(text)?;
+            Ok((value, value))
+        }
+    }
+}
+
+fn parse_size(text: &str) -> Result<u64, String> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", text))?;
+    let multiplier: u64 = match unit.trim().trim_end_matches(['B', 'b']) {
+        "" => 1,
+        "k" | "K" | "Ki" => 1 << 10,
+        "m" | "M" | "Mi" => 1 << 20,
+        "g" | "G" | "Gi" => 1 << 30,
+        "t" | "T" | "Ti" => 1 << 40,
+        _ => return Err(format!("unknown unit in '{}'", text)),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// A date covers the whole day and an age alone the time since then, in a
+/// range both are points in time.
+fn parse_modified(
+    text: &str,
+    now: SystemTime,
+) -> Result<(Option<SystemTime>, Option<SystemTime>), String> {
+    if text.contains("..") {
+        return parse_range(text, |bound| parse_time(bound, now));
+    }
+    match text.trim() {
+        "" => Ok((None, None)),
+        text => match parse_date(text) {
+            Some(day) => Ok((Some(day), Some(day + Duration::from_secs(24 * 60 * 60 - 1)))),
+            None => Ok((Some(parse_time(text, now)?), None)),
+        },
+    }
+}
+
+fn parse_time(text: &str, now: SystemTime) -> Result<SystemTime, String> {
+    if text.contains('-') {
+        return parse_date(text).ok_or_else(|| format!("'{}' is not a valid date", text));
+    }
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a date or an age", text))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" | "" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown unit in '{}'", text)),
+    };
+    Ok(now
+        .checked_sub(Duration::from_secs(number * seconds))
+        .unwrap_or(SystemTime::UNIX_EPOCH))
+}
+
+fn parse_date(text: &str) -> Option<SystemTime> {
+    let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()?;
+    let midnight = Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()?;
+    Some(SystemTime::from(midnight))
+}
@@ -0,0 +1,364 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    sync::{mpsc::Sender, Arc},
+    thread,
+    time::Instant,
+};
+
+use regex::{Regex, RegexBuilder};
+
+use super::{
+    actions::{FileManagerActions, GrepAction},
+    file_system::{item_metadata::ItemType, FileSystem},
+    find::{FindSearch, RESULTS_INTERVAL},
+};
+
+/// Longest part of a matching line kept for the result list.
+const MAX_LINE_LENGTH: usize = 200;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatternSyntax {
+    Literal,
+    Regex,
+}
+
+impl PatternSyntax {
+    pub fn next(self) -> Self {
+        match self {
+            PatternSyntax::Literal => PatternSyntax::Regex,
+            PatternSyntax::Regex => PatternSyntax::Literal,
+        }
+    }
+}
+
+impl Display for PatternSyntax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatternSyntax::Literal => write!(f, "literal"),
+            PatternSyntax::Regex => write!(f, "regex"),
+        }
+    }
+}
+
+/// What to look for in the content of the files.
+#[derive(Clone, Debug)]
+pub struct GrepQuery {
+    pub pattern: String,
+    pub syntax: PatternSyntax,
+    pub ignore_case: bool,
+}
+
+impl Default for GrepQuery {
+    fn default() -> Self {
+        GrepQuery {
+            pattern: String::new(),
+            syntax: PatternSyntax::Literal,
+            ignore_case: false,
+        }
+    }
+}
+
+impl GrepQuery {
+    pub fn regex(&self) -> Result<Regex, String> {
+        if self.pattern.is_empty() {
+            return Err("the pattern is empty".to_string());
+        }
+        let pattern = match self.syntax {
+            PatternSyntax::Literal => regex::escape(&self.pattern),
+            PatternSyntax::Regex => self.pattern.clone(),
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(self.ignore_case)
+            .build()
+            .map_err(|err| {
+                let err = err.to_string();
+                let reason = err.lines().last().unwrap_or_default();
+                format!("invalid regex: {}", reason.trim_start_matches("error: "))
+            })
+    }
+}
+
+/// A line containing the pattern, `line` counts from 1.
+#[derive(Clone, Debug)]
+pub struct GrepMatch {
+    pub path: PathBuf,
+    pub line: usize,
+    pub text: String,
+}
+
+/// Content search below `root`, matches keep coming while `searching`.
+#[derive(Clone, Debug)]
+pub struct GrepResults {
+    pub root: PathBuf,
+    pub query: GrepQuery,
+    pub search: FindSearch,
+    pub matches: Vec<GrepMatch>,
+    pub searching: bool,
+}
+
+impl GrepResults {
+    pub fn new(root: PathBuf, query: GrepQuery, search: FindSearch) -> Self {
+        GrepResults {
+            root,
+            query,
+            search,
+            matches: Vec::new(),
+            searching: true,
+        }
+    }
+}
+
+/// Searches the files below `root` on a worker thread and sends the matching
+/// lines in batches as they are found. Binary files, symlinks and items
+/// ignored by `.gitignore` files are skipped.
+pub fn grep_files<TFileSystem: Clone + FileSystem + Send + 'static>(
+    file_system: TFileSystem,
+    root: PathBuf,
+    regex: Regex,
+    search: FindSearch,
+    sender: Sender<FileManagerActions>,
+) {
+    thread::spawn(move || {
+        let send = |action: GrepAction| sender.send(FileManagerActions::Grep(action)).is_ok();
+        let mut found = Vec::new();
+        let mut last_sent = Instant::now();
+        let mut pending = vec![(root.clone(), repository_ignores(&file_system, &root))];
+        while let Some((dir, mut ignores)) = pending.pop() {
+            if search.is_cancelled() {
+                return;
+            }
+            if let Some(rules) = IgnoreRules::read(&file_system, &dir) {
+                ignores.push(Arc::new(rules));
+            }
+            let mut children = match file_system.read_dir(&dir) {
+                Ok(children) => children,
+                Err(_) => continue,
+            };
+            children.sort();
+            let mut dirs = Vec::new();
+            for child in children {
+                let metadata = match file_system.metadata(&child) {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                };
+                let is_dir = metadata.item_type == ItemType::Directory;
+                if child.file_name().is_some_and(|name| name == ".git")
+                    || is_ignored(&ignores, &child, is_dir)
+                {
+                    continue;
+                }
+                match metadata.item_type {
+                    ItemType::Directory => dirs.push((child, ignores.clone())),
+                    ItemType::File => {
+                        if let Ok(file) = file_system.open_file(&child) {
+                            let _ = grep_file(file, &child, &regex, &mut found);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            // Matches come in the order of the paths.
+            pending.extend(dirs.into_iter().rev());
+            if !found.is_empty() && last_sent.elapsed() >= RESULTS_INTERVAL {
+                let matches = std::mem::take(&mut found);
+                if !send(GrepAction::Found {
+                    search: search.clone(),
+                    matches,
+                }) {
+                    return;
+                }
+                last_sent = Instant::now();
+            }
+        }
+        if !found.is_empty() {
+            send(GrepAction::Found {
+                search: search.clone(),
+                matches: found,
+            });
+        }
+        send(GrepAction::Finished { id: search.id });
+    });
+}
+
+/// Adds the lines of `file` matching `regex` to `found`. Files with a NUL
+/// byte in their first block are taken as binary and skipped.
+fn grep_file(
+    file: Box<dyn Read + Send>,
+    path: &Path,
+    regex: &Regex,
+    found: &mut Vec<GrepMatch>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(file);
+    if reader.fill_buf()?.contains(&0) {
+        return Ok(());
+    }
+    let mut buffer = Vec::new();
+    let mut line = 0;
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        line += 1;
+        let text = String::from_utf8_lossy(&buffer);
+        let text = text.trim_end_matches(['\n', '\r']);
+        if regex.is_match(text) {
+            found.push(GrepMatch {
+                path: path.to_path_buf(),
+                line,
+                text: text.trim().chars().take(MAX_LINE_LENGTH).collect(),
+            });
+        }
+        buffer.clear();
+    }
+    Ok(())
+}
+
+/// The patterns of one `.gitignore` file, which apply below `base`.
+#[derive(Debug)]
+struct IgnoreRules {
+    base: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+#[derive(Debug)]
+struct IgnoreRule {
+    regex: Regex,
+    negated: bool,
+    dir_only: bool,
+}
+
+impl IgnoreRules {
+    fn read<TFileSystem: FileSystem>(file_system: &TFileSystem, dir: &Path) -> Option<Self> {
+        let mut content = String::new();
+        file_system
+            .open_file(dir.join(".gitignore"))
+            .ok()?
+            .read_to_string(&mut content)
+            .ok()?;
+        let rules = content.lines().filter_map(IgnoreRule::parse).collect();
+        Some(IgnoreRules {
+            base: dir.to_path_buf(),
+            rules,
+        })
+    }
+
+    /// Whether the last pattern matching `path` ignores or keeps it, `None`
+    /// when no pattern matches.
+    fn check(&self, path: &Path, is_dir: bool) -> Option<bool> {
+        let relative = path.strip_prefix(&self.base).ok()?.to_string_lossy();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| (is_dir || !rule.dir_only) && rule.regex.is_match(&relative))
+            .map(|rule| !rule.negated)
+    }
+}
+
+impl IgnoreRule {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negated, pattern) = match line.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, line.strip_prefix('\\').unwrap_or(line)),
+        };
+        let (dir_only, pattern) = match pattern.strip_suffix('/') {
+            Some(pattern) => (true, pattern),
+            None => (false, pattern),
+        };
+        // Patterns with a slash are relative to the `.gitignore` file, the
+        // others match names at any depth.
+        let anchored = pattern.contains('/');
+        let pattern = pattern.trim_start_matches('/');
+        if pattern.is_empty() {
+            return None;
+        }
+        let prefix = if anchored { "^" } else { "^(?:.*/)?" };
+        let regex = Regex::new(&format!("{}{}$", prefix, ignore_glob_to_regex(pattern))).ok()?;
+        Some(IgnoreRule {
+            regex,
+            negated,
+            dir_only,
+        })
+    }
+}
+
+/// Converts a `.gitignore` glob, where only `**` crosses directories.
+fn ignore_glob_to_regex(glob: &str) -> String {
+    let mut regex = String::new();
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    regex.push_str("(?:.*/)?");
+                } else {
+                    regex.push_str(".*");
+                }
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            '\\' => {
+                if let Some(c) = chars.next() {
+                    regex.push_str(&regex::escape(&c.to_string()));
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex
+}
+
+/// Rules from the closest to the outermost `.gitignore` decide, the first one
+/// with a matching pattern wins.
+fn is_ignored(ignores: &[Arc<IgnoreRules>], path: &Path, is_dir: bool) -> bool {
+    ignores
+        .iter()
+        .rev()
+        .find_map(|rules| rules.check(path, is_dir))
+        .unwrap_or(false)
+}
+
+/// The `.gitignore` files above `root` within its git repository, when the
+/// search starts inside one.
+fn repository_ignores<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    root: &Path,
+) -> Vec<Arc<IgnoreRules>> {
+    if file_system.metadata(root.join(".git")).is_ok() {
+        return Vec::new();
+    }
+    let ancestors: Vec<&Path> = root.ancestors().skip(1).collect();
+    let repository = ancestors
+        .iter()
+        .position(|dir| file_system.metadata(dir.join(".git")).is_ok());
+    match repository {
+        Some(repository) => ancestors[..=repository]
+            .iter()
+            .rev()
+            .filter_map(|dir| IgnoreRules::read(file_system, dir))
+            .map(Arc::new)
+            .collect(),
+        None => Vec::new(),
+    }
+}
@@ -0,0 +1,668 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::Sender,
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{
+    actions::{FileManagerActions, JobAction},
+    file_system::{
+        copy::{copy_recursive, delete_except, verify_copy, CopyObserver, CopyOptions},
+        functions::first_free_path,
+        item_metadata::ItemMetadata,
+        FileSystem,
+    },
+    state::{AppError, FileOperation},
+};
+
+pub type JobId = usize;
+
+static NEXT_JOB_ID: AtomicUsize = AtomicUsize::new(0);
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobKind {
+    Copy,
+    Move,
+}
+
+impl Display for JobKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobKind::Copy => write!(f, "Copy"),
+            JobKind::Move => write!(f, "Move"),
+        }
+    }
+}
+
+/// How a job treats items which already exist at the destination. Existing
+/// directories are merged unless the whole directory is skipped or renamed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictResolution {
+    Overwrite,
+    OverwriteIfNewer,
+    Rename,
+    Skip,
+}
+
+impl Display for ConflictResolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictResolution::Overwrite => write!(f, "Overwrite"),
+            ConflictResolution::OverwriteIfNewer => write!(f, "Overwrite if newer"),
+            ConflictResolution::Rename => write!(f, "Rename"),
+            ConflictResolution::Skip => write!(f, "Skip"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    Running,
+    Paused,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct JobProgress {
+    pub total_bytes: u64,
+    pub total_files: u64,
+    pub done_bytes: u64,
+    pub done_files: u64,
+}
+
+impl JobProgress {
+    pub fn percent(&self) -> u16 {
+        let (done, total) = if self.total_bytes > 0 {
+            (self.done_bytes, self.total_bytes)
+        } else {
+            (self.done_files, self.total_files)
+        };
+        (done.min(total) * 100).checked_div(total).unwrap_or(0) as u16
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum JobOutcome {
+    /// `replaced_existing` is set when the target was there before the job
+    /// ran, so the job overwrote it, merged into it or left it alone.
+    /// `moved_across_devices` is set when a move had to copy the item.
+    Completed {
+        replaced_existing: bool,
+        moved_across_devices: bool,
+    },
+    Cancelled,
+    Failed(AppError),
+}
+
+/// Flags shared between the state and the worker thread of a job. The worker
+/// checks them between files.
+#[derive(Clone, Debug, Default)]
+pub struct JobControl {
+    paused: Arc<AtomicBool>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl JobControl {
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn wait_while_paused(&self) {
+        while self.paused.load(Ordering::Relaxed) && !self.is_cancelled() {
+            thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Job {
+    pub id: JobId,
+    pub kind: JobKind,
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub is_dir: bool,
+    pub on_conflict: Option<ConflictResolution>,
+    pub options: CopyOptions,
+    pub status: JobStatus,
+    pub progress: JobProgress,
+    pub control: JobControl,
+}
+
+impl Job {
+    pub fn new(
+        kind: JobKind,
+        from: PathBuf,
+        to: PathBuf,
+        is_dir: bool,
+        options: CopyOptions,
+    ) -> Self {
+        Job {
+            id: NEXT_JOB_ID.fetch_add(1, Ordering::Relaxed),
+            kind,
+            from,
+            to,
+            is_dir,
+            on_conflict: None,
+            options,
+            status: JobStatus::Running,
+            progress: JobProgress::default(),
+            control: JobControl::default(),
+        }
+    }
+
+    pub fn operation(&self) -> FileOperation {
+        match (self.kind, self.is_dir) {
+            (JobKind::Copy, false) => FileOperation::CopyFile,
+            (JobKind::Copy, true) => FileOperation::CopyDir,
+            (JobKind::Move, false) => FileOperation::RenameFile,
+            (JobKind::Move, true) => FileOperation::RenameDir,
+        }
+    }
+
+    /// Runs the job on a worker thread. Progress and the final outcome are
+    /// sent back to the store as `JobAction`s.
+    pub fn start<TFileSystem: Clone + FileSystem + Send + 'static>(
+        &self,
+        file_system: TFileSystem,
+        sender: Sender<FileManagerActions>,
+    ) {
+        let job = self.clone();
+        thread::spawn(move || {
+            let mut worker = JobWorker {
+                id: job.id,
+                operation: job.operation(),
+                on_conflict: job.on_conflict,
+                options: job.options,
+                file_system,
+                control: job.control.clone(),
+                sender,
+                progress: JobProgress::default(),
+                last_report: Instant::now(),
+                interrupt: None,
+                created: Vec::new(),
+                created_dirs: HashSet::new(),
+                moved_across_devices: false,
+            };
+            let replaced_existing = worker.file_system.exist(&job.to);
+            let outcome = match worker.run(job.kind, &job.from, &job.to) {
+                Ok(_) => JobOutcome::Completed {
+                    replaced_existing,
+                    moved_across_devices: worker.moved_across_devices,
+                },
+                Err(Interrupt::Cancelled) => JobOutcome::Cancelled,
+                Err(Interrupt::Failed(error)) => JobOutcome::Failed(error),
+            };
+            worker.report(true);
+            let _ = worker
+                .sender
+                .send(FileManagerActions::Job(JobAction::Finished {
+                    id: job.id,
+                    outcome,
+                }));
+        });
+    }
+}
+
+impl Display for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} -> {}",
+            self.kind,
+            self.from.to_string_lossy(),
+            self.to.to_string_lossy()
+        )
+    }
+}
+
+enum Interrupt {
+    Cancelled,
+    Failed(AppError),
+}
+
+struct JobWorker<TFileSystem: FileSystem> {
+    id: JobId,
+    operation: FileOperation,
+    on_conflict: Option<ConflictResolution>,
+    options: CopyOptions,
+    file_system: TFileSystem,
+    control: JobControl,
+    sender: Sender<FileManagerActions>,
+    progress: JobProgress,
+    last_report: Instant,
+    /// Why the observer stopped `copy_recursive`, which only sees an
+    /// `io::Error`.
+    interrupt: Option<Interrupt>,
+    /// Items the copy wrote to paths which were free, items inside them are
+    /// left out. A move which fails removes them again.
+    created: Vec<PathBuf>,
+    created_dirs: HashSet<PathBuf>,
+    moved_across_devices: bool,
+}
+
+impl<TFileSystem: Clone + FileSystem> JobWorker<TFileSystem> {
+    fn run(&mut self, kind: JobKind, from: &Path, to: &Path) -> Result<(), Interrupt> {
+        match kind {
+            JobKind::Copy => {
+                self.scan(from)?;
+                self.report(true);
+                let options = self.options;
+                self.copy_item(from, to, &options)
+            }
+            JobKind::Move => {
+                self.progress.total_files = 1;
+                self.move_item(from, to)?;
+                self.progress.done_files = self.progress.total_files;
+                self.progress.done_bytes = self.progress.total_bytes;
+                Ok(())
+            }
+        }
+    }
+
+    fn scan(&mut self, path: &Path) -> Result<(), Interrupt> {
+        self.checkpoint()?;
+        let metadata = self
+            .file_system
+            .metadata(path)
+            .map_err(|err| self.failed(path, path, err))?;
+        if metadata.is_dir() {
+            let children = self
+                .file_system
+                .read_dir(path)
+                .map_err(|err| self.failed(path, path, err))?;
+            for child in children {
+                self.scan(&child)?;
+            }
+        } else {
+            self.progress.total_files += 1;
+            self.progress.total_bytes += metadata.size;
+        }
+        Ok(())
+    }
+
+    fn copy_item(
+        &mut self,
+        from: &Path,
+        to: &Path,
+        options: &CopyOptions,
+    ) -> Result<(), Interrupt> {
+        let mut file_system = self.file_system.clone();
+        match copy_recursive(&mut file_system, from, to, options, self) {
+            Ok(_) => Ok(()),
+            Err(err) => Err(self
+                .interrupt
+                .take()
+                .unwrap_or_else(|| self.failed(from, to, err))),
+        }
+    }
+
+    fn move_item(&mut self, from: &Path, to: &Path) -> Result<(), Interrupt> {
+        self.checkpoint()?;
+        let metadata = self
+            .file_system
+            .metadata(from)
+            .map_err(|err| self.failed(from, to, err))?;
+        if metadata.is_dir() && self.merges_into_dir(&metadata, to) {
+            let children = self
+                .file_system
+                .read_dir(from)
+                .map_err(|err| self.failed(from, to, err))?;
+            for child in children {
+                if let Some(name) = child.file_name() {
+                    self.move_item(&child, &to.join(name))?;
+                }
+            }
+            // Skipped items stay in the source directory.
+            let is_empty = self
+                .file_system
+                .read_dir(from)
+                .map(|children| children.is_empty())
+                .unwrap_or(false);
+            if is_empty {
+                self.file_system
+                    .delete_empty_dir(from)
+                    .map_err(|err| self.failed(from, from, err))?;
+            }
+            return Ok(());
+        }
+
+        if let Some(to) = self.resolve_target(from, to, &metadata)? {
+            match self.file_system.rename_item(from, &to) {
+                Ok(_) => {}
+                Err(err) if err.kind() == io::ErrorKind::CrossesDevices => {
+                    self.move_across_devices(from, &to)?
+                }
+                Err(err) => return Err(self.failed(from, &to, err)),
+            }
+        }
+        Ok(())
+    }
+
+    /// Items can't be renamed between mount points, so they are copied, the
+    /// copy is compared with the source and only then the source is deleted.
+    /// Items the copy skips, like sockets, stay in the source and are named in
+    /// the returned error.
+    /// A failure before the delete leaves the source untouched and removes
+    /// what was copied so far.
+    fn move_across_devices(&mut self, from: &Path, to: &Path) -> Result<(), Interrupt> {
+        self.scan(from)?;
+        self.report(true);
+        // A move keeps symlinks as they are, whatever the copy settings say.
+        let options = CopyOptions {
+            dereference_symlinks: false,
+            ..self.options
+        };
+        self.created.clear();
+        self.created_dirs.clear();
+        let mut skipped = Vec::new();
+        let copied = match self.copy_item(from, to, &options) {
+            Ok(_) => {
+                verify_copy(&self.file_system, from, to, &options, &mut skipped).map_err(|err| {
+                    self.failed(
+                        from,
+                        to,
+                        io::Error::new(
+                            err.kind(),
+                            format!("the copy does not match the source ({})", err),
+                        ),
+                    )
+                })
+            }
+            Err(interrupt) => Err(interrupt),
+        };
+        if let Err(interrupt) = copied {
+            return Err(self.remove_partial_copy(from, to, interrupt));
+        }
+
+        delete_except(&mut self.file_system, from, &skipped).map_err(|err| {
+            self.failed(
+                from,
+                to,
+                io::Error::new(
+                    err.kind(),
+                    format!("copied, but the source could not be removed: {}", err),
+                ),
+            )
+        })?;
+
+        if skipped.is_empty() {
+            self.moved_across_devices = true;
+            return Ok(());
+        }
+        let skipped: Vec<String> = skipped
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        Err(self.failed(
+            from,
+            to,
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "moved, but these items can't be copied and stay in the source: {}",
+                    skipped.join(", ")
+                ),
+            ),
+        ))
+    }
+
+    /// Removes what a failed or cancelled move created at its target, items
+    /// which were there before are kept. Anything which could not be removed
+    /// is named in the returned error.
+    fn remove_partial_copy(&mut self, from: &Path, to: &Path, interrupt: Interrupt) -> Interrupt {
+        let created = std::mem::take(&mut self.created);
+        self.created_dirs.clear();
+        let left: Vec<String> = created
+            .into_iter()
+            .rev()
+            .filter(|path| {
+                let removed = match self.file_system.metadata(path) {
+                    Ok(metadata) if metadata.is_dir() => self.file_system.delete_dir(path),
+                    Ok(_) => self.file_system.delete_file(path),
+                    Err(_) => Ok(()),
+                };
+                removed.is_err()
+            })
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+
+        let note = if left.is_empty() {
+            String::from("the source is left untouched")
+        } else {
+            format!(
+                "the source is left untouched, the partial copy could not be removed: {}",
+                left.join(", ")
+            )
+        };
+        match interrupt {
+            Interrupt::Cancelled if left.is_empty() => Interrupt::Cancelled,
+            Interrupt::Cancelled => self.failed(
+                from,
+                to,
+                io::Error::new(io::ErrorKind::Interrupted, format!("cancelled, {}", note)),
+            ),
+            Interrupt::Failed(mut error) => {
+                error.message = format!("{}, {}", error.message, note);
+                Interrupt::Failed(error)
+            }
+        }
+    }
+
+    /// Whether the content of the `from` directory goes into an existing `to`
+    /// directory.
+    fn merges_into_dir(&self, metadata: &ItemMetadata, to: &Path) -> bool {
+        let target_is_dir = self
+            .file_system
+            .metadata(to)
+            .map(|target| target.is_dir())
+            .unwrap_or(false);
+        metadata.is_dir()
+            && target_is_dir
+            && matches!(
+                self.on_conflict,
+                Some(ConflictResolution::Overwrite) | Some(ConflictResolution::OverwriteIfNewer)
+            )
+    }
+
+    /// Returns where `from` should be written to, or `None` when it has to be
+    /// skipped because `to` already exists.
+    fn resolve_target(
+        &self,
+        from: &Path,
+        to: &Path,
+        metadata: &ItemMetadata,
+    ) -> Result<Option<PathBuf>, Interrupt> {
+        let target = match self.file_system.metadata(to) {
+            Ok(target) => target,
+            Err(_) => return Ok(Some(to.to_path_buf())),
+        };
+        match self.on_conflict {
+            None => Err(self.failed(
+                from,
+                to,
+                io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("{} already exists", to.display()),
+                ),
+            )),
+            Some(ConflictResolution::Overwrite) => Ok(Some(to.to_path_buf())),
+            Some(ConflictResolution::OverwriteIfNewer) if metadata.modified > target.modified => {
+                Ok(Some(to.to_path_buf()))
+            }
+            Some(ConflictResolution::OverwriteIfNewer) => Ok(None),
+            Some(ConflictResolution::Rename) => Ok(Some(first_free_path(&self.file_system, to))),
+            Some(ConflictResolution::Skip) => Ok(None),
+        }
+    }
+
+    fn checkpoint(&self) -> Result<(), Interrupt> {
+        self.control.wait_while_paused();
+        if self.control.is_cancelled() {
+            Err(Interrupt::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn failed(&self, from: &Path, to: &Path, err: io::Error) -> Interrupt {
+        let paths = if from == to {
+            vec![from.to_path_buf()]
+        } else {
+            vec![from.to_path_buf(), to.to_path_buf()]
+        };
+        Interrupt::Failed(AppError::new(self.operation, paths, &err))
+    }
+
+    fn report(&mut self, force: bool) {
+        if force || self.last_report.elapsed() >= PROGRESS_INTERVAL {
+            let _ = self
+                .sender
+                .send(FileManagerActions::Job(JobAction::Progress {
+                    id: self.id,
+                    progress: self.progress,
+                }));
+            self.last_report = Instant::now();
+        }
+    }
+}
+
+impl<TFileSystem: Clone + FileSystem> CopyObserver for JobWorker<TFileSystem> {
+    fn before_item(
+        &mut self,
+        from: &Path,
+        to: &Path,
+        metadata: &ItemMetadata,
+    ) -> io::Result<Option<PathBuf>> {
+        let target = self.checkpoint().and_then(|_| {
+            if self.merges_into_dir(metadata, to) {
+                Ok(Some(to.to_path_buf()))
+            } else {
+                self.resolve_target(from, to, metadata)
+            }
+        });
+        match target {
+            Ok(Some(to)) => {
+                if self.file_system.metadata(&to).is_err() {
+                    let in_created_dir =
+                        matches!(to.parent(), Some(parent) if self.created_dirs.contains(parent));
+                    if metadata.is_dir() {
+                        self.created_dirs.insert(to.clone());
+                    }
+                    if !in_created_dir {
+                        self.created.push(to.clone());
+                    }
+                }
+                Ok(Some(to))
+            }
+            Ok(None) => {
+                if !metadata.is_dir() {
+                    self.progress.done_files += 1;
+                    self.progress.done_bytes += metadata.size;
+                    self.report(false);
+                }
+                Ok(None)
+            }
+            Err(interrupt) => {
+                self.interrupt = Some(interrupt);
+                Err(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    "job interrupted",
+                ))
+            }
+        }
+    }
+
+    fn item_copied(&mut self, _from: &Path, metadata: &ItemMetadata, _bytes: u64) {
+        self.progress.done_files += 1;
+        self.progress.done_bytes += metadata.size;
+        self.report(false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, sync::mpsc};
+
+    use crate::app::{
+        actions::{FileManagerActions, JobAction},
+        file_system::{
+            copy::{CopyOptions, SpecialFiles},
+            in_memory::InMemoryFileSystem,
+            item_metadata::ItemType,
+            FileSystem,
+        },
+    };
+
+    use super::{Job, JobKind, JobOutcome};
+
+    fn run(job: Job, file_system: InMemoryFileSystem) -> JobOutcome {
+        let (sender, receiver) = mpsc::channel();
+        job.start(file_system, sender);
+        loop {
+            if let FileManagerActions::Job(JobAction::Finished { outcome, .. }) =
+                receiver.recv().unwrap()
+            {
+                return outcome;
+            }
+        }
+    }
+
+    #[test]
+    fn moves_across_devices_keep_skipped_items_in_the_source() {
+        let mut file_system = InMemoryFileSystem::default();
+        file_system.add_file("/home/dir/a.txt", b"a").unwrap();
+        file_system.add_file("/home/dir/sub/b.txt", b"b").unwrap();
+        let mut fifo = file_system.metadata("/home/dir/a.txt").unwrap();
+        fifo.item_type = ItemType::Fifo;
+        file_system
+            .create_special_file("/home/dir/sub/pipe", &fifo)
+            .unwrap();
+        file_system.add_mount_point("/mnt/usb").unwrap();
+        let job = Job::new(
+            JobKind::Move,
+            PathBuf::from("/home/dir"),
+            PathBuf::from("/mnt/usb/dir"),
+            true,
+            CopyOptions {
+                dereference_symlinks: false,
+                special_files: SpecialFiles::Skip,
+            },
+        );
+
+        match run(job, file_system.clone()) {
+            JobOutcome::Failed(error) => assert!(
+                error.message.contains("/home/dir/sub/pipe"),
+                "{}",
+                error.message
+            ),
+            outcome => panic!("{:?}", outcome),
+        }
+        assert!(file_system.exist("/mnt/usb/dir/a.txt"));
+        assert!(file_system.exist("/mnt/usb/dir/sub/b.txt"));
+        assert!(!file_system.exist("/home/dir/a.txt"));
+        assert!(!file_system.exist("/home/dir/sub/b.txt"));
+        assert!(file_system.exist("/home/dir/sub/pipe"));
+    }
+}
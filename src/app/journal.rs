@@ -0,0 +1,261 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use super::file_system::{
+    copy::move_by_copy,
+    trash::{move_to_trash, restore_from_trash, TrashEntry},
+    FileSystem,
+};
+
+/// File operation which can be reverted and applied again. Permanent
+/// deletions are not recorded as there is nothing left to bring back.
+#[derive(Clone, Debug)]
+pub enum JournalOperation {
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// A move between devices, which can't be renamed back either.
+    MoveAcrossDevices {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    CreateFile {
+        path: PathBuf,
+    },
+    CreateDir {
+        path: PathBuf,
+    },
+    CreateSymlink {
+        path: PathBuf,
+        target: PathBuf,
+    },
+    CopyFile {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    CopyDir {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    MoveToTrash {
+        entry: TrashEntry,
+    },
+    /// Operations done by one user action, undone and redone together.
+    Batch {
+        operations: Vec<JournalOperation>,
+    },
+}
+
+impl JournalOperation {
+    /// Paths touched by the operation, used to find tabs which need a reload.
+    pub fn paths(&self) -> Vec<PathBuf> {
+        match self {
+            JournalOperation::Rename { from, to } => vec![from.clone(), to.clone()],
+            JournalOperation::MoveAcrossDevices { from, to } => vec![from.clone(), to.clone()],
+            JournalOperation::CreateFile { path } => vec![path.clone()],
+            JournalOperation::CreateDir { path } => vec![path.clone()],
+            JournalOperation::CreateSymlink { path, .. } => vec![path.clone()],
+            JournalOperation::CopyFile { from, to } => vec![from.clone(), to.clone()],
+            JournalOperation::CopyDir { from, to } => vec![from.clone(), to.clone()],
+            JournalOperation::MoveToTrash { entry } => {
+                vec![entry.original_path.clone(), entry.trashed_path.clone()]
+            }
+            JournalOperation::Batch { operations } => operations
+                .iter()
+                .flat_map(|operation| operation.paths())
+                .collect(),
+        }
+    }
+
+    /// Reverts the operation and returns it in the form needed to apply it
+    /// again, e.g. with the trash entry updated after a restore.
+    pub fn undo<TFileSystem: FileSystem>(
+        self,
+        file_system: &mut TFileSystem,
+    ) -> io::Result<JournalOperation> {
+        match self {
+            JournalOperation::Rename { from, to } => {
+                rename_without_overwrite(file_system, &to, &from)?;
+                Ok(JournalOperation::Rename { from, to })
+            }
+            JournalOperation::MoveAcrossDevices { from, to } => {
+                move_by_copy(file_system, &to, &from)?;
+                Ok(JournalOperation::MoveAcrossDevices { from, to })
+            }
+            JournalOperation::CreateFile { path } => {
+                move_to_trash(file_system, &path)?;
+                Ok(JournalOperation::CreateFile { path })
+            }
+            JournalOperation::CreateDir { path } => {
+                move_to_trash(file_system, &path)?;
+                Ok(JournalOperation::CreateDir { path })
+            }
+            JournalOperation::CreateSymlink { path, target } => {
+                move_to_trash(file_system, &path)?;
+                Ok(JournalOperation::CreateSymlink { path, target })
+            }
+            JournalOperation::CopyFile { from, to } => {
+                move_to_trash(file_system, &to)?;
+                Ok(JournalOperation::CopyFile { from, to })
+            }
+            JournalOperation::CopyDir { from, to } => {
+                move_to_trash(file_system, &to)?;
+                Ok(JournalOperation::CopyDir { from, to })
+            }
+            JournalOperation::MoveToTrash { entry } => {
+                if file_system.exist(&entry.original_path) {
+                    return Err(already_exists(&entry.original_path));
+                }
+                restore_from_trash(file_system, &entry)?;
+                Ok(JournalOperation::MoveToTrash { entry })
+            }
+            JournalOperation::Batch { operations } => {
+                let mut undone = Vec::new();
+                for operation in operations.into_iter().rev() {
+                    match operation.undo(file_system) {
+                        Ok(operation) => undone.push(operation),
+                        Err(err) => {
+                            // Keeps the batch whole rather than half undone.
+                            for operation in undone.into_iter().rev() {
+                                let _ = operation.redo(file_system);
+                            }
+                            return Err(err);
+                        }
+                    }
+                }
+                undone.reverse();
+                Ok(JournalOperation::Batch { operations: undone })
+            }
+        }
+    }
+
+    /// Applies the operation again after it has been undone and returns it in
+    /// the form needed to undo it once more.
+    pub fn redo<TFileSystem: FileSystem>(
+        self,
+        file_system: &mut TFileSystem,
+    ) -> io::Result<JournalOperation> {
+        match self {
+            JournalOperation::Rename { from, to } => {
+                rename_without_overwrite(file_system, &from, &to)?;
+                Ok(JournalOperation::Rename { from, to })
+            }
+            JournalOperation::MoveAcrossDevices { from, to } => {
+                move_by_copy(file_system, &from, &to)?;
+                Ok(JournalOperation::MoveAcrossDevices { from, to })
+            }
+            JournalOperation::CreateFile { path } => {
+                create_if_missing(file_system, &path, |file_system, path| {
+                    file_system.create_file(path)
+                })?;
+                Ok(JournalOperation::CreateFile { path })
+            }
+            JournalOperation::CreateDir { path } => {
+                create_if_missing(file_system, &path, |file_system, path| {
+                    file_system.create_dir(path)
+                })?;
+                Ok(JournalOperation::CreateDir { path })
+            }
+            JournalOperation::CreateSymlink { path, target } => {
+                create_if_missing(file_system, &path, |file_system, path| {
+                    file_system.create_symlink(target.as_path(), path)
+                })?;
+                Ok(JournalOperation::CreateSymlink { path, target })
+            }
+            JournalOperation::CopyFile { from, to } => {
+                create_if_missing(file_system, &to, |file_system, to| {
+                    file_system.copy_file(from.as_path(), to).map(|_| ())
+                })?;
+                Ok(JournalOperation::CopyFile { from, to })
+            }
+            JournalOperation::CopyDir { from, to } => {
+                create_if_missing(file_system, &to, |file_system, to| {
+                    file_system.copy_dir(from.as_path(), to).map(|_| ())
+                })?;
+                Ok(JournalOperation::CopyDir { from, to })
+            }
+            JournalOperation::MoveToTrash { entry } => {
+                let entry = move_to_trash(file_system, &entry.original_path)?;
+                Ok(JournalOperation::MoveToTrash { entry })
+            }
+            JournalOperation::Batch { operations } => {
+                let mut redone = Vec::new();
+                for operation in operations {
+                    match operation.redo(file_system) {
+                        Ok(operation) => redone.push(operation),
+                        Err(err) => {
+                            for operation in redone.into_iter().rev() {
+                                let _ = operation.undo(file_system);
+                            }
+                            return Err(err);
+                        }
+                    }
+                }
+                Ok(JournalOperation::Batch { operations: redone })
+            }
+        }
+    }
+}
+
+/// Undo and redo history of the file operations done in the current session.
+#[derive(Clone, Debug, Default)]
+pub struct Journal {
+    undo_stack: Vec<JournalOperation>,
+    redo_stack: Vec<JournalOperation>,
+}
+
+impl Journal {
+    /// Records a new operation, which makes the undone ones impossible to redo.
+    pub fn record(&mut self, operation: JournalOperation) {
+        self.undo_stack.push(operation);
+        self.redo_stack.clear();
+    }
+
+    pub fn pop_undo(&mut self) -> Option<JournalOperation> {
+        self.undo_stack.pop()
+    }
+
+    pub fn pop_redo(&mut self) -> Option<JournalOperation> {
+        self.redo_stack.pop()
+    }
+
+    pub fn push_undo(&mut self, operation: JournalOperation) {
+        self.undo_stack.push(operation);
+    }
+
+    pub fn push_redo(&mut self, operation: JournalOperation) {
+        self.redo_stack.push(operation);
+    }
+}
+
+fn rename_without_overwrite<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    from: &Path,
+    to: &Path,
+) -> io::Result<()> {
+    if file_system.exist(to) {
+        return Err(already_exists(to));
+    }
+    file_system.rename_item(from, to)
+}
+
+fn create_if_missing<TFileSystem: FileSystem>(
+    file_system: &mut TFileSystem,
+    path: &Path,
+    create: impl FnOnce(&mut TFileSystem, &Path) -> io::Result<()>,
+) -> io::Result<()> {
+    if file_system.exist(path) {
+        return Err(already_exists(path));
+    }
+    create(file_system, path)
+}
+
+fn already_exists(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::AlreadyExists,
+        format!("{} already exists", path.display()),
+    )
+}
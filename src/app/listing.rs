@@ -0,0 +1,68 @@
+use std::{path::PathBuf, sync::mpsc::Sender, thread, time::Instant};
+
+use super::{
+    actions::{FileManagerActions, ListingAction},
+    config::icon_cfg::IconsConfig,
+    file_system::{
+        file_system_item::FileSystemItem, functions::map_path_to_file_system_item, FileSystem,
+    },
+    find::{FindSearch, RESULTS_INTERVAL},
+};
+
+/// Directories with more items than this are listed on a worker thread, the
+/// smaller ones at once.
+pub const SYNC_LISTING_LIMIT: usize = 2000;
+
+/// Items of a directory too big to be listed at once, streamed into its tab.
+#[derive(Clone, Debug, Default)]
+pub struct DirListing {
+    /// Set once the worker thread started.
+    pub search: Option<FindSearch>,
+    /// Items read again for a tab which already shows the directory, they
+    /// replace the old ones when all of them are read.
+    pub reloaded: Option<Vec<FileSystemItem>>,
+}
+
+/// Reads the metadata of the items of `path` on a worker thread and sends
+/// them in batches.
+pub fn list_dir_items<TFileSystem: Clone + FileSystem + Send + 'static>(
+    file_system: TFileSystem,
+    path: PathBuf,
+    icons: IconsConfig,
+    search: FindSearch,
+    sender: Sender<FileManagerActions>,
+) {
+    thread::spawn(move || {
+        let send = |action: ListingAction| sender.send(FileManagerActions::Listing(action)).is_ok();
+        let paths = file_system.read_dir(&path).unwrap_or_default();
+        let mut listed = Vec::new();
+        let mut last_sent = Instant::now();
+        for item_path in paths {
+            if search.is_cancelled() {
+                return;
+            }
+            listed.push(map_path_to_file_system_item(
+                &file_system,
+                item_path,
+                &icons,
+            ));
+            if last_sent.elapsed() >= RESULTS_INTERVAL {
+                let items = std::mem::take(&mut listed);
+                if !send(ListingAction::Listed {
+                    search: search.clone(),
+                    items,
+                }) {
+                    return;
+                }
+                last_sent = Instant::now();
+            }
+        }
+        if !listed.is_empty() {
+            send(ListingAction::Listed {
+                search: search.clone(),
+                items: listed,
+            });
+        }
+        send(ListingAction::Finished { id: search.id });
+    });
+}
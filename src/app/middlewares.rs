@@ -1,12 +1,24 @@
 use super::{
     actions::{
-        AppAction, DirectoryAction, FileAction, FileManagerActions, PanelInfo, SymlinkAction,
+        AppAction, CompareAction, DirSizeAction, DirectoryAction, FileAction, FileManagerActions,
+        FindAction, GrepAction, JobAction, ListingAction, PanelInfo, SymlinkAction, SyncAction,
     },
-    file_system::FileSystem,
+    compare::{compare_contents, content_pairs},
+    dir_size::scan_dir_sizes,
+    file_system::{
+        copy::{CopyOptions, SpecialFiles},
+        functions::first_free_path,
+        FileSystem,
+    },
+    find::{find_files, FindSearch},
+    grep::grep_files,
+    jobs::{ConflictResolution, Job, JobKind},
+    listing::list_dir_items,
     state::{AppState, ModalType},
+    sync::{compare_trees, SyncOperation},
 };
 use crate::core::store::Store;
-use std::{fmt::Debug, fs};
+use std::{fmt::Debug, path::PathBuf, time::SystemTime};
 
 pub fn symlink_middleware<TFileSystem: Clone + Debug + Default + FileSystem>(
     store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
@@ -19,13 +31,18 @@ pub fn symlink_middleware<TFileSystem: Clone + Debug + Default + FileSystem>(
 }
 
 fn symlink_resolver<TFileSystem: Clone + Debug + Default + FileSystem>(
-    _: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
     symlink_action: SymlinkAction,
 ) -> Option<FileManagerActions> {
+    let file_system = store.get_state().file_system;
     match symlink_action {
-        SymlinkAction::Open { panel, in_new_tab } => match fs::read_link(panel.path.as_path()) {
+        SymlinkAction::Open { panel, in_new_tab } => match file_system.canonicalize(&panel.path) {
             Ok(link_path) => {
-                if link_path.is_dir() {
+                let is_dir = file_system
+                    .metadata(&link_path)
+                    .map(|metadata| metadata.is_dir())
+                    .unwrap_or(false);
+                if is_dir {
                     Some(FileManagerActions::Directory(DirectoryAction::Open {
                         panel: PanelInfo {
                             path: link_path,
@@ -44,9 +61,9 @@ fn symlink_resolver<TFileSystem: Clone + Debug + Default + FileSystem>(
                     }))
                 }
             }
-            Err(err) => Some(FileManagerActions::App(AppAction::ShowModal(
+            Err(err) => Some(FileManagerActions::App(AppAction::ShowModal(Box::new(
                 ModalType::MessageboxModal(format!("{}", err)),
-            ))),
+            )))),
         },
         _ => Some(FileManagerActions::Symlink(symlink_action)),
     }
@@ -74,15 +91,316 @@ fn directory_resolver<TFileSystem: Clone + Debug + Default + FileSystem>(
                     is_empty,
                 }))
             } else {
-                Some(FileManagerActions::App(AppAction::ShowModal(
+                Some(FileManagerActions::App(AppAction::ShowModal(Box::new(
                     ModalType::DeleteDirWithContent {
                         panel_side: panel.side,
                         panel_tab: panel.tab,
                         path: panel.path,
+                        permanent: false,
                     },
-                )))
+                ))))
+            }
+        }
+        DirectoryAction::DeletePermanently { panel, is_empty } => {
+            if is_empty {
+                Some(FileManagerActions::Directory(
+                    DirectoryAction::DeletePermanently { panel, is_empty },
+                ))
+            } else {
+                Some(FileManagerActions::App(AppAction::ShowModal(Box::new(
+                    ModalType::DeleteDirWithContent {
+                        panel_side: panel.side,
+                        panel_tab: panel.tab,
+                        path: panel.path,
+                        permanent: true,
+                    },
+                ))))
             }
         }
         _ => Some(FileManagerActions::Directory(dir_action)),
     }
 }
+
+/// Runs copy and move operations as background jobs so the reducers do not
+/// block the UI while a large tree is transferred. Jobs whose target already
+/// exists wait in the conflict queue until the user picks a resolution.
+pub fn job_middleware<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static>(
+    store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    action: FileManagerActions,
+) -> Option<FileManagerActions> {
+    let (kind, from, to, is_dir) = match &action {
+        FileManagerActions::File(FileAction::Copy { from, to }) => {
+            (JobKind::Copy, from.path.clone(), to.path.clone(), false)
+        }
+        FileManagerActions::File(FileAction::Move { from, to }) => {
+            (JobKind::Move, from.path.clone(), to.path.clone(), false)
+        }
+        FileManagerActions::Directory(DirectoryAction::Copy { from, to }) => {
+            (JobKind::Copy, from.path.clone(), to.path.clone(), true)
+        }
+        FileManagerActions::Directory(DirectoryAction::Move { from, to }) => {
+            (JobKind::Move, from.path.clone(), to.path.clone(), true)
+        }
+        FileManagerActions::Job(JobAction::ResolveConflict {
+            resolution,
+            apply_to_all,
+        }) => return resolve_conflicts(store, *resolution, *apply_to_all),
+        _ => return Some(action),
+    };
+
+    let state = store.get_state();
+    let job = Job::new(kind, from, to, is_dir, copy_options(&state));
+
+    if state.file_system.exist(&job.to) {
+        return Some(FileManagerActions::Job(JobAction::Conflict { job }));
+    }
+
+    job.start(state.file_system, store.action_sender());
+    Some(FileManagerActions::Job(JobAction::Start { job }))
+}
+
+fn copy_options<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: &AppState<TFileSystem>,
+) -> CopyOptions {
+    CopyOptions {
+        dereference_symlinks: state.config.core_cfg.dereference_symlinks,
+        special_files: if state.config.core_cfg.copy_special_files {
+            SpecialFiles::Recreate
+        } else {
+            SpecialFiles::Skip
+        },
+    }
+}
+
+fn resolve_conflicts<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static>(
+    store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    resolution: ConflictResolution,
+    apply_to_all: bool,
+) -> Option<FileManagerActions> {
+    let state = store.get_state();
+    let count = if apply_to_all {
+        state.conflicts.len()
+    } else {
+        state.conflicts.len().min(1)
+    };
+    let jobs: Vec<Job> = state.conflicts.into_iter().take(count).collect();
+    store.dispatch(FileManagerActions::Job(JobAction::ConflictsResolved {
+        ids: jobs.iter().map(|job| job.id).collect(),
+    }));
+
+    for mut job in jobs {
+        match resolution {
+            ConflictResolution::Skip => continue,
+            ConflictResolution::Rename => {
+                job.to = first_free_path(&state.file_system, &job.to);
+            }
+            // Overwriting an item with itself would destroy it.
+            _ if job.from == job.to => continue,
+            resolution => job.on_conflict = Some(resolution),
+        }
+        job.start(store.get_state().file_system, store.action_sender());
+        store.dispatch(FileManagerActions::Job(JobAction::Start { job }));
+    }
+
+    None
+}
+
+/// Starts background scans of directory sizes. Directories which are already
+/// being scanned are left out, and so are cached ones unless `force` is set.
+pub fn dir_size_middleware<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static>(
+    store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    action: FileManagerActions,
+) -> Option<FileManagerActions> {
+    let (paths, force) = match action {
+        FileManagerActions::DirSize(DirSizeAction::Scan { paths, force }) => (paths, force),
+        _ => return Some(action),
+    };
+
+    let state = store.get_state();
+    let paths: Vec<PathBuf> = paths
+        .into_iter()
+        .filter(|path| !state.dir_sizes.is_scanning(path))
+        .filter(|path| {
+            force
+                || state
+                    .file_system
+                    .metadata(path)
+                    .map(|metadata| state.dir_sizes.get(path, metadata.modified).is_none())
+                    .unwrap_or(false)
+        })
+        .collect();
+    if paths.is_empty() {
+        return None;
+    }
+
+    scan_dir_sizes(state.file_system, paths.clone(), store.action_sender());
+    Some(FileManagerActions::DirSize(DirSizeAction::Started {
+        paths,
+    }))
+}
+
+/// Starts hashing the files a comparison by content has to tell apart.
+pub fn compare_middleware<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static>(
+    store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    action: FileManagerActions,
+) -> Option<FileManagerActions> {
+    if let FileManagerActions::Compare(CompareAction::Start { by_content: true }) = action {
+        let state = store.get_state();
+        let pairs = content_pairs(
+            &state.left_panel.tabs[state.left_panel.current_tab].items,
+            &state.right_panel.tabs[state.right_panel.current_tab].items,
+        );
+        if !pairs.is_empty() {
+            compare_contents(state.file_system, pairs, store.action_sender());
+        }
+    }
+    Some(action)
+}
+
+/// Compares the trees to synchronize in the background, and starts the copies
+/// of the confirmed plan as jobs which overwrite the older items.
+pub fn sync_middleware<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static>(
+    store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    action: FileManagerActions,
+) -> Option<FileManagerActions> {
+    match &action {
+        FileManagerActions::Sync(SyncAction::Compare {
+            left_root,
+            right_root,
+        }) => {
+            let state = store.get_state();
+            compare_trees(
+                state.file_system,
+                left_root.clone(),
+                right_root.clone(),
+                store.action_sender(),
+            );
+        }
+        FileManagerActions::Sync(SyncAction::Run) => {
+            let state = store.get_state();
+            let plan = match state.sync.as_ref() {
+                Some(plan) => plan,
+                None => return Some(action),
+            };
+            for entry in plan.entries.iter().flatten() {
+                let (from, to) = match entry.operation {
+                    SyncOperation::CopyToRight => (plan.left_path(entry), plan.right_path(entry)),
+                    SyncOperation::CopyToLeft => (plan.right_path(entry), plan.left_path(entry)),
+                    _ => continue,
+                };
+                let mut job = Job::new(
+                    JobKind::Copy,
+                    from,
+                    to,
+                    entry.is_dir(),
+                    copy_options(&state),
+                );
+                job.on_conflict = Some(ConflictResolution::Overwrite);
+                job.start(state.file_system.clone(), store.action_sender());
+                store.dispatch(FileManagerActions::Job(JobAction::Start { job }));
+            }
+        }
+        _ => {}
+    }
+    Some(action)
+}
+
+/// Starts searching the tree in the background, the results are streamed
+/// into a tab opened by the reducer.
+pub fn find_middleware<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static>(
+    store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    action: FileManagerActions,
+) -> Option<FileManagerActions> {
+    let (panel_side, root, query) = match action {
+        FileManagerActions::Find(FindAction::Start {
+            panel_side,
+            root,
+            query,
+        }) => (panel_side, root, query),
+        _ => return Some(action),
+    };
+
+    let criteria = match query.criteria(SystemTime::now()) {
+        Ok(criteria) => criteria,
+        Err(err) => {
+            return Some(FileManagerActions::App(AppAction::ShowModal(Box::new(
+                ModalType::MessageboxModal(err),
+            ))))
+        }
+    };
+    let search = FindSearch::new();
+    find_files(
+        store.get_state().file_system,
+        root.clone(),
+        criteria,
+        search.clone(),
+        store.action_sender(),
+    );
+    Some(FileManagerActions::Find(FindAction::Started {
+        panel_side,
+        root,
+        query,
+        search,
+    }))
+}
+
+pub fn grep_middleware<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static>(
+    store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    action: FileManagerActions,
+) -> Option<FileManagerActions> {
+    let (root, query) = match action {
+        FileManagerActions::Grep(GrepAction::Start { root, query }) => (root, query),
+        _ => return Some(action),
+    };
+
+    let regex = match query.regex() {
+        Ok(regex) => regex,
+        Err(err) => {
+            return Some(FileManagerActions::App(AppAction::ShowModal(Box::new(
+                ModalType::MessageboxModal(err),
+            ))))
+        }
+    };
+    let search = FindSearch::new();
+    grep_files(
+        store.get_state().file_system,
+        root.clone(),
+        regex,
+        search.clone(),
+        store.action_sender(),
+    );
+    Some(FileManagerActions::Grep(GrepAction::Started {
+        root,
+        query,
+        search,
+    }))
+}
+
+/// Lists the items of a big directory on a worker thread, they are streamed
+/// into the tab in batches.
+pub fn listing_middleware<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static>(
+    store: &mut Store<AppState<TFileSystem>, FileManagerActions>,
+    action: FileManagerActions,
+) -> Option<FileManagerActions> {
+    let (panel_side, path) = match action {
+        FileManagerActions::Listing(ListingAction::Start { panel_side, path }) => {
+            (panel_side, path)
+        }
+        _ => return Some(action),
+    };
+
+    let state = store.get_state();
+    let search = FindSearch::new();
+    list_dir_items(
+        state.file_system,
+        path.clone(),
+        state.config.icons,
+        search.clone(),
+        store.action_sender(),
+    );
+    Some(FileManagerActions::Listing(ListingAction::Started {
+        panel_side,
+        path,
+        search,
+    }))
+}
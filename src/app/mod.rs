@@ -1,7 +1,20 @@
 pub mod actions;
+pub mod bulk_rename;
+pub mod columns;
+pub mod compare;
 pub mod components;
 pub mod config;
+pub mod dir_size;
 pub mod file_system;
+pub mod filter;
+pub mod find;
+pub mod grep;
+pub mod jobs;
+pub mod journal;
+pub mod listing;
 pub mod middlewares;
+pub mod multi_rename;
 pub mod reducers;
 pub mod state;
+pub mod sync;
+pub mod watcher;
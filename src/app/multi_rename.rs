@@ -0,0 +1,239 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+
+use super::{bulk_rename::is_valid_name, file_system::FileSystem};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaseConversion {
+    Unchanged,
+    Lower,
+    Upper,
+    Capitalize,
+}
+
+impl CaseConversion {
+    pub fn next(self) -> Self {
+        match self {
+            CaseConversion::Unchanged => CaseConversion::Lower,
+            CaseConversion::Lower => CaseConversion::Upper,
+            CaseConversion::Upper => CaseConversion::Capitalize,
+            CaseConversion::Capitalize => CaseConversion::Unchanged,
+        }
+    }
+
+    pub fn prev(self) -> Self {
+        match self {
+            CaseConversion::Unchanged => CaseConversion::Capitalize,
+            CaseConversion::Lower => CaseConversion::Unchanged,
+            CaseConversion::Upper => CaseConversion::Lower,
+            CaseConversion::Capitalize => CaseConversion::Upper,
+        }
+    }
+
+    fn apply(self, name: &str) -> String {
+        match self {
+            CaseConversion::Unchanged => name.to_string(),
+            CaseConversion::Lower => name.to_lowercase(),
+            CaseConversion::Upper => name.to_uppercase(),
+            CaseConversion::Capitalize => {
+                let mut result = String::with_capacity(name.len());
+                let mut word_start = true;
+                for c in name.chars() {
+                    if word_start {
+                        result.extend(c.to_uppercase());
+                    } else {
+                        result.extend(c.to_lowercase());
+                    }
+                    word_start = !c.is_alphanumeric();
+                }
+                result
+            }
+        }
+    }
+}
+
+impl Display for CaseConversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            CaseConversion::Unchanged => "unchanged",
+            CaseConversion::Lower => "lower case",
+            CaseConversion::Upper => "UPPER CASE",
+            CaseConversion::Capitalize => "Capitalize Words",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+/// How the multi-rename tool builds new names. The masks are applied first,
+/// then search and replace runs on the whole name and the case conversion
+/// comes last.
+///
+/// Masks understand `[N]` for the name without extension, `[E]` for the
+/// extension and `[C]` for a counter over the items starting at 1, which
+/// `[C:3]` pads with zeros to three digits. The extension is joined to the
+/// name with a dot unless it comes out empty.
+#[derive(Clone, Debug)]
+pub struct RenamePattern {
+    pub name_mask: String,
+    pub extension_mask: String,
+    /// Regex, the replacement can refer to its groups as `$1` or `${name}`.
+    pub search: String,
+    pub replace: String,
+    pub case: CaseConversion,
+}
+
+impl Default for RenamePattern {
+    fn default() -> Self {
+        RenamePattern {
+            name_mask: String::from("[N]"),
+            extension_mask: String::from("[E]"),
+            search: String::new(),
+            replace: String::new(),
+            case: CaseConversion::Unchanged,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PreviewEntry {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    /// Why the item can't get its new name.
+    pub problem: Option<String>,
+}
+
+impl PreviewEntry {
+    pub fn is_renamed(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+impl RenamePattern {
+    /// New names of `paths` with whatever would stop them from being
+    /// applied, or an error when the search regex is invalid.
+    pub fn preview<TFileSystem: FileSystem>(
+        &self,
+        file_system: &TFileSystem,
+        paths: &[PathBuf],
+    ) -> Result<Vec<PreviewEntry>, regex::Error> {
+        let search = if self.search.is_empty() {
+            None
+        } else {
+            Some(Regex::new(&self.search)?)
+        };
+
+        let mut entries: Vec<PreviewEntry> = paths
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| {
+                let name = self.new_name(path, idx + 1, search.as_ref());
+                PreviewEntry {
+                    from: path.clone(),
+                    to: path.with_file_name(&name),
+                    problem: if is_valid_name(&name) {
+                        None
+                    } else {
+                        Some(String::from("invalid name"))
+                    },
+                }
+            })
+            .collect();
+
+        let mut targets: HashMap<PathBuf, usize> = HashMap::new();
+        for entry in entries.iter() {
+            *targets.entry(entry.to.clone()).or_insert(0) += 1;
+        }
+        for idx in 0..entries.len() {
+            if entries[idx].problem.is_some() || !entries[idx].is_renamed() {
+                continue;
+            }
+            let to = &entries[idx].to;
+            let freed = entries
+                .iter()
+                .any(|other| other.is_renamed() && &other.from == to);
+            entries[idx].problem = if targets[to] > 1 {
+                Some(String::from("same name as another item"))
+            } else if !freed && file_system.exist(to) {
+                Some(String::from("already exists"))
+            } else {
+                None
+            };
+        }
+
+        Ok(entries)
+    }
+
+    fn new_name(&self, path: &Path, counter: usize, search: Option<&Regex>) -> String {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let name = expand_mask(&self.name_mask, &stem, &extension, counter);
+        let extension = expand_mask(&self.extension_mask, &stem, &extension, counter);
+        let mut new_name = if extension.is_empty() {
+            name
+        } else {
+            format!("{}.{}", name, extension)
+        };
+        if let Some(search) = search {
+            new_name = search
+                .replace_all(&new_name, self.replace.as_str())
+                .to_string();
+        }
+        self.case.apply(&new_name)
+    }
+}
+
+/// Renames of a preview, or `None` when any of them has a problem.
+pub fn preview_renames(entries: &[PreviewEntry]) -> Option<Vec<(PathBuf, PathBuf)>> {
+    if entries.iter().any(|entry| entry.problem.is_some()) {
+        return None;
+    }
+    Some(
+        entries
+            .iter()
+            .filter(|entry| entry.is_renamed())
+            .map(|entry| (entry.from.clone(), entry.to.clone()))
+            .collect(),
+    )
+}
+
+fn expand_mask(mask: &str, stem: &str, extension: &str, counter: usize) -> String {
+    let mut result = String::new();
+    let mut rest = mask;
+    while let Some(start) = rest.find('[') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = match rest.find(']') {
+            Some(end) => end,
+            None => break,
+        };
+        let placeholder = &rest[1..end];
+        match placeholder {
+            "N" => result.push_str(stem),
+            "E" => result.push_str(extension),
+            "C" => result.push_str(&counter.to_string()),
+            _ => match placeholder
+                .strip_prefix("C:")
+                .and_then(|width| width.parse::<usize>().ok())
+            {
+                Some(width) => result.push_str(&format!("{:0width$}", counter, width = width)),
+                // Anything else is kept as it is.
+                None => result.push_str(&rest[..=end]),
+            },
+        }
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+    result
+}
@@ -0,0 +1,123 @@
+use std::fmt::Debug;
+
+use crate::app::{
+    actions::{CompareAction, PanelSide},
+    compare::{Comparison, ContentCheck, Difference},
+    file_system::FileSystem,
+    state::{AppState, PanelState},
+};
+
+pub fn compare_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    compare_action: CompareAction,
+) -> AppState<TFileSystem> {
+    match compare_action {
+        CompareAction::Start { by_content } => start_comparison(state, by_content),
+        CompareAction::Stop => AppState {
+            comparison: None,
+            ..state
+        },
+        CompareAction::ContentCompared {
+            left,
+            right,
+            left_modified,
+            right_modified,
+            equal,
+        } => {
+            let mut state = state;
+            if let Some(comparison) = state.comparison.as_mut() {
+                comparison.content_checks.insert(
+                    left,
+                    ContentCheck {
+                        right,
+                        left_modified,
+                        right_modified,
+                        equal,
+                    },
+                );
+            }
+            state
+        }
+        CompareAction::SelectDifferent { panel_side } => match panel_side {
+            PanelSide::Left => AppState {
+                left_panel: select_different(state.left_panel),
+                ..state
+            },
+            PanelSide::Right => AppState {
+                right_panel: select_different(state.right_panel),
+                ..state
+            },
+        },
+    }
+}
+
+fn start_comparison<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    by_content: bool,
+) -> AppState<TFileSystem> {
+    let left_path = state.left_panel.tabs[state.left_panel.current_tab]
+        .path
+        .clone();
+    let right_path = state.right_panel.tabs[state.right_panel.current_tab]
+        .path
+        .clone();
+    AppState {
+        comparison: Some(Comparison::new(by_content, left_path, right_path)),
+        ..state
+    }
+}
+
+/// Selects the items which are missing or older on the other side, so they
+/// can be copied over.
+fn select_different<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut panel: PanelState<TFileSystem>,
+) -> PanelState<TFileSystem> {
+    let tab = &mut panel.tabs[panel.current_tab];
+    tab.selected = tab
+        .items
+        .iter()
+        .filter(|item| {
+            matches!(
+                tab.differences.get(&item.get_path()),
+                Some(Difference::Unique) | Some(Difference::Newer)
+            )
+        })
+        .cloned()
+        .collect();
+    panel
+}
+
+/// Marks the differences between the current tabs of both panels while they
+/// are compared, and ends the comparison once either of them shows another
+/// directory.
+pub fn update_differences<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+) -> AppState<TFileSystem> {
+    let left_tab = state.left_panel.current_tab;
+    let right_tab = state.right_panel.current_tab;
+    let still_compared = state.comparison.as_ref().is_some_and(|comparison| {
+        state.left_panel.tabs[left_tab].path == comparison.left_path
+            && state.right_panel.tabs[right_tab].path == comparison.right_path
+    });
+    if !still_compared {
+        state.comparison = None;
+    }
+
+    for tab in state
+        .left_panel
+        .tabs
+        .iter_mut()
+        .chain(state.right_panel.tabs.iter_mut())
+    {
+        tab.differences.clear();
+    }
+    if let Some(comparison) = state.comparison.as_ref() {
+        let (left, right) = comparison.differences(
+            &state.left_panel.tabs[left_tab].items,
+            &state.right_panel.tabs[right_tab].items,
+        );
+        state.left_panel.tabs[left_tab].differences = left;
+        state.right_panel.tabs[right_tab].differences = right;
+    }
+    state
+}
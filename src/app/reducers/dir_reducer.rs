@@ -2,87 +2,41 @@ use crate::app::{
     actions::{DirectoryAction, PanelInfo, PanelSide},
     config::icon_cfg::IconsConfig,
     config::Config,
-    file_system::FileSystem,
-    state::{AppState, PanelState, TabIdx, TabState},
+    file_system::{trash::move_to_trash, FileSystem},
+    journal::JournalOperation,
+    state::{AppError, AppState, FileOperation, PanelState, TabIdx, TabState},
 };
 use std::fmt::Debug;
 use std::path::PathBuf;
 
-use super::{reload_tab, reload_tab_contain_item, reload_tab_with_path};
+use super::{reload_tab, reload_tab_contain_item, reload_tab_with_path, TabContext};
 
 pub fn dir_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
     state: AppState<TFileSystem>,
     dir_action: DirectoryAction,
 ) -> AppState<TFileSystem> {
     match dir_action {
-        DirectoryAction::Delete { panel, .. } => delete_dir(state, panel),
+        DirectoryAction::Delete { panel, .. } => delete_dir(state, panel, false),
+        DirectoryAction::DeletePermanently { panel, .. } => delete_dir(state, panel, true),
         DirectoryAction::Rename { from, to } => rename_dir(state, from, to),
-        DirectoryAction::Move { from, to } => rename_dir(state, from, to),
         DirectoryAction::Open { panel, in_new_tab } => open_dir(state, panel, in_new_tab),
         DirectoryAction::Create { dir_name, panel } => create_directory(state, dir_name, panel),
-        DirectoryAction::DeleteWithContent { panel } => delete_dir_with_content(state, panel),
-        DirectoryAction::Copy { from, to } => copy_dir(state, from, to),
-    }
-}
-
-fn copy_dir<TFileSystem: Clone + Debug + Default + FileSystem>(
-    mut state: AppState<TFileSystem>,
-    from: PanelInfo,
-    to: PanelInfo,
-) -> AppState<TFileSystem> {
-    match to.side {
-        PanelSide::Left => AppState {
-            left_panel: PanelState {
-                tabs: copy_dir_to_tab(
-                    from.path,
-                    to.path,
-                    to.tab,
-                    state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
-                ),
-                ..state.left_panel
-            },
-            right_panel: PanelState {
-                tabs: reload_tab(
-                    from.tab,
-                    state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
-                ),
-                ..state.right_panel
-            },
-            ..state
-        },
-        PanelSide::Right => AppState {
-            right_panel: PanelState {
-                tabs: copy_dir_to_tab(
-                    from.path,
-                    to.path,
-                    to.tab,
-                    state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
-                ),
-                ..state.right_panel
-            },
-            left_panel: PanelState {
-                tabs: reload_tab(
-                    from.tab,
-                    state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
-                ),
-                ..state.left_panel
-            },
-            ..state
-        },
+        DirectoryAction::DeleteWithContent { panel } => {
+            delete_dir_with_content(state, panel, false)
+        }
+        DirectoryAction::DeleteWithContentPermanently { panel } => {
+            delete_dir_with_content(state, panel, true)
+        }
+        // Copies and moves run as jobs, `job_middleware` takes these actions
+        // before they get here.
+        DirectoryAction::Copy { .. } | DirectoryAction::Move { .. } => state,
     }
 }
 
 fn delete_dir_with_content<TFileSystem: Clone + Debug + Default + FileSystem>(
     mut state: AppState<TFileSystem>,
     panel: PanelInfo,
+    permanent: bool,
 ) -> AppState<TFileSystem> {
     match panel.side {
         PanelSide::Left => AppState {
@@ -90,9 +44,14 @@ fn delete_dir_with_content<TFileSystem: Clone + Debug + Default + FileSystem>(
                 tabs: delete_dir_with_content_from_tab(
                     panel.path.clone(),
                     panel.tab,
+                    permanent,
                     state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.left_panel
             },
@@ -112,9 +71,14 @@ fn delete_dir_with_content<TFileSystem: Clone + Debug + Default + FileSystem>(
                 tabs: delete_dir_with_content_from_tab(
                     panel.path.clone(),
                     panel.tab,
+                    permanent,
                     state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.right_panel
             },
@@ -145,8 +109,12 @@ fn create_directory<TFileSystem: Clone + Debug + Default + FileSystem>(
                     panel.path.clone(),
                     panel.tab,
                     state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.left_panel
             },
@@ -168,8 +136,12 @@ fn create_directory<TFileSystem: Clone + Debug + Default + FileSystem>(
                     panel.path.clone(),
                     panel.tab,
                     state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.right_panel
             },
@@ -257,8 +229,12 @@ fn rename_dir<TFileSystem: Clone + Debug + Default + FileSystem>(
                     to.path,
                     to.tab,
                     state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.left_panel
             },
@@ -280,8 +256,12 @@ fn rename_dir<TFileSystem: Clone + Debug + Default + FileSystem>(
                     to.path,
                     to.tab,
                     state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.right_panel
             },
@@ -302,6 +282,7 @@ fn rename_dir<TFileSystem: Clone + Debug + Default + FileSystem>(
 fn delete_dir<TFileSystem: Clone + Debug + Default + FileSystem>(
     mut state: AppState<TFileSystem>,
     panel: PanelInfo,
+    permanent: bool,
 ) -> AppState<TFileSystem> {
     match panel.side {
         PanelSide::Left => AppState {
@@ -309,9 +290,14 @@ fn delete_dir<TFileSystem: Clone + Debug + Default + FileSystem>(
                 tabs: delete_dir_from_tab(
                     panel.path.clone(),
                     panel.tab,
+                    permanent,
                     state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.left_panel
             },
@@ -331,9 +317,14 @@ fn delete_dir<TFileSystem: Clone + Debug + Default + FileSystem>(
                 tabs: delete_dir_from_tab(
                     panel.path.clone(),
                     panel.tab,
+                    permanent,
                     state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.right_panel
             },
@@ -351,36 +342,6 @@ fn delete_dir<TFileSystem: Clone + Debug + Default + FileSystem>(
     }
 }
 
-fn copy_dir_to_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
-    from: PathBuf,
-    to: PathBuf,
-    current_tab: TabIdx,
-    mut tabs: Vec<TabState<TFileSystem>>,
-    file_system: &mut TFileSystem,
-    //icons: &IconsConfig,
-    big_config: &Config,
-) -> Vec<TabState<TFileSystem>> {
-    let mut result = Vec::<TabState<TFileSystem>>::new();
-
-    for (idx, tab_state) in tabs.iter_mut().enumerate() {
-        if idx == current_tab {
-            match file_system.copy_dir(from.as_path(), to.as_path()) {
-                Ok(_) => result.push(TabState::with_dir(
-                    tab_state.path.as_path(),
-                    file_system,
-                    //icons,
-                    big_config,
-                )),
-                Err(_) => {}
-            }
-        } else {
-            result.push(tab_state.clone());
-        }
-    }
-
-    result
-}
-
 fn open_dir_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     path: PathBuf,
     current_tab: TabIdx,
@@ -398,16 +359,16 @@ fn open_dir_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
                 .iter()
                 .find(|item| item.is_dir() && item.get_path().eq(path.as_path()));
             if let Some(item) = dir_to_open {
-                result.push(TabState::with_dir(
+                result.push(val.open_dir(
                     item.get_path().as_path(),
                     file_system,
                     //icons,
                     big_config,
                 ));
             } else {
-                if path.exists() {
+                if file_system.exist(&path) {
                     //result.push(TabState::with_dir(path.as_path(), file_system, icons));
-                    result.push(TabState::with_dir(path.as_path(), file_system, big_config));
+                    result.push(val.open_dir(path.as_path(), file_system, big_config));
                 } else {
                     result.push(val.clone());
                 }
@@ -425,28 +386,34 @@ fn rename_dir_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     to: PathBuf,
     current_tab: TabIdx,
     tabs: Vec<TabState<TFileSystem>>,
-    file_system: &mut TFileSystem,
-    //icons: &IconsConfig,
-    big_config: &Config,
+    context: TabContext<TFileSystem>,
 ) -> Vec<TabState<TFileSystem>> {
+    let TabContext {
+        file_system,
+        errors,
+        journal,
+        config: big_config,
+    } = context;
     let mut result = Vec::<TabState<TFileSystem>>::new();
 
     for (idx, tab_state) in tabs.iter().enumerate() {
         if idx == current_tab {
             match file_system.rename_item(&from, &to) {
-                Ok(_) => result.push(TabState::with_dir(
-                    tab_state.path.as_path(),
-                    file_system,
-                    //icons,
-                    big_config,
-                )),
-                Err(_) => result.push(TabState::with_dir(
-                    //TODO: temporary fix add proper error handling in reducers
-                    tab_state.path.as_path(),
-                    file_system,
-                    //icons,
-                    big_config,
-                )),
+                Ok(_) => {
+                    journal.record(JournalOperation::Rename {
+                        from: from.clone(),
+                        to: to.clone(),
+                    });
+                    result.push(tab_state.reload(file_system, big_config))
+                }
+                Err(err) => {
+                    errors.push(AppError::new(
+                        FileOperation::RenameDir,
+                        vec![from.clone(), to.clone()],
+                        &err,
+                    ));
+                    result.push(tab_state.clone());
+                }
             }
         } else {
             result.push(tab_state.clone());
@@ -459,11 +426,16 @@ fn rename_dir_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
 fn delete_dir_from_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     path: PathBuf,
     current_tab: TabIdx,
+    permanent: bool,
     mut tabs: Vec<TabState<TFileSystem>>,
-    file_system: &mut TFileSystem,
-    //icons: &IconsConfig,
-    big_config: &Config,
+    context: TabContext<TFileSystem>,
 ) -> Vec<TabState<TFileSystem>> {
+    let TabContext {
+        file_system,
+        errors,
+        journal,
+        config: big_config,
+    } = context;
     let mut result = Vec::<TabState<TFileSystem>>::new();
 
     for (idx, val) in tabs.iter_mut().enumerate() {
@@ -473,16 +445,26 @@ fn delete_dir_from_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
                 .iter()
                 .find(|item| item.is_dir() && item.get_path().eq(path.as_path()));
             if let Some(item) = dir_to_delete {
-                match file_system.delete_empty_dir(&item.get_path()) {
-                    Ok(_) => {
-                        //result.push(TabState::with_dir(val.path.as_path(), file_system, icons))
-                        result.push(TabState::with_dir(
-                            val.path.as_path(),
-                            file_system,
-                            big_config,
-                        ))
+                let delete_result = if permanent {
+                    file_system.delete_empty_dir(&item.get_path())
+                } else {
+                    move_to_trash(file_system, &item.get_path())
+                        .map(|entry| journal.record(JournalOperation::MoveToTrash { entry }))
+                };
+                match delete_result {
+                    Ok(_) => result.push(val.reload(file_system, big_config)),
+                    Err(err) => {
+                        errors.push(AppError::new(
+                            if permanent {
+                                FileOperation::DeleteDir
+                            } else {
+                                FileOperation::MoveToTrash
+                            },
+                            vec![item.get_path()],
+                            &err,
+                        ));
+                        result.push(val.clone());
                     }
-                    Err(_) => {}
                 }
             } else {
                 result.push(val.clone());
@@ -498,11 +480,16 @@ fn delete_dir_from_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
 fn delete_dir_with_content_from_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     path: PathBuf,
     current_tab: TabIdx,
+    permanent: bool,
     mut tabs: Vec<TabState<TFileSystem>>,
-    file_system: &mut TFileSystem,
-    //icons: &IconsConfig,
-    big_config: &Config,
+    context: TabContext<TFileSystem>,
 ) -> Vec<TabState<TFileSystem>> {
+    let TabContext {
+        file_system,
+        errors,
+        journal,
+        config: big_config,
+    } = context;
     let mut result = Vec::<TabState<TFileSystem>>::new();
 
     for (idx, val) in tabs.iter_mut().enumerate() {
@@ -512,16 +499,26 @@ fn delete_dir_with_content_from_tab<TFileSystem: Clone + Debug + Default + FileS
                 .iter()
                 .find(|item| item.is_dir() && item.get_path().eq(path.as_path()));
             if let Some(item) = dir_to_delete {
-                match file_system.delete_dir(&item.get_path()) {
-                    Ok(_) => {
-                        //result.push(TabState::with_dir(val.path.as_path(), file_system, icons))
-                        result.push(TabState::with_dir(
-                            val.path.as_path(),
-                            file_system,
-                            big_config,
-                        ))
+                let delete_result = if permanent {
+                    file_system.delete_dir(&item.get_path())
+                } else {
+                    move_to_trash(file_system, &item.get_path())
+                        .map(|entry| journal.record(JournalOperation::MoveToTrash { entry }))
+                };
+                match delete_result {
+                    Ok(_) => result.push(val.reload(file_system, big_config)),
+                    Err(err) => {
+                        errors.push(AppError::new(
+                            if permanent {
+                                FileOperation::DeleteDir
+                            } else {
+                                FileOperation::MoveToTrash
+                            },
+                            vec![item.get_path()],
+                            &err,
+                        ));
+                        result.push(val.clone());
                     }
-                    Err(_) => {}
                 }
             } else {
                 result.push(val.clone());
@@ -538,25 +535,34 @@ fn create_directory_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     parent_path: PathBuf,
     tab: TabIdx,
     mut tabs: Vec<TabState<TFileSystem>>,
-    file_system: &mut TFileSystem,
-    //icons: &IconsConfig,
-    big_config: &Config,
+    context: TabContext<TFileSystem>,
 ) -> Vec<TabState<TFileSystem>> {
+    let TabContext {
+        file_system,
+        errors,
+        journal,
+        config: big_config,
+    } = context;
     let mut result = Vec::<TabState<TFileSystem>>::new();
     for (idx, val) in tabs.iter_mut().enumerate() {
         if idx == tab {
-            if parent_path.exists() {
+            if file_system.exist(&parent_path) {
                 let mut dir_path = parent_path.clone();
                 dir_path.push(dir_name.clone());
 
                 match file_system.create_dir(&dir_path) {
-                    Ok(_) => result.push(TabState::with_dir(
-                        parent_path.as_path(),
-                        file_system,
-                        //icons,
-                        big_config,
-                    )),
-                    Err(_) => {}
+                    Ok(_) => {
+                        journal.record(JournalOperation::CreateDir { path: dir_path });
+                        result.push(val.refresh(file_system, big_config))
+                    }
+                    Err(err) => {
+                        errors.push(AppError::new(
+                            FileOperation::CreateDir,
+                            vec![dir_path],
+                            &err,
+                        ));
+                        result.push(val.clone());
+                    }
                 };
             } else {
                 result.push(val.clone());
@@ -0,0 +1,106 @@
+use std::{fmt::Debug, path::Path, time::SystemTime};
+
+use crate::app::{
+    actions::DirSizeAction,
+    dir_size::DirSizes,
+    file_system::{
+        file_system_item::FileSystemItem, functions::sort_file_system_items, FileSystem,
+    },
+    state::{AppState, TabState},
+};
+
+pub fn dir_size_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    dir_size_action: DirSizeAction,
+) -> AppState<TFileSystem> {
+    match dir_size_action {
+        // Resolved by the middleware, which starts the scan.
+        DirSizeAction::Scan { .. } => {}
+        DirSizeAction::Started { paths } => state.dir_sizes.start_scan(&paths),
+        DirSizeAction::Calculated {
+            path,
+            modified,
+            size,
+        } => state.dir_sizes.insert(path, modified, size),
+        DirSizeAction::Failed { path } => state.dir_sizes.remove(&path),
+    }
+    state
+}
+
+/// Forgets the sizes of the directories above `path` after it was changed,
+/// so tabs which scan them on their own compute them again.
+pub fn forget_dir_sizes<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    path: &Path,
+) -> AppState<TFileSystem> {
+    state.dir_sizes.forget_with_ancestors(path);
+    for tab in state
+        .left_panel
+        .tabs
+        .iter_mut()
+        .chain(state.right_panel.tabs.iter_mut())
+    {
+        for item in tab.items.iter_mut() {
+            if let FileSystemItem::Directory(dir) = item {
+                if path.starts_with(dir.get_path()) {
+                    dir.content = None;
+                }
+            }
+        }
+    }
+    state
+}
+
+/// Puts the cached sizes into the directories listed in tabs, so they are
+/// shown and sorted by the size of their content.
+pub fn apply_dir_sizes<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+) -> AppState<TFileSystem> {
+    for tab in state
+        .left_panel
+        .tabs
+        .iter_mut()
+        .chain(state.right_panel.tabs.iter_mut())
+    {
+        apply_to_tab(tab, &state.dir_sizes);
+    }
+    state
+}
+
+fn apply_to_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
+    tab: &mut TabState<TFileSystem>,
+    dir_sizes: &DirSizes,
+) {
+    let mut changed = false;
+    for item in tab.items.iter_mut() {
+        if let FileSystemItem::Directory(dir) = item {
+            let content = dir_sizes.get(&dir.get_path(), SystemTime::from(dir.modified));
+            if let Some(content) = content.filter(|content| dir.content != Some(*content)) {
+                dir.size = content.bytes;
+                dir.content = Some(content);
+                changed = true;
+            }
+        }
+    }
+    if !changed {
+        return;
+    }
+
+    // Sorting by size can move items, keep the cursor on the same one.
+    let current = tab
+        .tab_state
+        .selected()
+        .and_then(|idx| tab.filtered_items().get(idx).map(|item| item.get_path()));
+    if tab.is_disk_usage() {
+        tab.rank_by_size();
+    } else {
+        sort_file_system_items(&mut tab.items, &tab.sort);
+    }
+    if let Some(current) = current {
+        let idx = tab
+            .filtered_items()
+            .iter()
+            .position(|item| item.get_path() == current);
+        tab.tab_state.select(idx);
+    }
+}
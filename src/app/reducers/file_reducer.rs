@@ -3,83 +3,128 @@ use std::{ffi::OsStr, path::PathBuf};
 
 use crate::app::{
     actions::{FileAction, PanelInfo, PanelSide},
+    bulk_rename::{apply_renames, BulkRename},
     //config::{icon_cfg::IconsConfig, program_associations::FileAssociatedPrograms, Config},
-    config::{program_associations::FileAssociatedPrograms, Config},
-    file_system::{file_system_item::FileSystemItem, FileSystem},
-    state::{AppState, ChildProgramDesc, PanelState, TabIdx, TabState},
+    config::program_associations::FileAssociatedPrograms,
+    file_system::{file_system_item::FileSystemItem, trash::move_to_trash, FileSystem},
+    journal::JournalOperation,
+    state::{AppError, AppState, ChildProgramDesc, FileOperation, PanelState, TabIdx, TabState},
 };
 
-use super::{reload_tab, reload_tab_contain_item, reload_tab_with_path};
+use super::{
+    reload_tab, reload_tab_contain_item, reload_tab_with_path, reload_tabs_with_items, TabContext,
+};
 
 pub fn file_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
     state: AppState<TFileSystem>,
     file_action: FileAction,
 ) -> AppState<TFileSystem> {
     match file_action {
-        FileAction::Delete { panel } => delete_file(state, panel),
+        FileAction::Delete { panel } => delete_file(state, panel, false),
+        FileAction::DeletePermanently { panel } => delete_file(state, panel, true),
         FileAction::Rename { from, to } => rename_file(state, from, to),
-        FileAction::Move { from, to } => rename_file(state, from, to),
         FileAction::Open { panel } => open_file(state, panel),
         FileAction::Create { file_name, panel } => create_file(state, file_name, panel),
-        FileAction::Copy { from, to } => copy_file(state, from, to),
+        // Copies and moves run as jobs, `job_middleware` takes these actions
+        // before they get here.
+        FileAction::Copy { .. } | FileAction::Move { .. } => state,
+        FileAction::BulkRename { panel, items } => start_bulk_rename(state, panel, items),
+        FileAction::MultiRename { panel, renames } => rename_items(state, panel.path, renames),
     }
 }
 
-fn copy_file<TFileSystem: Clone + Debug + Default + FileSystem>(
+/// Writes the names of `items` to a temporary file and opens it in the
+/// editor, the renames are applied by `finish_bulk_rename` once it exits.
+fn start_bulk_rename<TFileSystem: Clone + Debug + Default + FileSystem>(
     mut state: AppState<TFileSystem>,
-    from: PanelInfo,
-    to: PanelInfo,
+    panel: PanelInfo,
+    items: Vec<PathBuf>,
 ) -> AppState<TFileSystem> {
-    match to.side {
-        PanelSide::Left => AppState {
-            left_panel: PanelState {
-                tabs: copy_file_to_tab(
-                    from.path,
-                    to.path,
-                    to.tab,
-                    state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
-                ),
-                ..state.left_panel
-            },
-            right_panel: PanelState {
-                tabs: reload_tab(
-                    from.tab,
-                    state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
-                ),
-                ..state.right_panel
-            },
-            ..state
-        },
-        PanelSide::Right => AppState {
-            right_panel: PanelState {
-                tabs: copy_file_to_tab(
-                    from.path,
-                    to.path,
-                    to.tab,
-                    state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
-                ),
-                ..state.right_panel
-            },
-            left_panel: PanelState {
-                tabs: reload_tab(
-                    from.tab,
-                    state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
-                ),
-                ..state.left_panel
-            },
+    match BulkRename::start(&mut state.file_system, panel.path.clone(), items) {
+        Ok(bulk_rename) => AppState {
+            child_program: Some(ChildProgramDesc {
+                program_name: state
+                    .config
+                    .file_associated_programs
+                    .get_program_name(String::from("txt")),
+                args: vec![bulk_rename.list_path.to_string_lossy().to_string()],
+            }),
+            bulk_rename: Some(bulk_rename),
             ..state
         },
+        Err(err) => {
+            state.errors.push(AppError::new(
+                FileOperation::BulkRename,
+                vec![panel.path],
+                &err,
+            ));
+            state
+        }
+    }
+}
+
+/// Applies the names edited in the editor opened by `start_bulk_rename`.
+pub fn finish_bulk_rename<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+) -> AppState<TFileSystem> {
+    let bulk_rename = match state.bulk_rename.take() {
+        Some(bulk_rename) => bulk_rename,
+        None => return state,
+    };
+
+    let renames = bulk_rename.read_renames(&state.file_system);
+    let _ = state.file_system.delete_file(&bulk_rename.list_path);
+    match renames {
+        Ok(renames) => rename_items(state, bulk_rename.dir, renames),
+        Err(err) => {
+            state.errors.push(AppError::new(
+                FileOperation::BulkRename,
+                vec![bulk_rename.dir],
+                &err,
+            ));
+            state
+        }
     }
 }
 
+/// Reports the editor opened by `start_bulk_rename` failing and drops the
+/// edited names, nothing is renamed.
+pub fn abort_bulk_rename<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    error: AppError,
+) -> AppState<TFileSystem> {
+    if let Some(bulk_rename) = state.bulk_rename.take() {
+        let _ = state.file_system.delete_file(&bulk_rename.list_path);
+    }
+    state.errors.push(error);
+    state
+}
+
+/// Applies renames checked beforehand, all recorded as one journal entry
+/// so a single undo reverts them.
+fn rename_items<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    dir: PathBuf,
+    renames: Vec<(PathBuf, PathBuf)>,
+) -> AppState<TFileSystem> {
+    let (operations, result) = apply_renames(&mut state.file_system, renames);
+    if let Err(err) = result {
+        state
+            .errors
+            .push(AppError::new(FileOperation::BulkRename, vec![dir], &err));
+    }
+    if operations.is_empty() {
+        return state;
+    }
+
+    let paths: Vec<PathBuf> = operations
+        .iter()
+        .flat_map(|operation| operation.paths())
+        .collect();
+    state.journal.record(JournalOperation::Batch { operations });
+    reload_tabs_with_items(state, &paths)
+}
+
 fn create_file<TFileSystem: Clone + Debug + Default + FileSystem>(
     mut state: AppState<TFileSystem>,
     file_name: String,
@@ -93,8 +138,12 @@ fn create_file<TFileSystem: Clone + Debug + Default + FileSystem>(
                     panel.path.clone(),
                     panel.tab,
                     state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.left_panel
             },
@@ -116,8 +165,12 @@ fn create_file<TFileSystem: Clone + Debug + Default + FileSystem>(
                     panel.path.clone(),
                     panel.tab,
                     state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.right_panel
             },
@@ -140,7 +193,11 @@ fn open_file<TFileSystem: Clone + Debug + Default + FileSystem>(
     panel: PanelInfo,
 ) -> AppState<TFileSystem> {
     AppState {
-        child_program: open_file_from_tab(panel.path, &state.config.file_associated_programs),
+        child_program: open_file_from_tab(
+            panel.path,
+            &state.file_system,
+            &state.config.file_associated_programs,
+        ),
         ..state
     }
 }
@@ -148,6 +205,7 @@ fn open_file<TFileSystem: Clone + Debug + Default + FileSystem>(
 fn delete_file<TFileSystem: Clone + Debug + Default + FileSystem>(
     mut state: AppState<TFileSystem>,
     panel: PanelInfo,
+    permanent: bool,
 ) -> AppState<TFileSystem> {
     match panel.side {
         PanelSide::Left => AppState {
@@ -155,9 +213,14 @@ fn delete_file<TFileSystem: Clone + Debug + Default + FileSystem>(
                 tabs: delete_file_from_tab(
                     panel.path.clone(),
                     panel.tab,
+                    permanent,
                     state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.left_panel
             },
@@ -177,9 +240,14 @@ fn delete_file<TFileSystem: Clone + Debug + Default + FileSystem>(
                 tabs: delete_file_from_tab(
                     panel.path.clone(),
                     panel.tab,
+                    permanent,
                     state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.right_panel
             },
@@ -210,8 +278,12 @@ fn rename_file<TFileSystem: Clone + Debug + Default + FileSystem>(
                     to.path,
                     to.tab,
                     state.left_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.left_panel
             },
@@ -233,8 +305,12 @@ fn rename_file<TFileSystem: Clone + Debug + Default + FileSystem>(
                     to.path,
                     to.tab,
                     state.right_panel.tabs,
-                    &mut state.file_system,
-                    &state.config,
+                    TabContext {
+                        file_system: &mut state.file_system,
+                        errors: &mut state.errors,
+                        journal: &mut state.journal,
+                        config: &state.config,
+                    },
                 ),
                 ..state.right_panel
             },
@@ -257,26 +333,33 @@ fn create_file_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     dir_path: PathBuf,
     tab: TabIdx,
     mut tabs: Vec<TabState<TFileSystem>>,
-    file_system: &mut TFileSystem,
-    //icons: &IconsConfig,
-    big_config: &Config,
+    context: TabContext<TFileSystem>,
 ) -> Vec<TabState<TFileSystem>> {
+    let TabContext {
+        file_system,
+        errors,
+        journal,
+        config: big_config,
+    } = context;
     let mut result = Vec::<TabState<TFileSystem>>::new();
     for (idx, tab_state) in tabs.iter_mut().enumerate() {
         if idx == tab {
-            if dir_path.exists() {
+            if file_system.exist(&dir_path) {
                 let mut file_path = dir_path.clone();
                 file_path.push(file_name.clone());
                 match file_system.create_file(&file_path) {
                     Ok(_) => {
-                        //result.push(TabState::with_dir(dir_path.as_path(), file_system, icons))
-                        result.push(TabState::with_dir(
-                            dir_path.as_path(),
-                            file_system,
-                            big_config,
-                        ))
+                        journal.record(JournalOperation::CreateFile { path: file_path });
+                        result.push(tab_state.refresh(file_system, big_config))
+                    }
+                    Err(err) => {
+                        errors.push(AppError::new(
+                            FileOperation::CreateFile,
+                            vec![file_path],
+                            &err,
+                        ));
+                        result.push(tab_state.clone());
                     }
-                    Err(_) => {}
                 }
             } else {
                 result.push(tab_state.clone());
@@ -289,11 +372,17 @@ fn create_file_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     result
 }
 
-fn open_file_from_tab(
+fn open_file_from_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     path: PathBuf,
+    file_system: &TFileSystem,
     file_associated_programs: &FileAssociatedPrograms,
 ) -> Option<ChildProgramDesc> {
-    if path.is_file() && path.exists() {
+    let is_file = file_system
+        .canonicalize(&path)
+        .and_then(|target_path| file_system.metadata(target_path))
+        .map(|metadata| metadata.is_file())
+        .unwrap_or(false);
+    if is_file {
         let file_extension = path.extension().unwrap_or(OsStr::new(""));
         Some(ChildProgramDesc {
             program_name: file_associated_programs
@@ -308,11 +397,16 @@ fn open_file_from_tab(
 fn delete_file_from_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     path: PathBuf,
     current_tab: TabIdx,
+    permanent: bool,
     mut tabs: Vec<TabState<TFileSystem>>,
-    file_system: &mut TFileSystem,
-    //icons: &IconsConfig,
-    big_config: &Config,
+    context: TabContext<TFileSystem>,
 ) -> Vec<TabState<TFileSystem>> {
+    let TabContext {
+        file_system,
+        errors,
+        journal,
+        config: big_config,
+    } = context;
     let mut result = Vec::<TabState<TFileSystem>>::new();
 
     for (idx, tab_state) in tabs.iter_mut().enumerate() {
@@ -323,14 +417,26 @@ fn delete_file_from_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
                 .find(|item| item.is_file() && item.get_path().eq(&path));
             if let Some(item) = item_to_delete {
                 if let FileSystemItem::File(file) = item {
-                    match file_system.delete_file(&file.get_path()) {
-                        Ok(_) => result.push(TabState::with_dir(
-                            tab_state.path.as_path(),
-                            file_system,
-                            //icons,
-                            big_config,
-                        )),
-                        Err(_) => {} //TODO: add error handling to state
+                    let delete_result = if permanent {
+                        file_system.delete_file(&file.get_path())
+                    } else {
+                        move_to_trash(file_system, &file.get_path())
+                            .map(|entry| journal.record(JournalOperation::MoveToTrash { entry }))
+                    };
+                    match delete_result {
+                        Ok(_) => result.push(tab_state.reload(file_system, big_config)),
+                        Err(err) => {
+                            errors.push(AppError::new(
+                                if permanent {
+                                    FileOperation::DeleteFile
+                                } else {
+                                    FileOperation::MoveToTrash
+                                },
+                                vec![file.get_path()],
+                                &err,
+                            ));
+                            result.push(tab_state.clone());
+                        }
                     }
                 } else {
                     result.push(tab_state.clone());
@@ -346,55 +452,38 @@ fn delete_file_from_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     result
 }
 
-fn copy_file_to_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
-    from: PathBuf,
-    to: PathBuf,
-    current_tab: TabIdx,
-    mut tabs: Vec<TabState<TFileSystem>>,
-    file_system: &mut TFileSystem,
-    //icons: &IconsConfig,
-    big_config: &Config,
-) -> Vec<TabState<TFileSystem>> {
-    let mut result = Vec::<TabState<TFileSystem>>::new();
-    for (idx, tab_state) in tabs.iter_mut().enumerate() {
-        if idx == current_tab {
-            match file_system.copy_file(from.as_path(), to.as_path()) {
-                Ok(_) => result.push(TabState::with_dir(
-                    tab_state.path.as_path(),
-                    file_system,
-                    //icons,
-                    big_config,
-                )),
-                Err(_) => {}
-            }
-        } else {
-            result.push(tab_state.clone());
-        }
-    }
-
-    result
-}
-
 fn rename_file_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     from: PathBuf,
     to: PathBuf,
     current_tab: TabIdx,
     mut tabs: Vec<TabState<TFileSystem>>,
-    file_system: &mut TFileSystem,
-    //icons: &IconsConfig,
-    big_config: &Config,
+    context: TabContext<TFileSystem>,
 ) -> Vec<TabState<TFileSystem>> {
+    let TabContext {
+        file_system,
+        errors,
+        journal,
+        config: big_config,
+    } = context;
     let mut result = Vec::<TabState<TFileSystem>>::new();
     for (idx, tab_state) in tabs.iter_mut().enumerate() {
         if idx == current_tab {
             match file_system.rename_item(&from.as_path(), &to.as_path()) {
-                Ok(_) => result.push(TabState::with_dir(
-                    tab_state.path.as_path(),
-                    file_system,
-                    //icons,
-                    big_config,
-                )),
-                Err(_) => {} //TODO: add error handling to state
+                Ok(_) => {
+                    journal.record(JournalOperation::Rename {
+                        from: from.clone(),
+                        to: to.clone(),
+                    });
+                    result.push(tab_state.reload(file_system, big_config))
+                }
+                Err(err) => {
+                    errors.push(AppError::new(
+                        FileOperation::RenameFile,
+                        vec![from.clone(), to.clone()],
+                        &err,
+                    ));
+                    result.push(tab_state.clone());
+                }
             }
         } else {
             result.push(tab_state.clone());
@@ -0,0 +1,72 @@
+use std::fmt::Debug;
+
+use crate::app::{
+    actions::{FindAction, PanelSide},
+    file_system::FileSystem,
+    state::{AppState, TabState},
+};
+
+use super::tab_reducer::open_tab_in_panel;
+
+pub fn find_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    find_action: FindAction,
+) -> AppState<TFileSystem> {
+    match find_action {
+        // Resolved by the middleware, which starts the search.
+        FindAction::Start { .. } => state,
+        FindAction::Started {
+            panel_side,
+            root,
+            query,
+            search,
+        } => {
+            let tab = TabState::with_find_results(&root, query, search, &state.config);
+            match panel_side {
+                PanelSide::Left => AppState {
+                    left_panel: open_tab_in_panel(state.left_panel, tab),
+                    ..state
+                },
+                PanelSide::Right => AppState {
+                    right_panel: open_tab_in_panel(state.right_panel, tab),
+                    ..state
+                },
+            }
+        }
+        FindAction::Found { search, paths } => {
+            let mut shown = false;
+            for tab in state
+                .left_panel
+                .tabs
+                .iter_mut()
+                .chain(state.right_panel.tabs.iter_mut())
+            {
+                if tab
+                    .find_search()
+                    .is_some_and(|tab_search| tab_search.id == search.id)
+                {
+                    tab.add_find_results(paths.clone(), &state.file_system, &state.config);
+                    shown = true;
+                }
+            }
+            // The tab was closed or shows something else now.
+            if !shown {
+                search.cancel();
+            }
+            state
+        }
+        FindAction::Finished { id } => {
+            for tab in state
+                .left_panel
+                .tabs
+                .iter_mut()
+                .chain(state.right_panel.tabs.iter_mut())
+            {
+                if tab.find_search().is_some_and(|search| search.id == id) {
+                    tab.finish_find();
+                }
+            }
+            state
+        }
+    }
+}
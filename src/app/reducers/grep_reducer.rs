@@ -0,0 +1,85 @@
+use std::fmt::Debug;
+
+use crate::app::{
+    actions::GrepAction,
+    file_system::FileSystem,
+    grep::GrepResults,
+    state::{AppState, ChildProgramDesc},
+};
+
+pub fn grep_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    grep_action: GrepAction,
+) -> AppState<TFileSystem> {
+    match grep_action {
+        // Resolved by the middleware, which starts the search.
+        GrepAction::Start { .. } => state,
+        GrepAction::Started {
+            root,
+            query,
+            search,
+        } => {
+            if let Some(grep) = state.grep.take() {
+                grep.search.cancel();
+            }
+            AppState {
+                grep: Some(GrepResults::new(root, query, search)),
+                ..state
+            }
+        }
+        GrepAction::Found { search, matches } => {
+            match state.grep.as_mut() {
+                Some(grep) if grep.search.id == search.id => grep.matches.extend(matches),
+                // The search window was closed or started another search.
+                _ => search.cancel(),
+            }
+            state
+        }
+        GrepAction::Finished { id } => {
+            if let Some(grep) = state.grep.as_mut().filter(|grep| grep.search.id == id) {
+                grep.searching = false;
+            }
+            state
+        }
+        GrepAction::Open { index } => open_match(state, index),
+        GrepAction::Close => {
+            if let Some(grep) = state.grep.take() {
+                grep.search.cancel();
+            }
+            AppState {
+                modal: None,
+                ..state
+            }
+        }
+    }
+}
+
+/// Opens the file in its associated program, which is passed the line as
+/// `+LINE` the way vi, emacs, nano and less take it.
+fn open_match<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    index: usize,
+) -> AppState<TFileSystem> {
+    let found = match state.grep.as_ref().and_then(|grep| grep.matches.get(index)) {
+        Some(found) => found.clone(),
+        None => return state,
+    };
+    let file_extension = found
+        .path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_string())
+        .unwrap_or_default();
+    AppState {
+        child_program: Some(ChildProgramDesc {
+            program_name: state
+                .config
+                .file_associated_programs
+                .get_program_name(file_extension),
+            args: vec![
+                format!("+{}", found.line),
+                found.path.to_string_lossy().to_string(),
+            ],
+        }),
+        ..state
+    }
+}
@@ -0,0 +1,159 @@
+use std::fmt::Debug;
+
+use crate::app::{
+    actions::JobAction,
+    file_system::FileSystem,
+    jobs::{Job, JobId, JobKind, JobOutcome, JobProgress, JobStatus},
+    journal::JournalOperation,
+    state::{AppState, ModalType},
+};
+
+use super::reload_tabs_with_items;
+
+pub fn job_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    job_action: JobAction,
+) -> AppState<TFileSystem> {
+    match job_action {
+        JobAction::Start { job } => start_job(state, job),
+        JobAction::Progress { id, progress } => update_progress(state, id, progress),
+        JobAction::Finished { id, outcome } => finish_job(state, id, outcome),
+        JobAction::Pause { id } => set_job_status(state, id, JobStatus::Paused),
+        JobAction::Resume { id } => set_job_status(state, id, JobStatus::Running),
+        JobAction::Cancel { id } => cancel_job(state, id),
+        JobAction::Conflict { job } => queue_conflict(state, job),
+        JobAction::ResolveConflict { .. } => state,
+        JobAction::ConflictsResolved { ids } => remove_conflicts(state, ids),
+    }
+}
+
+fn start_job<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    job: Job,
+) -> AppState<TFileSystem> {
+    let modal = match state.modal {
+        Some(modal) => Some(modal),
+        None => Some(ModalType::JobsModal {
+            job_id: Some(job.id),
+        }),
+    };
+    state.jobs.push(job);
+
+    AppState { modal, ..state }
+}
+
+fn queue_conflict<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    job: Job,
+) -> AppState<TFileSystem> {
+    let modal = match state.modal {
+        None | Some(ModalType::JobsModal { .. }) => Some(ModalType::ConflictModal),
+        Some(modal) => Some(modal),
+    };
+    state.conflicts.push(job);
+
+    AppState { modal, ..state }
+}
+
+fn remove_conflicts<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    ids: Vec<JobId>,
+) -> AppState<TFileSystem> {
+    state.conflicts.retain(|job| !ids.contains(&job.id));
+    let modal = match state.modal {
+        Some(ModalType::ConflictModal) if state.conflicts.is_empty() => None,
+        modal => modal,
+    };
+
+    AppState { modal, ..state }
+}
+
+fn update_progress<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    id: JobId,
+    progress: JobProgress,
+) -> AppState<TFileSystem> {
+    if let Some(job) = state.jobs.iter_mut().find(|job| job.id == id) {
+        job.progress = progress;
+    }
+
+    state
+}
+
+fn set_job_status<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    id: JobId,
+    status: JobStatus,
+) -> AppState<TFileSystem> {
+    if let Some(job) = state.jobs.iter_mut().find(|job| job.id == id) {
+        match status {
+            JobStatus::Paused => job.control.pause(),
+            JobStatus::Running => job.control.resume(),
+        }
+        job.status = status;
+    }
+
+    state
+}
+
+fn cancel_job<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    id: JobId,
+) -> AppState<TFileSystem> {
+    if let Some(job) = state.jobs.iter().find(|job| job.id == id) {
+        job.control.cancel();
+    }
+
+    state
+}
+
+fn finish_job<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    id: JobId,
+    outcome: JobOutcome,
+) -> AppState<TFileSystem> {
+    let job = match state.jobs.iter().position(|job| job.id == id) {
+        Some(idx) => state.jobs.remove(idx),
+        None => return state,
+    };
+
+    match outcome {
+        // Overwritten items can't be brought back and undoing a merge would
+        // delete what was there before, so only jobs which created their
+        // target are recorded, whatever the conflict resolution was.
+        JobOutcome::Completed {
+            replaced_existing: false,
+            moved_across_devices,
+        } => state
+            .journal
+            .record(journal_operation(&job, moved_across_devices)),
+        JobOutcome::Completed { .. } => {}
+        JobOutcome::Cancelled => {}
+        JobOutcome::Failed(error) => state.errors.push(error),
+    }
+
+    let modal = match state.modal {
+        Some(ModalType::JobsModal {
+            job_id: Some(job_id),
+        }) if job_id == id => None,
+        modal => modal,
+    };
+
+    reload_tabs_with_items(
+        AppState { modal, ..state },
+        &[job.from.clone(), job.to.clone()],
+    )
+}
+
+fn journal_operation(job: &Job, moved_across_devices: bool) -> JournalOperation {
+    let from = job.from.clone();
+    let to = job.to.clone();
+    match (job.kind, job.is_dir) {
+        (JobKind::Copy, false) => JournalOperation::CopyFile { from, to },
+        (JobKind::Copy, true) => JournalOperation::CopyDir { from, to },
+        (JobKind::Move, _) if moved_across_devices => {
+            JournalOperation::MoveAcrossDevices { from, to }
+        }
+        (JobKind::Move, _) => JournalOperation::Rename { from, to },
+    }
+}
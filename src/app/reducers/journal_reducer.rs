@@ -0,0 +1,67 @@
+use std::fmt::Debug;
+
+use crate::app::{
+    actions::JournalAction,
+    file_system::FileSystem,
+    state::{AppError, AppState, FileOperation},
+};
+
+use super::reload_tabs_with_items;
+
+pub fn journal_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    journal_action: JournalAction,
+) -> AppState<TFileSystem> {
+    match journal_action {
+        JournalAction::Undo => undo(state),
+        JournalAction::Redo => redo(state),
+    }
+}
+
+fn undo<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+) -> AppState<TFileSystem> {
+    let operation = match state.journal.pop_undo() {
+        Some(operation) => operation,
+        None => return state,
+    };
+
+    let paths = operation.paths();
+    // Kept for a retry once the cause of a failure is fixed.
+    let failed = operation.clone();
+    match operation.undo(&mut state.file_system) {
+        Ok(operation) => state.journal.push_redo(operation),
+        Err(err) => {
+            state.journal.push_undo(failed);
+            state
+                .errors
+                .push(AppError::new(FileOperation::Undo, paths.clone(), &err));
+        }
+    }
+
+    reload_tabs_with_items(state, &paths)
+}
+
+fn redo<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+) -> AppState<TFileSystem> {
+    let operation = match state.journal.pop_redo() {
+        Some(operation) => operation,
+        None => return state,
+    };
+
+    let paths = operation.paths();
+    // Kept for a retry once the cause of a failure is fixed.
+    let failed = operation.clone();
+    match operation.redo(&mut state.file_system) {
+        Ok(operation) => state.journal.push_undo(operation),
+        Err(err) => {
+            state.journal.push_redo(failed);
+            state
+                .errors
+                .push(AppError::new(FileOperation::Redo, paths.clone(), &err));
+        }
+    }
+
+    reload_tabs_with_items(state, &paths)
+}
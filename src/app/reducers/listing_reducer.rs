@@ -0,0 +1,67 @@
+use std::fmt::Debug;
+
+use crate::app::{
+    actions::{ListingAction, PanelSide},
+    file_system::FileSystem,
+    state::AppState,
+};
+
+pub fn listing_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    listing_action: ListingAction,
+) -> AppState<TFileSystem> {
+    match listing_action {
+        // Resolved by the middleware, which starts the listing.
+        ListingAction::Start { .. } => state,
+        ListingAction::Started {
+            panel_side,
+            path,
+            search,
+        } => {
+            let panel = match panel_side {
+                PanelSide::Left => &mut state.left_panel,
+                PanelSide::Right => &mut state.right_panel,
+            };
+            let listing = panel
+                .tabs
+                .iter_mut()
+                .filter(|tab| tab.path == path && tab.is_listing_pending())
+                .find_map(|tab| tab.listing.as_mut());
+            match listing {
+                Some(listing) => listing.search = Some(search),
+                None => search.cancel(),
+            }
+            state
+        }
+        ListingAction::Listed { search, items } => {
+            let tab = state
+                .left_panel
+                .tabs
+                .iter_mut()
+                .chain(state.right_panel.tabs.iter_mut())
+                .find(|tab| {
+                    tab.listing_search()
+                        .is_some_and(|tab_search| tab_search.id == search.id)
+                });
+            match tab {
+                Some(tab) => tab.add_listed_items(items),
+                // The tab was closed or shows something else now.
+                None => search.cancel(),
+            }
+            state
+        }
+        ListingAction::Finished { id } => {
+            for tab in state
+                .left_panel
+                .tabs
+                .iter_mut()
+                .chain(state.right_panel.tabs.iter_mut())
+            {
+                if tab.listing_search().is_some_and(|search| search.id == id) {
+                    tab.finish_listing();
+                }
+            }
+            state
+        }
+    }
+}
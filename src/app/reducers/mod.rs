@@ -1,34 +1,54 @@
 use super::{
-    actions::{AppAction, FileManagerActions},
+    actions::{AppAction, DirectoryAction, FileAction, FileManagerActions, SymlinkAction},
     //config::icon_cfg::IconsConfig,
     config::Config,
     file_system::FileSystem,
-    state::{AppState, PanelState, TabIdx, TabState},
+    journal::Journal,
+    state::{AppError, AppState, ModalType, PanelState, TabIdx, TabState},
 };
 use std::{
     fmt::Debug,
     path::{Path, PathBuf},
 };
 
+mod compare_reducer;
 mod dir_reducer;
+mod dir_size_reducer;
 mod file_reducer;
+mod find_reducer;
+mod grep_reducer;
+mod job_reducer;
+mod journal_reducer;
+mod listing_reducer;
 mod panel_reducer;
 mod search_reducer;
 mod symlink_reducer;
+mod sync_reducer;
 mod tab_reducer;
+mod trash_reducer;
 
+use compare_reducer::{compare_reducer, update_differences};
 use dir_reducer::dir_reducer;
-use file_reducer::file_reducer;
+use dir_size_reducer::{apply_dir_sizes, dir_size_reducer, forget_dir_sizes};
+use file_reducer::{abort_bulk_rename, file_reducer, finish_bulk_rename};
+use find_reducer::find_reducer;
+use grep_reducer::grep_reducer;
+use job_reducer::job_reducer;
+use journal_reducer::journal_reducer;
+use listing_reducer::listing_reducer;
 use panel_reducer::panel_reducer;
 use search_reducer::search_reducer;
 use symlink_reducer::symlink_reducer;
+use sync_reducer::sync_reducer;
 use tab_reducer::tab_reducer;
+use trash_reducer::trash_reducer;
 
 pub fn root_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
     state: AppState<TFileSystem>,
     action: FileManagerActions,
 ) -> AppState<TFileSystem> {
-    match action {
+    let deleted_path = deleted_path(&action);
+    let state = match action {
         FileManagerActions::App(app_action) => app_reducer(state.clone(), app_action),
         FileManagerActions::File(file_action) => file_reducer(state.clone(), file_action),
         FileManagerActions::Directory(dir_action) => dir_reducer(state.clone(), dir_action),
@@ -38,6 +58,72 @@ pub fn root_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
         FileManagerActions::Panel(panel_action) => panel_reducer(state.clone(), panel_action),
         FileManagerActions::Tab(tab_action) => tab_reducer(state.clone(), tab_action),
         FileManagerActions::Search(search_action) => search_reducer(state.clone(), search_action),
+        FileManagerActions::Trash(trash_action) => trash_reducer(state.clone(), trash_action),
+        FileManagerActions::Journal(journal_action) => {
+            journal_reducer(state.clone(), journal_action)
+        }
+        FileManagerActions::Job(job_action) => job_reducer(state.clone(), job_action),
+        FileManagerActions::DirSize(dir_size_action) => {
+            dir_size_reducer(state.clone(), dir_size_action)
+        }
+        FileManagerActions::Compare(compare_action) => {
+            compare_reducer(state.clone(), compare_action)
+        }
+        FileManagerActions::Sync(sync_action) => sync_reducer(state.clone(), sync_action),
+        FileManagerActions::Find(find_action) => find_reducer(state.clone(), find_action),
+        FileManagerActions::Grep(grep_action) => grep_reducer(state.clone(), grep_action),
+        FileManagerActions::Listing(listing_action) => {
+            listing_reducer(state.clone(), listing_action)
+        }
+    };
+
+    let state = match deleted_path {
+        Some(path) => forget_dir_sizes(state, &path),
+        None => state,
+    };
+
+    show_pending_conflict(show_pending_error(update_differences(apply_dir_sizes(
+        state,
+    ))))
+}
+
+fn deleted_path(action: &FileManagerActions) -> Option<PathBuf> {
+    match action {
+        FileManagerActions::File(FileAction::Delete { panel })
+        | FileManagerActions::File(FileAction::DeletePermanently { panel })
+        | FileManagerActions::Directory(DirectoryAction::Delete { panel, .. })
+        | FileManagerActions::Directory(DirectoryAction::DeletePermanently { panel, .. })
+        | FileManagerActions::Directory(DirectoryAction::DeleteWithContent { panel })
+        | FileManagerActions::Directory(DirectoryAction::DeleteWithContentPermanently { panel })
+        | FileManagerActions::Symlink(SymlinkAction::Delete { panel }) => Some(panel.path.clone()),
+        _ => None,
+    }
+}
+
+fn show_pending_error<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+) -> AppState<TFileSystem> {
+    if state.modal.is_none() && !state.errors.is_empty() {
+        let error = state.errors.remove(0);
+        AppState {
+            modal: Some(ModalType::MessageboxModal(error.to_string())),
+            ..state
+        }
+    } else {
+        state
+    }
+}
+
+fn show_pending_conflict<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+) -> AppState<TFileSystem> {
+    if state.modal.is_none() && !state.conflicts.is_empty() {
+        AppState {
+            modal: Some(ModalType::ConflictModal),
+            ..state
+        }
+    } else {
+        state
     }
 }
 
@@ -72,12 +158,19 @@ fn app_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
             },
             ..state
         },
-        AppAction::ChildProgramClosed => AppState {
+        AppAction::ChildProgramClosed { error: None } => finish_bulk_rename(AppState {
             child_program: None,
             ..state
-        },
+        }),
+        AppAction::ChildProgramClosed { error: Some(error) } => abort_bulk_rename(
+            AppState {
+                child_program: None,
+                ..state
+            },
+            error,
+        ),
         AppAction::ShowModal(modal_type) => AppState {
-            modal: Some(modal_type),
+            modal: Some(*modal_type),
             ..state
         },
         AppAction::CloseModal => AppState {
@@ -87,6 +180,16 @@ fn app_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
     }
 }
 
+/// What the functions changing the items of a tab work with: the file
+/// system, where failures and done operations go and the config the tab is
+/// reloaded with.
+struct TabContext<'a, TFileSystem> {
+    file_system: &'a mut TFileSystem,
+    errors: &'a mut Vec<AppError>,
+    journal: &'a mut Journal,
+    config: &'a Config,
+}
+
 fn reload_tab<TFileSystem: Clone + Default + Debug + FileSystem>(
     tab: TabIdx,
     tabs: Vec<TabState<TFileSystem>>,
@@ -97,12 +200,7 @@ fn reload_tab<TFileSystem: Clone + Default + Debug + FileSystem>(
     let mut result = Vec::<TabState<TFileSystem>>::new();
     for (idx, tab_state) in tabs.iter().enumerate() {
         if idx == tab {
-            result.push(TabState::with_dir(
-                tab_state.path.as_path(),
-                file_system,
-                &big_config,
-                //icons_cfg,
-            ));
+            result.push(tab_state.reload(file_system, big_config));
         } else {
             result.push(tab_state.clone());
         }
@@ -121,12 +219,7 @@ fn reload_tab_with_path<TFileSystem: Clone + Default + Debug + FileSystem>(
     let mut result = Vec::<TabState<TFileSystem>>::new();
     for tab_state in tabs.iter() {
         if tab_state.path == tab_path {
-            result.push(TabState::with_dir(
-                tab_state.path.as_path(),
-                file_system,
-                //icons_cfg,
-                &big_config,
-            ));
+            result.push(tab_state.reload(file_system, big_config));
         } else {
             result.push(tab_state.clone());
         }
@@ -164,9 +257,62 @@ fn reload_if_contain<TFileSystem: Clone + Default + Debug + FileSystem>(
     big_config: &Config,
 ) -> TabState<TFileSystem> {
     if tab_state.items.iter().any(|i| i.get_path() == path) {
-        //TabState::with_dir(tab_state.path.as_path(), file_system, icons_cfg)
-        TabState::with_dir(tab_state.path.as_path(), file_system, &big_config)
+        tab_state.reload(file_system, big_config)
     } else {
         tab_state.clone()
     }
 }
+
+/// Reloads the trash tabs, the tabs showing the parent directory of any of
+/// `paths` and the find results listing one of them.
+fn reload_tabs_with_items<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    paths: &[PathBuf],
+) -> AppState<TFileSystem> {
+    AppState {
+        left_panel: PanelState {
+            tabs: reload_panel_tabs_with_items(
+                paths,
+                state.left_panel.tabs,
+                &state.file_system,
+                &state.config,
+            ),
+            ..state.left_panel
+        },
+        right_panel: PanelState {
+            tabs: reload_panel_tabs_with_items(
+                paths,
+                state.right_panel.tabs,
+                &state.file_system,
+                &state.config,
+            ),
+            ..state.right_panel
+        },
+        ..state
+    }
+}
+
+fn reload_panel_tabs_with_items<TFileSystem: Clone + Debug + Default + FileSystem>(
+    paths: &[PathBuf],
+    tabs: Vec<TabState<TFileSystem>>,
+    file_system: &TFileSystem,
+    big_config: &Config,
+) -> Vec<TabState<TFileSystem>> {
+    tabs.into_iter()
+        .map(|tab_state| {
+            let contains_item = paths
+                .iter()
+                .any(|path| path.parent() == Some(tab_state.path.as_path()));
+            let shows_item = tab_state.is_find()
+                && tab_state
+                    .items
+                    .iter()
+                    .any(|item| paths.contains(&item.get_path()));
+            if tab_state.is_trash() || contains_item || shows_item {
+                tab_state.reload(file_system, big_config)
+            } else {
+                tab_state
+            }
+        })
+        .collect()
+}
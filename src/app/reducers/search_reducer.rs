@@ -168,10 +168,14 @@ fn input_search_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
 
     for (idx, tab_state) in tabs.iter().enumerate() {
         if idx == tab && tab_state.search_mode {
-            result.push(TabState {
+            let mut tab_state = TabState {
                 phrase: phrase.clone(),
                 ..tab_state.clone()
-            });
+            };
+            // The cursor follows the best match while typing.
+            let first_match = Some(0).filter(|_| !tab_state.filtered_items().is_empty());
+            tab_state.tab_state.select(first_match);
+            result.push(tab_state);
         } else {
             result.push(tab_state.clone());
         }
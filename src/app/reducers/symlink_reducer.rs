@@ -3,7 +3,8 @@ use std::{fmt::Debug, path::PathBuf};
 use crate::app::{
     actions::{PanelSide, SymlinkAction},
     file_system::FileSystem,
-    state::{AppState, PanelState, TabIdx, TabState},
+    journal::{Journal, JournalOperation},
+    state::{AppError, AppState, FileOperation, PanelState, TabIdx, TabState},
 };
 
 pub fn symlink_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
@@ -32,6 +33,8 @@ fn create_symlink<TFileSystem: Clone + Debug + Default + FileSystem>(
                     panel.tab,
                     panel.path,
                     &mut state.file_system,
+                    &mut state.errors,
+                    &mut state.journal,
                     state.left_panel.tabs,
                 ),
                 ..state.left_panel
@@ -45,6 +48,8 @@ fn create_symlink<TFileSystem: Clone + Debug + Default + FileSystem>(
                     panel.tab,
                     panel.path,
                     &mut state.file_system,
+                    &mut state.errors,
+                    &mut state.journal,
                     state.right_panel.tabs,
                 ),
                 ..state.right_panel
@@ -59,16 +64,28 @@ fn create_symlink_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     tab: TabIdx,
     path: PathBuf,
     file_system: &mut TFileSystem,
+    errors: &mut Vec<AppError>,
+    journal: &mut Journal,
     tabs: Vec<TabState<TFileSystem>>,
 ) -> Vec<TabState<TFileSystem>> {
     let mut result = Vec::<TabState<TFileSystem>>::new();
 
     for (idx, tab_state) in tabs.iter().enumerate() {
         if idx == tab {
-            match file_system.create_symlink(&symlink_path, &path) {
-                Ok(_) => result.push(tab_state.clone()),
+            match file_system.create_symlink(&path, &symlink_path) {
+                Ok(_) => {
+                    journal.record(JournalOperation::CreateSymlink {
+                        path: symlink_path.clone(),
+                        target: path.clone(),
+                    });
+                    result.push(tab_state.clone())
+                }
                 Err(err) => {
-                    eprintln!("{}", err);
+                    errors.push(AppError::new(
+                        FileOperation::CreateSymlink,
+                        vec![symlink_path.clone(), path.clone()],
+                        &err,
+                    ));
                     result.push(tab_state.clone())
                 }
             }
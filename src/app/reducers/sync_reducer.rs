@@ -0,0 +1,142 @@
+use std::{fmt::Debug, path::PathBuf};
+
+use crate::app::{
+    actions::SyncAction,
+    file_system::{trash::move_to_trash, FileSystem},
+    journal::JournalOperation,
+    state::{AppError, AppState, FileOperation, ModalType},
+    sync::{SyncEntry, SyncOperation, SyncPlan},
+};
+
+use super::{dir_size_reducer::forget_dir_sizes, reload_tabs_with_items};
+
+pub fn sync_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    sync_action: SyncAction,
+) -> AppState<TFileSystem> {
+    match sync_action {
+        SyncAction::Compare {
+            left_root,
+            right_root,
+        } => AppState {
+            sync: Some(SyncPlan::new(left_root, right_root)),
+            modal: Some(ModalType::SyncModal),
+            ..state
+        },
+        SyncAction::Compared {
+            left_root,
+            right_root,
+            entries,
+        } => set_entries(state, left_root, right_root, entries),
+        SyncAction::SetOperation { index, operation } => set_operation(state, index, operation),
+        SyncAction::Run => run_plan(state),
+        SyncAction::Cancel => AppState {
+            sync: None,
+            modal: None,
+            ..state
+        },
+    }
+}
+
+fn set_entries<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    left_root: PathBuf,
+    right_root: PathBuf,
+    entries: Result<Vec<SyncEntry>, AppError>,
+) -> AppState<TFileSystem> {
+    // The result of a comparison the user has already cancelled.
+    let plan = match state.sync.as_mut() {
+        Some(plan) if plan.left_root == left_root && plan.right_root == right_root => plan,
+        _ => return state,
+    };
+    match entries {
+        Ok(entries) => {
+            plan.entries = Some(entries);
+            state
+        }
+        Err(err) => {
+            state.errors.push(err);
+            AppState {
+                sync: None,
+                modal: None,
+                ..state
+            }
+        }
+    }
+}
+
+fn set_operation<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    index: usize,
+    operation: SyncOperation,
+) -> AppState<TFileSystem> {
+    let entry = state
+        .sync
+        .as_mut()
+        .and_then(|plan| plan.entries.as_mut())
+        .and_then(|entries| entries.get_mut(index));
+    if let Some(entry) = entry {
+        if entry.operations().contains(&operation) {
+            entry.operation = operation;
+        }
+    }
+    state
+}
+
+/// Moves the entries marked for deletion to the trash. The copies are run as
+/// jobs started by the middleware, so their progress is shown afterwards.
+fn run_plan<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+) -> AppState<TFileSystem> {
+    let plan = match state.sync.take() {
+        Some(plan) => plan,
+        None => return state,
+    };
+    let entries = plan.entries.clone().unwrap_or_default();
+
+    let mut operations = Vec::new();
+    let mut paths = Vec::new();
+    for entry in entries
+        .iter()
+        .filter(|entry| entry.operation == SyncOperation::Delete)
+    {
+        let path = if entry.left.is_some() {
+            plan.left_path(entry)
+        } else {
+            plan.right_path(entry)
+        };
+        match move_to_trash(&mut state.file_system, &path) {
+            Ok(entry) => operations.push(JournalOperation::MoveToTrash { entry }),
+            Err(err) => {
+                state.errors.push(AppError::new(
+                    FileOperation::MoveToTrash,
+                    vec![path.clone()],
+                    &err,
+                ));
+            }
+        }
+        paths.push(path);
+    }
+    if !operations.is_empty() {
+        state.journal.record(JournalOperation::Batch { operations });
+    }
+
+    let copies = entries.iter().any(|entry| {
+        matches!(
+            entry.operation,
+            SyncOperation::CopyToRight | SyncOperation::CopyToLeft
+        )
+    });
+    let state = AppState {
+        modal: if copies {
+            Some(ModalType::JobsModal { job_id: None })
+        } else {
+            None
+        },
+        ..state
+    };
+    let state = paths
+        .iter()
+        .fold(state, |state, path| forget_dir_sizes(state, path));
+    reload_tabs_with_items(state, &paths)
+}
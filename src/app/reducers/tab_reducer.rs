@@ -1,11 +1,16 @@
 use crate::app::{
     actions::{PanelSide, TabAction},
+    config::{
+        tab_config::{SortEnum, SortKey},
+        Config,
+    },
     file_system::FileSystem,
     state::{AppState, PanelState, TabState},
 };
-use std::{fmt::Debug, path::PathBuf};
-
-use super::reload_tab_with_path;
+use std::{
+    fmt::Debug,
+    path::{Path, PathBuf},
+};
 
 pub fn tab_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
     state: AppState<TFileSystem>,
@@ -18,6 +23,75 @@ pub fn tab_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
         TabAction::SelectPrev => select_multiple_prev(state),
         TabAction::ClearSelection => clear_selections(state),
         TabAction::ReloadTab { panel_side, path } => reload_state_tab(state, panel_side, path),
+        TabAction::OpenDiskUsage { panel_side, path } => open_disk_usage(state, panel_side, path),
+        TabAction::SetSort {
+            panel_side,
+            key,
+            order,
+        } => set_sort(state, panel_side, key, order),
+        TabAction::ToggleDetailView { panel_side } => toggle_detail_view(state, panel_side),
+    }
+}
+
+fn set_sort<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    panel_side: PanelSide,
+    key: SortKey,
+    order: SortEnum,
+) -> AppState<TFileSystem> {
+    let panel = match panel_side {
+        PanelSide::Left => &mut state.left_panel,
+        PanelSide::Right => &mut state.right_panel,
+    };
+    if let Some(tab) = panel.tabs.get_mut(panel.current_tab) {
+        let sort = tab.sort.by(key, order);
+        tab.set_sort(sort);
+    }
+    state
+}
+
+fn toggle_detail_view<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    panel_side: PanelSide,
+) -> AppState<TFileSystem> {
+    let panel = match panel_side {
+        PanelSide::Left => &mut state.left_panel,
+        PanelSide::Right => &mut state.right_panel,
+    };
+    if let Some(tab) = panel.tabs.get_mut(panel.current_tab) {
+        tab.detail_view = !tab.detail_view;
+    }
+    state
+}
+
+fn open_disk_usage<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    panel_side: PanelSide,
+    path: PathBuf,
+) -> AppState<TFileSystem> {
+    let tab = TabState::with_disk_usage(path.as_path(), &state.file_system, &state.config);
+    match panel_side {
+        PanelSide::Left => AppState {
+            left_panel: open_tab_in_panel(state.left_panel, tab),
+            ..state
+        },
+        PanelSide::Right => AppState {
+            right_panel: open_tab_in_panel(state.right_panel, tab),
+            ..state
+        },
+    }
+}
+
+pub fn open_tab_in_panel<TFileSystem: Clone + Debug + Default + FileSystem>(
+    panel_state: PanelState<TFileSystem>,
+    tab: TabState<TFileSystem>,
+) -> PanelState<TFileSystem> {
+    let mut tabs = panel_state.tabs;
+    tabs.push(tab);
+    PanelState {
+        current_tab: tabs.len() - 1,
+        tabs,
+        ..panel_state
     }
 }
 
@@ -29,7 +103,7 @@ fn reload_state_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     match panel_side {
         PanelSide::Left => AppState {
             left_panel: PanelState {
-                tabs: reload_tab_with_path(
+                tabs: refresh_tabs_with_path(
                     path.as_ref(),
                     state.left_panel.tabs,
                     &state.file_system,
@@ -41,7 +115,7 @@ fn reload_state_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
         },
         PanelSide::Right => AppState {
             right_panel: PanelState {
-                tabs: reload_tab_with_path(
+                tabs: refresh_tabs_with_path(
                     path.as_ref(),
                     state.right_panel.tabs,
                     &state.file_system,
@@ -54,6 +128,26 @@ fn reload_state_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
     }
 }
 
+/// Reloads the tabs showing `tab_path` after it changed outside of the
+/// application. Tabs of a directory which is gone keep their items.
+fn refresh_tabs_with_path<TFileSystem: Clone + Debug + Default + FileSystem>(
+    tab_path: &Path,
+    tabs: Vec<TabState<TFileSystem>>,
+    file_system: &TFileSystem,
+    big_config: &Config,
+) -> Vec<TabState<TFileSystem>> {
+    let exists = file_system.exist(tab_path);
+    tabs.iter()
+        .map(|tab_state| {
+            if exists && tab_state.path == tab_path {
+                tab_state.refresh(file_system, big_config)
+            } else {
+                tab_state.clone()
+            }
+        })
+        .collect()
+}
+
 fn clear_selections<TFileSystem: Clone + Debug + Default + FileSystem>(
     state: AppState<TFileSystem>,
 ) -> AppState<TFileSystem> {
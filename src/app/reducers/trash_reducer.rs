@@ -0,0 +1,203 @@
+use std::{fmt::Debug, path::PathBuf};
+
+use crate::app::{
+    actions::{PanelInfo, PanelSide, TrashAction},
+    config::Config,
+    file_system::{
+        trash::{purge_from_trash, restore_from_trash},
+        FileSystem,
+    },
+    state::{AppError, AppState, FileOperation, PanelState, TabIdx, TabState},
+};
+
+use super::reload_tab_with_path;
+
+pub fn trash_reducer<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    trash_action: TrashAction,
+) -> AppState<TFileSystem> {
+    match trash_action {
+        TrashAction::Open { panel_side } => open_trash(state, panel_side),
+        TrashAction::Restore { panel } => restore_item(state, panel),
+        TrashAction::Purge { panel } => purge_item(state, panel),
+    }
+}
+
+fn open_trash<TFileSystem: Clone + Debug + Default + FileSystem>(
+    state: AppState<TFileSystem>,
+    panel_side: PanelSide,
+) -> AppState<TFileSystem> {
+    match panel_side {
+        PanelSide::Left => AppState {
+            left_panel: open_trash_in_panel(state.left_panel, &state.file_system, &state.config),
+            ..state
+        },
+        PanelSide::Right => AppState {
+            right_panel: open_trash_in_panel(state.right_panel, &state.file_system, &state.config),
+            ..state
+        },
+    }
+}
+
+fn open_trash_in_panel<TFileSystem: Clone + Debug + Default + FileSystem>(
+    panel_state: PanelState<TFileSystem>,
+    file_system: &TFileSystem,
+    big_config: &Config,
+) -> PanelState<TFileSystem> {
+    let mut tabs = panel_state.tabs;
+    let current_tab = match tabs.iter().position(|tab| tab.is_trash()) {
+        Some(idx) => {
+            tabs[idx] = tabs[idx].refresh(file_system, big_config);
+            idx
+        }
+        None => {
+            tabs.push(TabState::with_trash(file_system, big_config));
+            tabs.len() - 1
+        }
+    };
+
+    PanelState {
+        tabs,
+        current_tab,
+        ..panel_state
+    }
+}
+
+fn restore_item<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    panel: PanelInfo,
+) -> AppState<TFileSystem> {
+    let restored_path = match panel.side {
+        PanelSide::Left => restore_in_tab(
+            panel.path.clone(),
+            panel.tab,
+            &state.left_panel.tabs,
+            &mut state.file_system,
+            &mut state.errors,
+        ),
+        PanelSide::Right => restore_in_tab(
+            panel.path.clone(),
+            panel.tab,
+            &state.right_panel.tabs,
+            &mut state.file_system,
+            &mut state.errors,
+        ),
+    };
+
+    match restored_path.as_ref().and_then(|path| path.parent()) {
+        Some(parent) => AppState {
+            left_panel: PanelState {
+                tabs: reload_tab_with_path(
+                    parent,
+                    reload_trash_tabs(state.left_panel.tabs, &state.file_system, &state.config),
+                    &state.file_system,
+                    &state.config,
+                ),
+                ..state.left_panel
+            },
+            right_panel: PanelState {
+                tabs: reload_tab_with_path(
+                    parent,
+                    reload_trash_tabs(state.right_panel.tabs, &state.file_system, &state.config),
+                    &state.file_system,
+                    &state.config,
+                ),
+                ..state.right_panel
+            },
+            ..state
+        },
+        None => state,
+    }
+}
+
+fn purge_item<TFileSystem: Clone + Debug + Default + FileSystem>(
+    mut state: AppState<TFileSystem>,
+    panel: PanelInfo,
+) -> AppState<TFileSystem> {
+    match panel.side {
+        PanelSide::Left => purge_in_tab(
+            panel.path,
+            panel.tab,
+            &state.left_panel.tabs,
+            &mut state.file_system,
+            &mut state.errors,
+        ),
+        PanelSide::Right => purge_in_tab(
+            panel.path,
+            panel.tab,
+            &state.right_panel.tabs,
+            &mut state.file_system,
+            &mut state.errors,
+        ),
+    };
+
+    AppState {
+        left_panel: PanelState {
+            tabs: reload_trash_tabs(state.left_panel.tabs, &state.file_system, &state.config),
+            ..state.left_panel
+        },
+        right_panel: PanelState {
+            tabs: reload_trash_tabs(state.right_panel.tabs, &state.file_system, &state.config),
+            ..state.right_panel
+        },
+        ..state
+    }
+}
+
+fn restore_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
+    path: PathBuf,
+    tab: TabIdx,
+    tabs: &[TabState<TFileSystem>],
+    file_system: &mut TFileSystem,
+    errors: &mut Vec<AppError>,
+) -> Option<PathBuf> {
+    let entry = tabs.get(tab)?.trash_entry(path.as_path())?;
+    match restore_from_trash(file_system, entry) {
+        Ok(restored_path) => Some(restored_path),
+        Err(err) => {
+            errors.push(AppError::new(
+                FileOperation::RestoreFromTrash,
+                vec![entry.trashed_path.clone(), entry.original_path.clone()],
+                &err,
+            ));
+            None
+        }
+    }
+}
+
+fn purge_in_tab<TFileSystem: Clone + Debug + Default + FileSystem>(
+    path: PathBuf,
+    tab: TabIdx,
+    tabs: &[TabState<TFileSystem>],
+    file_system: &mut TFileSystem,
+    errors: &mut Vec<AppError>,
+) {
+    if let Some(entry) = tabs
+        .get(tab)
+        .and_then(|tab| tab.trash_entry(path.as_path()))
+    {
+        if let Err(err) = purge_from_trash(file_system, entry) {
+            errors.push(AppError::new(
+                FileOperation::PurgeFromTrash,
+                vec![entry.trashed_path.clone()],
+                &err,
+            ));
+        }
+    }
+}
+
+fn reload_trash_tabs<TFileSystem: Clone + Debug + Default + FileSystem>(
+    tabs: Vec<TabState<TFileSystem>>,
+    file_system: &TFileSystem,
+    big_config: &Config,
+) -> Vec<TabState<TFileSystem>> {
+    tabs.into_iter()
+        .map(|tab_state| {
+            if tab_state.is_trash() {
+                tab_state.reload(file_system, big_config)
+            } else {
+                tab_state
+            }
+        })
+        .collect()
+}
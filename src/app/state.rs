@@ -1,12 +1,33 @@
-use std::fmt::Debug;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+use std::io;
 use std::path::{Path, PathBuf};
 
 use tui::widgets::ListState;
 
+use crate::core::config::FilterMode;
+
 use super::{
     actions::PanelSide,
-    config::{icon_cfg::IconsConfig, Config},
-    file_system::{file_system_item::FileSystemItem, DirInfo, FileSystem},
+    bulk_rename::BulkRename,
+    compare::{Comparison, Difference},
+    config::{icon_cfg::IconsConfig, tab_config::TabSort, Config},
+    dir_size::DirSizes,
+    file_system::{
+        file_system_item::FileSystemItem,
+        functions::{
+            map_path_to_file_system_item, map_paths_to_file_system_items, sort_file_system_items,
+        },
+        trash::{home_trash_dir, list_trash, TrashEntry},
+        FileSystem,
+    },
+    filter::{match_name, FilterMatch},
+    find::{FindQuery, FindSearch},
+    grep::GrepResults,
+    jobs::{Job, JobId},
+    journal::Journal,
+    listing::{DirListing, SYNC_LISTING_LIMIT},
+    sync::SyncPlan,
 };
 
 pub type TabIdx = usize;
@@ -24,13 +45,23 @@ pub struct AppState<TFileSystem: Clone + Debug + Default + FileSystem> {
     pub app_exit: bool,
     pub config: Config,
     pub child_program: Option<ChildProgramDesc>,
+    pub bulk_rename: Option<BulkRename>,
     pub modal: Option<ModalType>,
+    pub errors: Vec<AppError>,
+    pub journal: Journal,
+    pub jobs: Vec<Job>,
+    pub conflicts: Vec<Job>,
+    pub dir_sizes: DirSizes,
+    pub comparison: Option<Comparison>,
+    pub sync: Option<SyncPlan>,
+    pub grep: Option<GrepResults>,
     pub file_system: TFileSystem,
 }
 
 impl<TFileSystem: Clone + Debug + Default + FileSystem> AppState<TFileSystem> {
-    pub fn new(config: Config, file_system: TFileSystem) -> Self {
-        let mut state = AppState::set_default(&config);
+    pub fn new(mut config: Config, file_system: TFileSystem) -> Self {
+        let mut state = AppState::set_default(&config, &file_system);
+        state.errors = std::mem::take(&mut config.errors);
         state.file_system = file_system;
         state.config = config;
 
@@ -46,20 +77,38 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> Default for AppState<TFi
             app_exit: false,
             config: Config::default(),
             child_program: None,
+            bulk_rename: None,
             modal: None,
+            errors: Vec::new(),
+            journal: Journal::default(),
+            jobs: Vec::new(),
+            conflicts: Vec::new(),
+            dir_sizes: DirSizes::default(),
+            comparison: None,
+            sync: None,
+            grep: None,
             file_system: TFileSystem::default(),
         }
     }
 }
 impl<TFileSystem: Clone + Debug + Default + FileSystem> AppState<TFileSystem> {
-    fn set_default(config: &Config) -> Self {
+    fn set_default(config: &Config, file_system: &TFileSystem) -> Self {
         AppState {
-            left_panel: PanelState::set_default(config),
-            right_panel: PanelState::set_default(config),
+            left_panel: PanelState::set_default(config, file_system),
+            right_panel: PanelState::set_default(config, file_system),
             app_exit: false,
             config: Config::default(),
             child_program: None,
+            bulk_rename: None,
             modal: None,
+            errors: Vec::new(),
+            journal: Journal::default(),
+            jobs: Vec::new(),
+            conflicts: Vec::new(),
+            dir_sizes: DirSizes::default(),
+            comparison: None,
+            sync: None,
+            grep: None,
             file_system: TFileSystem::default(),
         }
     }
@@ -84,9 +133,9 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> Default for PanelState<T
     }
 }
 impl<TFileSystem: Clone + Debug + Default + FileSystem> PanelState<TFileSystem> {
-    fn set_default(config: &Config) -> Self {
+    fn set_default(config: &Config, file_system: &TFileSystem) -> Self {
         PanelState {
-            tabs: vec![TabState::set_default(config)],
+            tabs: vec![TabState::set_default(config, file_system)],
             is_focused: false,
             current_tab: 0,
             marker: std::marker::PhantomData,
@@ -103,25 +152,62 @@ pub struct TabState<TFileSystem: Clone + Debug + Default + FileSystem> {
     pub tab_state: ListState,
     pub search_mode: bool,
     pub phrase: String,
+    pub filter_mode: FilterMode,
+    pub sort: TabSort,
+    /// Shows the columns of the detail view next to the names.
+    pub detail_view: bool,
+    pub kind: TabKind,
+    /// Set while the items of a big directory are read in the background.
+    pub listing: Option<DirListing>,
+    /// How the items differ from the other panel while they are compared.
+    pub differences: HashMap<PathBuf, Difference>,
     pub marker: std::marker::PhantomData<TFileSystem>,
 }
 
+#[derive(Clone, Debug)]
+pub enum TabKind {
+    Directory,
+    Trash {
+        entries: Vec<TrashEntry>,
+    },
+    /// Items ranked by their size, `total` is the sum of their sizes.
+    DiskUsage {
+        total: u64,
+    },
+    /// Items found below `path`, they keep coming while `searching`.
+    Find {
+        query: FindQuery,
+        search: FindSearch,
+        searching: bool,
+    },
+}
+
 impl<TFileSystem: Clone + Debug + Default + FileSystem> Default for TabState<TFileSystem> {
     fn default() -> Self {
         TabState::with_dir(&Path::new("."), &TFileSystem::default(), &Config::default())
     }
 }
 impl<TFileSystem: Clone + Debug + Default + FileSystem> TabState<TFileSystem> {
-    fn set_default(config: &Config) -> Self {
-        TabState::with_dir(&Path::new("."), &TFileSystem::default(), &config)
+    fn set_default(config: &Config, file_system: &TFileSystem) -> Self {
+        TabState::with_dir(Path::new("."), file_system, config)
     }
 }
 
 impl<TFileSystem: Clone + Debug + Default + FileSystem> TabState<TFileSystem> {
     //pub fn with_dir(dir_path: &Path, file_system: &TFileSystem, icons: &IconsConfig) -> Self {
     pub fn with_dir(dir_path: &Path, file_system: &TFileSystem, big_config: &Config) -> Self {
-        let dir_info = DirInfo::new(&dir_path).unwrap();
-        let items = file_system.list_dir(&dir_info.path, &big_config);
+        let dir_info = file_system.get_dir_info(dir_path).unwrap();
+        let paths = file_system.read_dir(&dir_info.path).unwrap_or_default();
+        // Reading the metadata of every item of a big directory takes long,
+        // its items are streamed in once the tab is shown.
+        let (items, listing) = if paths.len() > SYNC_LISTING_LIMIT {
+            (Vec::new(), Some(DirListing::default()))
+        } else {
+            (
+                map_paths_to_file_system_items(file_system, paths, big_config),
+                None,
+            )
+        };
         TabState {
             name: dir_info.name.clone(),
             icon: big_config.icons.get_dir_icon(dir_info.name.clone()),
@@ -131,24 +217,366 @@ impl<TFileSystem: Clone + Debug + Default + FileSystem> TabState<TFileSystem> {
             tab_state: ListState::default(),
             search_mode: false,
             phrase: String::from(""),
+            filter_mode: big_config.core_cfg.filter_mode,
+            sort: big_config.tab_config.sort(),
+            detail_view: big_config.core_cfg.detail_view,
+            kind: TabKind::Directory,
+            listing,
+            differences: HashMap::new(),
             marker: std::marker::PhantomData,
         }
     }
 
+    pub fn with_trash(file_system: &TFileSystem, big_config: &Config) -> Self {
+        let entries = list_trash(file_system);
+        let mut items: Vec<FileSystemItem> = entries
+            .iter()
+            .map(|entry| {
+                map_path_to_file_system_item(
+                    file_system,
+                    entry.trashed_path.clone(),
+                    &big_config.icons,
+                )
+            })
+            .collect();
+        sort_file_system_items(&mut items, &big_config.tab_config.sort());
+        let path = home_trash_dir(file_system)
+            .map(|trash_dir| trash_dir.join("files"))
+            .unwrap_or_default();
+        TabState {
+            name: String::from("Trash"),
+            icon: big_config.icons.get_dir_icon(String::from("Trash")),
+            path,
+            items,
+            selected: Vec::new(),
+            tab_state: ListState::default(),
+            search_mode: false,
+            phrase: String::from(""),
+            filter_mode: big_config.core_cfg.filter_mode,
+            sort: big_config.tab_config.sort(),
+            detail_view: big_config.core_cfg.detail_view,
+            kind: TabKind::Trash { entries },
+            listing: None,
+            differences: HashMap::new(),
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Lists `dir_path` like `with_dir` but ranks the items by their size,
+    /// directories get theirs from background scans.
+    pub fn with_disk_usage(
+        dir_path: &Path,
+        file_system: &TFileSystem,
+        big_config: &Config,
+    ) -> Self {
+        let mut tab = TabState::with_dir(dir_path, file_system, big_config);
+        tab.name = format!("Disk usage: {}", tab.name);
+        tab.kind = TabKind::DiskUsage { total: 0 };
+        tab.rank_by_size();
+        tab
+    }
+
+    /// Empty tab for the results of a search below `root`.
+    pub fn with_find_results(
+        root: &Path,
+        query: FindQuery,
+        search: FindSearch,
+        big_config: &Config,
+    ) -> Self {
+        TabState {
+            name: find_tab_name(&query, true),
+            icon: big_config.icons.get_dir_icon(String::from("default")),
+            path: root.to_path_buf(),
+            items: Vec::new(),
+            selected: Vec::new(),
+            tab_state: ListState::default(),
+            search_mode: false,
+            phrase: String::from(""),
+            filter_mode: big_config.core_cfg.filter_mode,
+            sort: big_config.tab_config.sort(),
+            detail_view: big_config.core_cfg.detail_view,
+            kind: TabKind::Find {
+                query,
+                search,
+                searching: true,
+            },
+            listing: None,
+            differences: HashMap::new(),
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn reload(&self, file_system: &TFileSystem, big_config: &Config) -> Self {
+        let mut tab = match self.kind {
+            TabKind::Directory => TabState::with_dir(self.path.as_path(), file_system, big_config),
+            TabKind::Trash { .. } => TabState::with_trash(file_system, big_config),
+            TabKind::DiskUsage { .. } => {
+                TabState::with_disk_usage(self.path.as_path(), file_system, big_config)
+            }
+            TabKind::Find { .. } => self.reload_find_results(file_system, big_config),
+        };
+        tab.set_sort(self.sort.clone());
+        tab.detail_view = self.detail_view;
+        tab
+    }
+
+    /// Reloads the items after they changed outside of the application,
+    /// keeping the cursor on the same item, the selection and the filter.
+    pub fn refresh(&self, file_system: &TFileSystem, big_config: &Config) -> Self {
+        let mut tab = TabState {
+            phrase: self.phrase.clone(),
+            search_mode: self.search_mode,
+            ..self.reload(file_system, big_config)
+        };
+        if let Some(listing) = tab.listing.as_mut() {
+            // The old items stay until all the new ones are read.
+            listing.reloaded = Some(Vec::new());
+            tab.items = self.items.clone();
+            tab.selected = self.selected.clone();
+            tab.tab_state = self.tab_state.clone();
+            return tab;
+        }
+        let (cursor, cursor_path) = self.cursor();
+        tab.keep_position(&self.selected, cursor, cursor_path);
+        tab
+    }
+
+    /// Index of the item under the cursor among the filtered items, and its
+    /// path.
+    fn cursor(&self) -> (Option<usize>, Option<PathBuf>) {
+        let cursor = self.tab_state.selected();
+        let cursor_path =
+            cursor.and_then(|idx| self.filtered_items().get(idx).map(|item| item.get_path()));
+        (cursor, cursor_path)
+    }
+
+    /// Selects the items of `selected` which are still listed and moves the
+    /// cursor back to the item at `cursor_path`, or near `cursor` when it is
+    /// gone.
+    fn keep_position(
+        &mut self,
+        selected: &[FileSystemItem],
+        cursor: Option<usize>,
+        cursor_path: Option<PathBuf>,
+    ) {
+        self.selected = selected
+            .iter()
+            .filter_map(|selected| {
+                self.items
+                    .iter()
+                    .find(|item| item.get_path() == selected.get_path())
+                    .cloned()
+            })
+            .collect();
+
+        let items = self.filtered_items();
+        let new_cursor = cursor_path
+            .and_then(|path| items.iter().position(|item| item.get_path() == path))
+            .or_else(|| cursor.map(|idx| idx.min(items.len().saturating_sub(1))))
+            .filter(|_| !items.is_empty());
+        self.tab_state.select(new_cursor);
+    }
+
+    /// Adds items streamed in by the listing of this tab, the cursor stays
+    /// on the same item as they are sorted in.
+    pub fn add_listed_items(&mut self, items: Vec<FileSystemItem>) {
+        if let Some(reloaded) = self
+            .listing
+            .as_mut()
+            .and_then(|listing| listing.reloaded.as_mut())
+        {
+            reloaded.extend(items);
+            return;
+        }
+        let (cursor, cursor_path) = self.cursor();
+        let selected = std::mem::take(&mut self.selected);
+        self.items.extend(items);
+        self.sort_items();
+        self.keep_position(&selected, cursor, cursor_path);
+    }
+
+    /// Marks the listing of this tab as done, a reload replaces the old
+    /// items with the new ones now.
+    pub fn finish_listing(&mut self) {
+        let reloaded = self.listing.take().and_then(|listing| listing.reloaded);
+        if let Some(reloaded) = reloaded {
+            let (cursor, cursor_path) = self.cursor();
+            let selected = std::mem::take(&mut self.selected);
+            self.items = reloaded;
+            self.sort_items();
+            self.keep_position(&selected, cursor, cursor_path);
+        }
+    }
+
+    pub fn listing_search(&self) -> Option<&FindSearch> {
+        self.listing
+            .as_ref()
+            .and_then(|listing| listing.search.as_ref())
+    }
+
+    /// Whether the items of this tab still have to be listed.
+    pub fn is_listing_pending(&self) -> bool {
+        self.listing
+            .as_ref()
+            .is_some_and(|listing| listing.search.is_none())
+    }
+
+    /// Sorts the items the way `sort` says, the cursor stays on the same
+    /// item.
+    pub fn set_sort(&mut self, sort: TabSort) {
+        if self.sort == sort {
+            return;
+        }
+        self.sort = sort;
+        let (cursor, cursor_path) = self.cursor();
+        let selected = std::mem::take(&mut self.selected);
+        self.sort_items();
+        self.keep_position(&selected, cursor, cursor_path);
+    }
+
+    /// Whether the items follow `sort`, disk usage tabs rank them by size and
+    /// find results stay in the order they were found.
+    pub fn is_sortable(&self) -> bool {
+        matches!(self.kind, TabKind::Directory | TabKind::Trash { .. })
+    }
+
+    fn sort_items(&mut self) {
+        match self.kind {
+            TabKind::DiskUsage { .. } => self.rank_by_size(),
+            TabKind::Find { .. } => {}
+            _ => sort_file_system_items(&mut self.items, &self.sort),
+        }
+    }
+
+    /// Reads the found items again, dropping the ones which are gone.
+    fn reload_find_results(&self, file_system: &TFileSystem, big_config: &Config) -> Self {
+        let items = self
+            .items
+            .iter()
+            .map(|item| item.get_path())
+            .filter(|path| file_system.exist(path))
+            .map(|path| map_path_to_file_system_item(file_system, path, &big_config.icons))
+            .collect();
+        TabState {
+            items,
+            selected: Vec::new(),
+            tab_state: ListState::default(),
+            ..self.clone()
+        }
+    }
+
+    /// Adds items found by the search of this tab.
+    pub fn add_find_results(
+        &mut self,
+        paths: Vec<PathBuf>,
+        file_system: &TFileSystem,
+        big_config: &Config,
+    ) {
+        self.items.extend(
+            paths
+                .into_iter()
+                .map(|path| map_path_to_file_system_item(file_system, path, &big_config.icons)),
+        );
+    }
+
+    /// Marks the search of this tab as done.
+    pub fn finish_find(&mut self) {
+        if let TabKind::Find {
+            ref query,
+            ref mut searching,
+            ..
+        } = self.kind
+        {
+            *searching = false;
+            self.name = find_tab_name(query, false);
+        }
+    }
+
+    pub fn find_search(&self) -> Option<&FindSearch> {
+        match &self.kind {
+            TabKind::Find { search, .. } => Some(search),
+            _ => None,
+        }
+    }
+
+    pub fn is_find(&self) -> bool {
+        matches!(self.kind, TabKind::Find { .. })
+    }
+
+    /// Lists `dir_path` in a tab of the same kind, disk usage tabs stay in
+    /// that view when moving through directories.
+    pub fn open_dir(
+        &self,
+        dir_path: &Path,
+        file_system: &TFileSystem,
+        big_config: &Config,
+    ) -> Self {
+        let mut tab = match self.kind {
+            TabKind::DiskUsage { .. } => {
+                TabState::with_disk_usage(dir_path, file_system, big_config)
+            }
+            _ => TabState::with_dir(dir_path, file_system, big_config),
+        };
+        tab.set_sort(self.sort.clone());
+        tab.detail_view = self.detail_view;
+        tab
+    }
+
+    /// Sorts a disk usage tab from the largest item down and updates its
+    /// total.
+    pub fn rank_by_size(&mut self) {
+        if let TabKind::DiskUsage { ref mut total } = self.kind {
+            self.items
+                .sort_by_key(|item| std::cmp::Reverse(item.get_size()));
+            *total = self.items.iter().map(|item| item.get_size()).sum();
+        }
+    }
+
+    pub fn is_disk_usage(&self) -> bool {
+        matches!(self.kind, TabKind::DiskUsage { .. })
+    }
+
+    pub fn is_trash(&self) -> bool {
+        matches!(self.kind, TabKind::Trash { .. })
+    }
+
+    pub fn trash_entry(&self, path: &Path) -> Option<&TrashEntry> {
+        match &self.kind {
+            TabKind::Trash { entries } => entries.iter().find(|entry| entry.trashed_path == path),
+            _ => None,
+        }
+    }
+
+    /// Items matching the filter phrase, the best matches first when the
+    /// filter is fuzzy.
     pub fn filtered_items(&self) -> Vec<&FileSystemItem> {
         if self.phrase.is_empty() {
             self.items.iter().collect()
         } else {
-            self.items
+            let mut matches: Vec<(i64, &FileSystemItem)> = self
+                .items
                 .iter()
-                .filter(|item| {
-                    item.get_name()
-                        .to_lowercase()
-                        .contains(&self.phrase.to_lowercase())
+                .filter_map(|item| {
+                    self.filter_match(item)
+                        .map(|filter_match| (filter_match.score, item))
                 })
-                .collect()
+                .collect();
+            matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            matches.into_iter().map(|(_, item)| item).collect()
         }
     }
+
+    pub fn filter_match(&self, item: &FileSystemItem) -> Option<FilterMatch> {
+        match_name(self.filter_mode, &self.phrase, &item.get_name())
+    }
+}
+
+fn find_tab_name(query: &FindQuery, searching: bool) -> String {
+    if searching {
+        format!("Find: {} ...", query)
+    } else {
+        format!("Find: {}", query)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -158,6 +586,12 @@ pub enum ModalType {
         panel_tab: TabIdx,
         item: FileSystemItem,
     },
+    MultiRenameModal {
+        panel_side: PanelSide,
+        panel_tab: TabIdx,
+        dir: PathBuf,
+        items: Vec<PathBuf>,
+    },
     CreateModal {
         item_index: Option<usize>,
         panel_side: PanelSide,
@@ -169,5 +603,107 @@ pub enum ModalType {
         panel_side: PanelSide,
         panel_tab: TabIdx,
         path: PathBuf,
+        permanent: bool,
+    },
+    PurgeFromTrash {
+        panel_side: PanelSide,
+        panel_tab: TabIdx,
+        entries: Vec<TrashEntry>,
+    },
+    JobsModal {
+        job_id: Option<JobId>,
+    },
+    ConflictModal,
+    SyncModal,
+    FindModal {
+        panel_side: PanelSide,
+        path: PathBuf,
+    },
+    GrepModal {
+        path: PathBuf,
     },
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileOperation {
+    CreateFile,
+    CreateDir,
+    CreateSymlink,
+    DeleteFile,
+    DeleteDir,
+    MoveToTrash,
+    RestoreFromTrash,
+    PurgeFromTrash,
+    RenameFile,
+    RenameDir,
+    CopyFile,
+    CopyDir,
+    BulkRename,
+    RunProgram,
+    Sync,
+    Undo,
+    Redo,
+    ReadConfig,
+}
+
+impl Display for FileOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let description = match self {
+            FileOperation::CreateFile => "create file",
+            FileOperation::CreateDir => "create directory",
+            FileOperation::CreateSymlink => "create symlink",
+            FileOperation::DeleteFile => "delete file",
+            FileOperation::DeleteDir => "delete directory",
+            FileOperation::MoveToTrash => "move to trash",
+            FileOperation::RestoreFromTrash => "restore from trash",
+            FileOperation::PurgeFromTrash => "remove from trash",
+            FileOperation::RenameFile => "rename file",
+            FileOperation::RenameDir => "rename directory",
+            FileOperation::CopyFile => "copy file",
+            FileOperation::CopyDir => "copy directory",
+            FileOperation::BulkRename => "rename items in",
+            FileOperation::RunProgram => "run",
+            FileOperation::Sync => "synchronize",
+            FileOperation::Undo => "undo",
+            FileOperation::Redo => "redo",
+            FileOperation::ReadConfig => "read config",
+        };
+        write!(f, "{}", description)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AppError {
+    pub operation: FileOperation,
+    pub paths: Vec<PathBuf>,
+    pub kind: io::ErrorKind,
+    pub message: String,
+}
+
+impl AppError {
+    pub fn new(operation: FileOperation, paths: Vec<PathBuf>, err: &io::Error) -> Self {
+        AppError {
+            operation,
+            paths,
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let paths: Vec<String> = self
+            .paths
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect();
+        write!(
+            f,
+            "Cannot {} {}: {}",
+            self.operation,
+            paths.join(" -> "),
+            self.message
+        )
+    }
+}
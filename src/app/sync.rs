@@ -0,0 +1,237 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Display},
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::Sender,
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::{
+    actions::{FileManagerActions, SyncAction},
+    file_system::FileSystem,
+    state::{AppError, FileOperation},
+};
+
+/// What synchronizing does with one entry of the plan.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncOperation {
+    CopyToRight,
+    CopyToLeft,
+    /// Removes the only copy of the entry, moving it to the trash.
+    Delete,
+    Skip,
+}
+
+impl Display for SyncOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncOperation::CopyToRight => write!(f, " -> "),
+            SyncOperation::CopyToLeft => write!(f, " <- "),
+            SyncOperation::Delete => write!(f, " del"),
+            SyncOperation::Skip => write!(f, "  = "),
+        }
+    }
+}
+
+/// The item found on one side of the synchronized trees.
+#[derive(Clone, Debug)]
+pub struct SyncItem {
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: SystemTime,
+}
+
+/// An item which is missing or differs on one of the sides, with the
+/// operation which resolves it.
+#[derive(Clone, Debug)]
+pub struct SyncEntry {
+    /// Path below both of the synchronized directories.
+    pub relative: PathBuf,
+    pub left: Option<SyncItem>,
+    pub right: Option<SyncItem>,
+    pub operation: SyncOperation,
+}
+
+impl SyncEntry {
+    /// Operations which make sense for the entry. An item is never copied
+    /// over an item of another type and only an item missing on the other
+    /// side can be deleted.
+    pub fn operations(&self) -> Vec<SyncOperation> {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) if left.is_dir != right.is_dir => vec![SyncOperation::Skip],
+            (Some(_), Some(_)) => vec![
+                SyncOperation::CopyToRight,
+                SyncOperation::CopyToLeft,
+                SyncOperation::Skip,
+            ],
+            (Some(_), None) => vec![
+                SyncOperation::CopyToRight,
+                SyncOperation::Delete,
+                SyncOperation::Skip,
+            ],
+            (None, Some(_)) => vec![
+                SyncOperation::CopyToLeft,
+                SyncOperation::Delete,
+                SyncOperation::Skip,
+            ],
+            (None, None) => vec![SyncOperation::Skip],
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.left
+            .as_ref()
+            .or(self.right.as_ref())
+            .is_some_and(|item| item.is_dir)
+    }
+
+    /// Why the entry is part of the plan.
+    pub fn reason(&self) -> &'static str {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) if left.is_dir != right.is_dir => "different types",
+            (Some(left), Some(right)) => match seconds(left.modified).cmp(&seconds(right.modified))
+            {
+                std::cmp::Ordering::Greater => "newer on the left",
+                std::cmp::Ordering::Less => "newer on the right",
+                std::cmp::Ordering::Equal => "different sizes",
+            },
+            (Some(_), None) => "only on the left",
+            (None, Some(_)) => "only on the right",
+            (None, None) => "",
+        }
+    }
+}
+
+/// Synchronization of two directory trees, set up from the current tabs of
+/// both panels.
+#[derive(Clone, Debug)]
+pub struct SyncPlan {
+    pub left_root: PathBuf,
+    pub right_root: PathBuf,
+    /// `None` until the trees are compared.
+    pub entries: Option<Vec<SyncEntry>>,
+}
+
+impl SyncPlan {
+    pub fn new(left_root: PathBuf, right_root: PathBuf) -> Self {
+        SyncPlan {
+            left_root,
+            right_root,
+            entries: None,
+        }
+    }
+
+    pub fn left_path(&self, entry: &SyncEntry) -> PathBuf {
+        self.left_root.join(&entry.relative)
+    }
+
+    pub fn right_path(&self, entry: &SyncEntry) -> PathBuf {
+        self.right_root.join(&entry.relative)
+    }
+}
+
+/// Compares the trees on a worker thread and sends the proposed plan.
+pub fn compare_trees<TFileSystem: Clone + FileSystem + Send + 'static>(
+    file_system: TFileSystem,
+    left_root: PathBuf,
+    right_root: PathBuf,
+    sender: Sender<FileManagerActions>,
+) {
+    thread::spawn(move || {
+        let entries = build_plan(&file_system, &left_root, &right_root).map_err(|err| {
+            AppError::new(
+                FileOperation::Sync,
+                vec![left_root.clone(), right_root.clone()],
+                &err,
+            )
+        });
+        let _ = sender.send(FileManagerActions::Sync(SyncAction::Compared {
+            left_root,
+            right_root,
+            entries,
+        }));
+    });
+}
+
+/// Walks both trees and lists the items which are not the same on both
+/// sides. Directories found on one side only are listed as a whole, and
+/// files are the same when their sizes and modification times, to the
+/// second, match. The newer file is proposed to be copied over the older
+/// one.
+pub fn build_plan<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    left_root: &Path,
+    right_root: &Path,
+) -> io::Result<Vec<SyncEntry>> {
+    let mut entries = Vec::new();
+    let mut pending = vec![PathBuf::new()];
+    while let Some(relative) = pending.pop() {
+        let names = child_names(file_system, &left_root.join(&relative))?
+            .union(&child_names(file_system, &right_root.join(&relative))?)
+            .cloned()
+            .collect::<Vec<PathBuf>>();
+        for name in names {
+            let relative = relative.join(name);
+            let left = sync_item(file_system, &left_root.join(&relative));
+            let right = sync_item(file_system, &right_root.join(&relative));
+            let operation = match (&left, &right) {
+                (Some(left), Some(right)) if left.is_dir && right.is_dir => {
+                    pending.push(relative);
+                    continue;
+                }
+                (Some(left), Some(right)) if left.is_dir != right.is_dir => SyncOperation::Skip,
+                (Some(left), Some(right)) => {
+                    let left_modified = seconds(left.modified);
+                    let right_modified = seconds(right.modified);
+                    if left.size == right.size && left_modified == right_modified {
+                        continue;
+                    } else if left_modified > right_modified {
+                        SyncOperation::CopyToRight
+                    } else if left_modified < right_modified {
+                        SyncOperation::CopyToLeft
+                    } else {
+                        SyncOperation::Skip
+                    }
+                }
+                (Some(_), None) => SyncOperation::CopyToRight,
+                (None, Some(_)) => SyncOperation::CopyToLeft,
+                (None, None) => continue,
+            };
+            entries.push(SyncEntry {
+                relative,
+                left,
+                right,
+                operation,
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(entries)
+}
+
+fn child_names<TFileSystem: FileSystem>(
+    file_system: &TFileSystem,
+    dir: &Path,
+) -> io::Result<BTreeSet<PathBuf>> {
+    Ok(file_system
+        .read_dir(dir)?
+        .into_iter()
+        .filter_map(|child| child.file_name().map(PathBuf::from))
+        .collect())
+}
+
+fn sync_item<TFileSystem: FileSystem>(file_system: &TFileSystem, path: &Path) -> Option<SyncItem> {
+    file_system.metadata(path).ok().map(|metadata| SyncItem {
+        is_dir: metadata.is_dir(),
+        size: metadata.size,
+        modified: metadata.modified,
+    })
+}
+
+fn seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,435 @@
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::{mpsc::Sender, Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+use super::{
+    actions::{FileManagerActions, PanelSide, TabAction},
+    file_system::FileSystem,
+    state::AppState,
+};
+
+use self::inotify::{Inotify, InotifyEvent};
+
+/// How often directories without inotify are listed again.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Events closer together than this are handled as one change.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+/// Longest a steady stream of events, like a build writing files, delays the
+/// reload.
+const MAX_DELAY: Duration = Duration::from_millis(500);
+
+/// Names, modification times and sizes of the items of a polled directory,
+/// `None` when it can't be read.
+type Listing = Option<Vec<(PathBuf, SystemTime, u64)>>;
+
+#[derive(Default)]
+struct Watches {
+    /// Panels with a tab showing each watched directory.
+    sides: HashMap<PathBuf, Vec<PanelSide>>,
+    /// Directories watched by inotify by their watch descriptor, several
+    /// paths of the same directory share one.
+    descriptors: HashMap<i32, Vec<PathBuf>>,
+    /// Directories listed every `POLL_INTERVAL` with their last listing.
+    polled: HashMap<PathBuf, Listing>,
+}
+
+/// Watches the directories of all open tabs of both panels and reloads the
+/// tabs when items are created, deleted, renamed or modified in them. Uses
+/// inotify on Linux and falls back to polling where it is not available or
+/// runs out of watches. Find result tabs list items from many directories and
+/// are not watched.
+pub struct DirWatcher<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static> {
+    file_system: TFileSystem,
+    inotify: Option<Arc<Inotify>>,
+    watches: Arc<Mutex<Watches>>,
+}
+
+impl<TFileSystem: Clone + Debug + Default + FileSystem + Send + 'static> DirWatcher<TFileSystem> {
+    pub fn start(file_system: TFileSystem, sender: Sender<FileManagerActions>) -> Self {
+        let watcher = DirWatcher::with_polling(file_system, sender.clone());
+        match Inotify::new() {
+            Ok(inotify) => {
+                let inotify = Arc::new(inotify);
+                read_events(inotify.clone(), watcher.watches.clone(), sender);
+                DirWatcher {
+                    inotify: Some(inotify),
+                    ..watcher
+                }
+            }
+            Err(_) => watcher,
+        }
+    }
+
+    /// Watcher which only polls, for file systems inotify doesn't see.
+    pub fn with_polling(file_system: TFileSystem, sender: Sender<FileManagerActions>) -> Self {
+        let watches = Arc::new(Mutex::new(Watches::default()));
+        poll_dirs(file_system.clone(), watches.clone(), sender);
+        DirWatcher {
+            file_system,
+            inotify: None,
+            watches,
+        }
+    }
+
+    /// Follows the tabs as they are opened, closed and moved to other
+    /// directories.
+    pub fn watch_tabs(&self, state: &AppState<TFileSystem>) {
+        let mut sides: HashMap<PathBuf, Vec<PanelSide>> = HashMap::new();
+        for (side, panel) in [
+            (PanelSide::Left, &state.left_panel),
+            (PanelSide::Right, &state.right_panel),
+        ] {
+            for tab in panel.tabs.iter().filter(|tab| !tab.is_find()) {
+                let panel_sides = sides.entry(tab.path.clone()).or_default();
+                if !panel_sides.contains(&side) {
+                    panel_sides.push(side);
+                }
+            }
+        }
+
+        let mut watches = self.watches.lock().unwrap();
+        if watches.sides == sides {
+            return;
+        }
+        let removed: Vec<PathBuf> = watches
+            .sides
+            .keys()
+            .filter(|path| !sides.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in removed {
+            self.unwatch(&mut watches, &path);
+        }
+        let added: Vec<PathBuf> = sides
+            .keys()
+            .filter(|path| !watches.sides.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in added {
+            self.watch(&mut watches, path);
+        }
+        watches.sides = sides;
+    }
+
+    fn watch(&self, watches: &mut Watches, path: PathBuf) {
+        let descriptor = self
+            .inotify
+            .as_ref()
+            .and_then(|inotify| inotify.add_watch(&path).ok());
+        match descriptor {
+            Some(descriptor) => watches
+                .descriptors
+                .entry(descriptor)
+                .or_default()
+                .push(path),
+            None => {
+                let listing = list_dir(&self.file_system, &path);
+                watches.polled.insert(path, listing);
+            }
+        }
+    }
+
+    fn unwatch(&self, watches: &mut Watches, path: &Path) {
+        watches.polled.remove(path);
+        let descriptor = watches
+            .descriptors
+            .iter()
+            .find(|(_, paths)| paths.iter().any(|watched| watched == path))
+            .map(|(descriptor, _)| *descriptor);
+        if let Some(descriptor) = descriptor {
+            let paths = watches.descriptors.get_mut(&descriptor).unwrap();
+            paths.retain(|watched| watched != path);
+            if paths.is_empty() {
+                watches.descriptors.remove(&descriptor);
+                if let Some(inotify) = self.inotify.as_ref() {
+                    inotify.remove_watch(descriptor);
+                }
+            }
+        }
+    }
+}
+
+/// Waits for inotify events on a worker thread and reloads the tabs of the
+/// changed directories once the events calm down.
+fn read_events(
+    inotify: Arc<Inotify>,
+    watches: Arc<Mutex<Watches>>,
+    sender: Sender<FileManagerActions>,
+) {
+    thread::spawn(move || loop {
+        let mut events = match inotify.read_events(None) {
+            Ok(events) => events,
+            Err(_) => return,
+        };
+        let first_event = Instant::now();
+        while first_event.elapsed() < MAX_DELAY {
+            match inotify.read_events(Some(DEBOUNCE)) {
+                Ok(more_events) if !more_events.is_empty() => events.extend(more_events),
+                _ => break,
+            }
+        }
+
+        let mut watches = watches.lock().unwrap();
+        let mut changed: Vec<PathBuf> = Vec::new();
+        let mut removed: Vec<i32> = Vec::new();
+        for event in events {
+            match event {
+                // Events were lost, anything may have changed.
+                InotifyEvent::Overflow => changed.extend(watches.sides.keys().cloned()),
+                InotifyEvent::Changed { descriptor } => {
+                    if let Some(paths) = watches.descriptors.get(&descriptor) {
+                        changed.extend(paths.iter().cloned());
+                    }
+                }
+                InotifyEvent::Removed { descriptor } => {
+                    if let Some(paths) = watches.descriptors.get(&descriptor) {
+                        changed.extend(paths.iter().cloned());
+                        removed.push(descriptor);
+                    }
+                }
+            }
+        }
+        changed.sort();
+        changed.dedup();
+        if !send_reloads(&watches, &changed, &sender) {
+            return;
+        }
+        // The directory is gone, the next update of the watches polls it
+        // until it comes back.
+        for descriptor in removed {
+            for path in watches.descriptors.remove(&descriptor).unwrap_or_default() {
+                watches.sides.remove(&path);
+            }
+        }
+    });
+}
+
+/// Lists the polled directories on a worker thread and reloads the tabs of
+/// the ones whose listing changed.
+fn poll_dirs<TFileSystem: Clone + FileSystem + Send + 'static>(
+    file_system: TFileSystem,
+    watches: Arc<Mutex<Watches>>,
+    sender: Sender<FileManagerActions>,
+) {
+    thread::spawn(move || loop {
+        thread::sleep(POLL_INTERVAL);
+        let paths: Vec<PathBuf> = watches.lock().unwrap().polled.keys().cloned().collect();
+        let listings: Vec<(PathBuf, Listing)> = paths
+            .into_iter()
+            .map(|path| {
+                let listing = list_dir(&file_system, &path);
+                (path, listing)
+            })
+            .collect();
+
+        let mut watches = watches.lock().unwrap();
+        let mut changed = Vec::new();
+        for (path, listing) in listings {
+            // Skips directories no tab shows anymore.
+            if let Some(last_listing) = watches.polled.get_mut(&path) {
+                if *last_listing != listing {
+                    *last_listing = listing;
+                    changed.push(path);
+                }
+            }
+        }
+        if !send_reloads(&watches, &changed, &sender) {
+            return;
+        }
+    });
+}
+
+/// Returns `false` once the application is gone.
+fn send_reloads(watches: &Watches, paths: &[PathBuf], sender: &Sender<FileManagerActions>) -> bool {
+    for path in paths {
+        for side in watches.sides.get(path).into_iter().flatten() {
+            let action = FileManagerActions::Tab(TabAction::ReloadTab {
+                panel_side: *side,
+                path: path.clone(),
+            });
+            if sender.send(action).is_err() {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+fn list_dir<TFileSystem: FileSystem>(file_system: &TFileSystem, path: &Path) -> Listing {
+    let mut listing: Vec<(PathBuf, SystemTime, u64)> = file_system
+        .read_dir(path)
+        .ok()?
+        .into_iter()
+        .filter_map(|child| {
+            let metadata = file_system.metadata(&child).ok()?;
+            Some((child, metadata.modified, metadata.size))
+        })
+        .collect();
+    listing.sort();
+    Some(listing)
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use std::{
+        ffi::CString,
+        io, mem,
+        os::unix::{ffi::OsStrExt, io::RawFd},
+        path::Path,
+        ptr,
+        time::Duration,
+    };
+
+    /// Creations, deletions, renames and modifications of the items of a
+    /// directory and of the directory itself.
+    const WATCH_MASK: u32 = libc::IN_CREATE
+        | libc::IN_DELETE
+        | libc::IN_MOVED_FROM
+        | libc::IN_MOVED_TO
+        | libc::IN_MODIFY
+        | libc::IN_ATTRIB
+        | libc::IN_DELETE_SELF
+        | libc::IN_MOVE_SELF;
+    /// Room for several events with the longest names.
+    const BUFFER_SIZE: usize = 16 * 1024;
+
+    pub enum InotifyEvent {
+        Changed {
+            descriptor: i32,
+        },
+        /// The watch ended because its directory was deleted or unmounted.
+        Removed {
+            descriptor: i32,
+        },
+        Overflow,
+    }
+
+    pub struct Inotify {
+        fd: RawFd,
+    }
+
+    impl Inotify {
+        pub fn new() -> io::Result<Self> {
+            let fd = unsafe { libc::inotify_init1(libc::IN_CLOEXEC) };
+            if fd < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(Inotify { fd })
+            }
+        }
+
+        pub fn add_watch(&self, path: &Path) -> io::Result<i32> {
+            let path = CString::new(path.as_os_str().as_bytes())?;
+            let descriptor = unsafe { libc::inotify_add_watch(self.fd, path.as_ptr(), WATCH_MASK) };
+            if descriptor < 0 {
+                Err(io::Error::last_os_error())
+            } else {
+                Ok(descriptor)
+            }
+        }
+
+        pub fn remove_watch(&self, descriptor: i32) {
+            unsafe { libc::inotify_rm_watch(self.fd, descriptor) };
+        }
+
+        /// Waits for events, at most `timeout` when given, and returns them.
+        pub fn read_events(&self, timeout: Option<Duration>) -> io::Result<Vec<InotifyEvent>> {
+            if let Some(timeout) = timeout {
+                let mut poll_fd = libc::pollfd {
+                    fd: self.fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let ready = unsafe { libc::poll(&mut poll_fd, 1, timeout.as_millis() as i32) };
+                if ready == 0 {
+                    return Ok(Vec::new());
+                }
+            }
+
+            let mut buffer = vec![0u8; BUFFER_SIZE];
+            let read = unsafe {
+                libc::read(
+                    self.fd,
+                    buffer.as_mut_ptr() as *mut libc::c_void,
+                    buffer.len(),
+                )
+            };
+            if read < 0 {
+                let err = io::Error::last_os_error();
+                return match err.kind() {
+                    io::ErrorKind::Interrupted => Ok(Vec::new()),
+                    _ => Err(err),
+                };
+            }
+
+            let header_size = mem::size_of::<libc::inotify_event>();
+            let mut events = Vec::new();
+            let mut offset = 0;
+            while offset + header_size <= read as usize {
+                let event = unsafe {
+                    ptr::read_unaligned(buffer.as_ptr().add(offset) as *const libc::inotify_event)
+                };
+                events.push(if event.mask & libc::IN_Q_OVERFLOW != 0 {
+                    InotifyEvent::Overflow
+                } else if event.mask & libc::IN_IGNORED != 0 {
+                    InotifyEvent::Removed {
+                        descriptor: event.wd,
+                    }
+                } else {
+                    InotifyEvent::Changed {
+                        descriptor: event.wd,
+                    }
+                });
+                offset += header_size + event.len as usize;
+            }
+            Ok(events)
+        }
+    }
+
+    impl Drop for Inotify {
+        fn drop(&mut self) {
+            unsafe { libc::close(self.fd) };
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod inotify {
+    use std::{io, path::Path, time::Duration};
+
+    pub enum InotifyEvent {
+        Changed { descriptor: i32 },
+        Removed { descriptor: i32 },
+        Overflow,
+    }
+
+    /// Stands in where inotify doesn't exist, every directory is polled.
+    pub struct Inotify;
+
+    impl Inotify {
+        pub fn new() -> io::Result<Self> {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "inotify is only available on Linux",
+            ))
+        }
+
+        pub fn add_watch(&self, _path: &Path) -> io::Result<i32> {
+            Err(io::Error::new(
+                io::ErrorKind::Other,
+                "inotify is only available on Linux",
+            ))
+        }
+
+        pub fn remove_watch(&self, _descriptor: i32) {}
+
+        pub fn read_events(&self, _timeout: Option<Duration>) -> io::Result<Vec<InotifyEvent>> {
+            Ok(Vec::new())
+        }
+    }
+}
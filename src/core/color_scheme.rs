@@ -45,6 +45,10 @@ pub struct ColorScheme {
     pub normal_link_foreground: Color,
     pub cursor_link_foreground: Color,
     pub select_link_foreground: Color,
+
+    pub compare_unique_foreground: Color,
+    pub compare_newer_foreground: Color,
+    pub compare_differs_foreground: Color,
 }
 
 impl ColorScheme {
@@ -88,6 +92,9 @@ impl ColorScheme {
         normal_link_foreground: Color,
         cursor_link_foreground: Color,
         select_link_foreground: Color,
+        compare_unique_foreground: Color,
+        compare_newer_foreground: Color,
+        compare_differs_foreground: Color,
     ) -> Self {
         Self {
             foreground,
@@ -129,6 +136,9 @@ impl ColorScheme {
             normal_link_foreground,
             cursor_link_foreground,
             select_link_foreground,
+            compare_unique_foreground,
+            compare_newer_foreground,
+            compare_differs_foreground,
         }
     }
 
@@ -258,6 +268,16 @@ impl ColorScheme {
         if let Some(item) = cfg.get("select_link_foreground") {
             self.select_link_foreground = map_color(&item);
         }
+
+        if let Some(item) = cfg.get("compare_unique_foreground") {
+            self.compare_unique_foreground = map_color(item);
+        }
+        if let Some(item) = cfg.get("compare_newer_foreground") {
+            self.compare_newer_foreground = map_color(item);
+        }
+        if let Some(item) = cfg.get("compare_differs_foreground") {
+            self.compare_differs_foreground = map_color(item);
+        }
     }
 }
 
@@ -333,6 +353,9 @@ impl Default for ColorScheme {
             Color::Gray,
             Color::White,
             Color::Black,
+            Color::LightCyan,
+            Color::LightGreen,
+            Color::LightYellow,
         )
     }
 }
@@ -3,12 +3,60 @@ use toml::Value;
 use super::color_scheme::ColorScheme;
 pub use super::color_scheme::ColorsFiles;
 
+/// How the panel filter matches names against the typed phrase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterMode {
+    /// Names containing the phrase, in the listing order.
+    Substring,
+    /// Names containing the characters of the phrase in order, best matches
+    /// first.
+    Fuzzy,
+}
+
+/// A column of the detail view.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    Name,
+    Size,
+    Modified,
+    Permissions,
+    /// Owner and group, as `user:group`.
+    Owner,
+    Inode,
+    Links,
+    /// Where a symlink points to, empty for the other items.
+    Target,
+}
+
+impl Column {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(Column::Name),
+            "size" => Some(Column::Size),
+            "mtime" => Some(Column::Modified),
+            "permissions" | "perms" => Some(Column::Permissions),
+            "owner" => Some(Column::Owner),
+            "inode" => Some(Column::Inode),
+            "links" => Some(Column::Links),
+            "target" => Some(Column::Target),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CoreConfig {
     pub tick_rate: u64,
     pub color_scheme: ColorScheme,
     pub list_arrow: String,
     pub colors_files: ColorsFiles,
+    pub dereference_symlinks: bool,
+    pub copy_special_files: bool,
+    pub scan_dir_sizes: bool,
+    pub filter_mode: FilterMode,
+    /// Whether new tabs start in the detail view.
+    pub detail_view: bool,
+    pub columns: Vec<Column>,
 }
 
 impl Default for CoreConfig {
@@ -18,12 +66,25 @@ impl Default for CoreConfig {
             color_scheme: ColorScheme::default(),
             list_arrow: "".to_string(),
             colors_files: ColorsFiles::default(),
+            dereference_symlinks: false,
+            copy_special_files: true,
+            scan_dir_sizes: false,
+            filter_mode: FilterMode::Fuzzy,
+            detail_view: false,
+            columns: vec![
+                Column::Name,
+                Column::Size,
+                Column::Modified,
+                Column::Permissions,
+                Column::Owner,
+            ],
         }
     }
 }
 
 impl CoreConfig {
-    pub fn update_from_file(&mut self, cfg: &Value) {
+    /// Entries which can't be used are described in `errors`.
+    pub fn update_from_file(&mut self, cfg: &Value, errors: &mut Vec<String>) {
         if let Some(core) = cfg.get("core") {
             if let Value::Table(core) = core {
                 if let Some(tick_rate) = core.get("tick_rate") {
@@ -37,6 +98,56 @@ impl CoreConfig {
                         self.list_arrow = list_arrow.clone();
                     }
                 }
+
+                if let Some(dereference_symlinks) = core.get("dereference_symlinks") {
+                    if let Value::Boolean(dereference_symlinks) = dereference_symlinks {
+                        self.dereference_symlinks = *dereference_symlinks;
+                    }
+                }
+
+                if let Some(copy_special_files) = core.get("copy_special_files") {
+                    if let Value::Boolean(copy_special_files) = copy_special_files {
+                        self.copy_special_files = *copy_special_files;
+                    }
+                }
+
+                if let Some(scan_dir_sizes) = core.get("scan_dir_sizes") {
+                    if let Value::Boolean(scan_dir_sizes) = scan_dir_sizes {
+                        self.scan_dir_sizes = *scan_dir_sizes;
+                    }
+                }
+
+                if let Some(filter_mode) = core.get("filter_mode") {
+                    if let Value::String(filter_mode) = filter_mode {
+                        match filter_mode.to_lowercase().as_str() {
+                            "fuzzy" => self.filter_mode = FilterMode::Fuzzy,
+                            "substring" => self.filter_mode = FilterMode::Substring,
+                            _ => {}
+                        }
+                    }
+                }
+
+                if let Some(detail_view) = core.get("detail_view") {
+                    if let Value::Boolean(detail_view) = detail_view {
+                        self.detail_view = *detail_view;
+                    }
+                }
+
+                if let Some(columns) = core.get("columns") {
+                    if let Value::Array(columns) = columns {
+                        let mut parsed = Vec::new();
+                        for column in columns {
+                            match column
+                                .as_str()
+                                .and_then(|name| Column::parse(&name.to_lowercase()))
+                            {
+                                Some(column) => parsed.push(column),
+                                None => errors.push(format!("unknown column {}", column)),
+                            }
+                        }
+                        self.columns = parsed;
+                    }
+                }
             }
         }
 
@@ -1,3 +1,5 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+
 pub struct Store<TState, TAction>
 where
     TState: Default + Clone,
@@ -8,6 +10,8 @@ where
     root_reducer: RootReducer<TState, TAction>,
     listeners: Vec<Listener<TState>>,
     middlewares: Vec<Middleware<TState, TAction>>,
+    action_sender: Sender<TAction>,
+    action_receiver: Receiver<TAction>,
 }
 
 impl<TState, TAction> Store<TState, TAction>
@@ -20,12 +24,15 @@ where
     }
 
     pub fn with_state(root_reducer: RootReducer<TState, TAction>, state: TState) -> Self {
+        let (action_sender, action_receiver) = channel();
         Store {
             is_dirty: false,
             state,
             root_reducer,
             listeners: Vec::new(),
             middlewares: Vec::new(),
+            action_sender,
+            action_receiver,
         }
     }
 
@@ -54,6 +61,18 @@ where
         self.mark_as_dirty();
     }
 
+    /// Returns a sender which lets other threads queue actions for the store.
+    pub fn action_sender(&self) -> Sender<TAction> {
+        self.action_sender.clone()
+    }
+
+    /// Dispatches the actions queued through `action_sender`.
+    pub fn dispatch_queued(&mut self) {
+        while let Ok(action) = self.action_receiver.try_recv() {
+            self.dispatch(action);
+        }
+    }
+
     pub fn register_listener(&mut self, listener: Listener<TState>) {
         self.listeners.push(listener);
     }
@@ -4,16 +4,25 @@ extern crate lazy_static;
 use crate::core::events::Event;
 use crate::core::ui::component::Component;
 use crate::core::{events::EventQueue, store::Store};
-use std::{error::Error, io::stdout, process::Command};
+use std::{
+    error::Error,
+    io::{self, stdout},
+    path::PathBuf,
+    process::Command,
+};
 
 use app::{
     actions::FileManagerActions,
     components::root::RootComponent,
     config::Config,
     file_system::PhysicalFileSystem,
-    middlewares::{dir_middleware, symlink_middleware},
+    middlewares::{
+        compare_middleware, dir_middleware, dir_size_middleware, find_middleware, grep_middleware,
+        job_middleware, listing_middleware, symlink_middleware, sync_middleware,
+    },
     reducers::root_reducer,
-    state::AppState,
+    state::{AppError, AppState, FileOperation},
+    watcher::DirWatcher,
 };
 
 use crossterm::{
@@ -57,12 +66,22 @@ fn main() -> Result<(), Box<dyn Error>> {
     store.dispatch(FileManagerActions::App(app::actions::AppAction::FocusLeft));
     store.register_middleware(symlink_middleware);
     store.register_middleware(dir_middleware);
+    store.register_middleware(job_middleware);
+    store.register_middleware(dir_size_middleware);
+    store.register_middleware(compare_middleware);
+    store.register_middleware(sync_middleware);
+    store.register_middleware(find_middleware);
+    store.register_middleware(grep_middleware);
+    store.register_middleware(listing_middleware);
     root_component.on_init(&store);
+    let dir_watcher = DirWatcher::start(store.get_state().file_system, store.action_sender());
 
     loop {
+        store.dispatch_queued();
         terminal.draw(|f| root_component.render(f, None))?;
 
         let state = store.get_state();
+        dir_watcher.watch_tabs(&state);
 
         if let Ok(event) = event_queue.pool() {
             if let Event::Tick = event {
@@ -74,20 +93,25 @@ fn main() -> Result<(), Box<dyn Error>> {
 
         if let Some(program_desc) = state.child_program {
             event_queue.lock_event_read();
-            match Command::new(program_desc.program_name)
+            match Command::new(&program_desc.program_name)
                 .args(program_desc.args.as_slice())
                 .spawn()
             {
                 Ok(mut child) => {
-                    child.wait().expect("");
-                    store.dispatch(FileManagerActions::App(
-                        app::actions::AppAction::ChildProgramClosed,
-                    ));
+                    let error = match child.wait() {
+                        Ok(status) if status.success() => None,
+                        Ok(status) => Some(io::Error::other(format!("it ended with {}", status))),
+                        Err(err) => Some(err),
+                    };
+                    store.dispatch(child_program_closed(&program_desc.program_name, error));
                     terminal.clear()?;
                     terminal.draw(|f| root_component.render(f, None))?;
                     event_queue.unlock_event_read();
                 }
-                Err(_) => {}
+                Err(err) => {
+                    store.dispatch(child_program_closed(&program_desc.program_name, Some(err)));
+                    event_queue.unlock_event_read();
+                }
             };
         }
 
@@ -106,3 +130,15 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+fn child_program_closed(program_name: &str, error: Option<io::Error>) -> FileManagerActions {
+    FileManagerActions::App(app::actions::AppAction::ChildProgramClosed {
+        error: error.map(|err| {
+            AppError::new(
+                FileOperation::RunProgram,
+                vec![PathBuf::from(program_name)],
+                &err,
+            )
+        }),
+    })
+}